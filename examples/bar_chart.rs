@@ -0,0 +1,53 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use druid::widget::SizedBox;
+use druid::{theme, AppLauncher, Color, LocalizedString, Widget, WindowDesc};
+
+use birog::charts::bar::{Bar, BarChartData, HorizontalBarChart};
+
+fn main() {
+  let window = WindowDesc::new(ui_builder)
+    .window_size((800., 500.))
+    .title(LocalizedString::new("bar-demo-window-title").with_placeholder("Bar Chart"));
+
+  AppLauncher::with_window(window)
+    .configure_env(|env, _| {
+      env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0x1F, 0x24, 0x30),
+      );
+      env.set(theme::FOREGROUND_DARK, Color::rgb8(0xCB, 0xCC, 0xC6));
+    })
+    .use_simple_logger()
+    .launch(data_builder())
+    .expect("launch failed");
+}
+
+fn ui_builder() -> impl Widget<BarChartData<f64>> {
+  SizedBox::new(HorizontalBarChart::new())
+    .expand_height()
+    .expand_width()
+}
+
+fn data_builder() -> BarChartData<f64> {
+  BarChartData::new(Color::rgb8(0x73, 0xD0, 0xFF))
+    .with_title("Population (millions)")
+    .with_bar(Bar::new("China", 1402.1))
+    .with_bar(Bar::new("India", 1380.0))
+    .with_bar(Bar::new("United States", 329.5))
+    .with_bar(Bar::new("Indonesia", 273.5))
+    .with_bar(Bar::new("Pakistan", 220.9))
+    .with_bar(Bar::new("Brazil", 212.6))
+}
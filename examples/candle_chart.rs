@@ -0,0 +1,65 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use druid::widget::SizedBox;
+use druid::{theme, AppLauncher, Color, LocalizedString, Widget, WindowDesc};
+
+use birog::charts::candle::{CandleChart, CandleChartData};
+
+fn main() {
+  let window = WindowDesc::new(ui_builder)
+    .window_size((800., 500.))
+    .title(LocalizedString::new("candle-demo-window-title").with_placeholder("Candle Chart"));
+
+  AppLauncher::with_window(window)
+    .configure_env(|env, _| {
+      env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0x1F, 0x24, 0x30),
+      );
+      env.set(theme::FOREGROUND_DARK, Color::rgb8(0xCB, 0xCC, 0xC6));
+    })
+    .use_simple_logger()
+    .launch(data_builder())
+    .expect("launch failed");
+}
+
+fn ui_builder() -> impl Widget<CandleChartData<i32, f64>> {
+  SizedBox::new(CandleChart::new())
+    .expand_height()
+    .expand_width()
+}
+
+fn data_builder() -> CandleChartData<i32, f64> {
+  let closes = [
+    34.15, 33.89, 33.23, 33.43, 32.72, 32.44, 32.26, 31.35, 31.23, 30.59, 30.45, 29.89, 29.28,
+    30.08, 30.49, 30.87, 30.54, 30.94, 31.57, 30.72, 31.2, 31.4, 30.91, 31.03, 31.24, 31.07, 31.03,
+    31.07, 30.56, 30.65,
+  ];
+
+  let mut open: f64 = 34.14;
+  let candles = closes
+    .iter()
+    .enumerate()
+    .map(|(day, &close)| {
+      let high = open.max(close) + 0.3;
+      let low = open.min(close) - 0.25;
+      let candle = (day as i32 + 1, open, high, low, close);
+      open = close;
+      candle
+    })
+    .collect();
+
+  CandleChartData::new(candles).with_title("Daily prices")
+}
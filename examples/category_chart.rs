@@ -0,0 +1,78 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Plots monthly values against the names of the months. Narrow the window to see the labels
+//! thin out to every other month.
+
+use druid::widget::SizedBox;
+use druid::{theme, AppLauncher, Color, LocalizedString, Widget, WindowDesc};
+
+use birog::charts::line::{Line, LineChart, LineChartData};
+
+const MONTHS: [&str; 12] = [
+  "January",
+  "February",
+  "March",
+  "April",
+  "May",
+  "June",
+  "July",
+  "August",
+  "September",
+  "October",
+  "November",
+  "December",
+];
+
+fn main() {
+  let window = WindowDesc::new(ui_builder)
+    .window_size((800., 500.))
+    .title(LocalizedString::new("category-demo-window-title").with_placeholder("Category Chart"));
+
+  AppLauncher::with_window(window)
+    .configure_env(|env, _| {
+      env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0x1F, 0x24, 0x30),
+      );
+      env.set(theme::FOREGROUND_DARK, Color::rgb8(0xCB, 0xCC, 0xC6));
+    })
+    .use_simple_logger()
+    .launch(data_builder())
+    .expect("launch failed");
+}
+
+fn ui_builder() -> impl Widget<LineChartData<i32, f64>> {
+  SizedBox::new(LineChart::new().with_legend(true))
+    .expand_height()
+    .expand_width()
+}
+
+fn data_builder() -> LineChartData<i32, f64> {
+  // Average temperatures (°C) of each month
+  let oslo = [
+    -4.3, -4.0, -0.2, 4.5, 10.8, 15.2, 16.4, 15.2, 10.8, 6.3, 0.7, -3.1,
+  ];
+  let madrid = [
+    6.3, 7.9, 11.2, 12.9, 16.7, 22.2, 25.6, 25.1, 20.9, 15.1, 9.9, 6.9,
+  ];
+
+  LineChartData::new()
+    .with_title("Average temperature")
+    .with_categories(MONTHS.iter().copied())
+    .with_line(Line::from_ys(oslo.iter().copied(), Color::rgb8(0x73, 0xD0, 0xFF)).with_name("Oslo"))
+    .with_line(
+      Line::from_ys(madrid.iter().copied(), Color::rgb8(0xFF, 0xA6, 0x57)).with_name("Madrid"),
+    )
+}
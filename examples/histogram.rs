@@ -0,0 +1,65 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Counts two sets of samples into shared bins, stacked on top of each other.
+
+use druid::widget::SizedBox;
+use druid::{theme, AppLauncher, Color, LocalizedString, Widget, WindowDesc};
+
+use birog::charts::histogram::{Histogram, HistogramData, HistogramMode};
+
+fn main() {
+  let window = WindowDesc::new(ui_builder)
+    .window_size((800., 500.))
+    .title(LocalizedString::new("histogram-demo-window-title").with_placeholder("Histogram"));
+
+  AppLauncher::with_window(window)
+    .configure_env(|env, _| {
+      env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0x1F, 0x24, 0x30),
+      );
+      env.set(theme::FOREGROUND_DARK, Color::rgb8(0xCB, 0xCC, 0xC6));
+    })
+    .use_simple_logger()
+    .launch(data_builder())
+    .expect("launch failed");
+}
+
+fn ui_builder() -> impl Widget<HistogramData> {
+  SizedBox::new(
+    Histogram::new()
+      .with_bin_width(5.0)
+      .with_mode(HistogramMode::Stacked),
+  )
+  .expand_height()
+  .expand_width()
+}
+
+/// Spreads `count` samples around `center`, as a sum of evenly spaced waves, so the example
+/// needs no random numbers.
+fn samples(count: usize, center: f64, spread: f64) -> Vec<f64> {
+  (0..count)
+    .map(|i| {
+      let t = i as f64;
+      center + spread * ((t * 0.37).sin() + (t * 0.11).cos() * 0.6 + (t * 1.7).sin() * 0.4)
+    })
+    .collect()
+}
+
+fn data_builder() -> HistogramData {
+  HistogramData::new(samples(400, 42.0, 9.0), Color::rgb8(0x73, 0xD0, 0xFF))
+    .with_title("Response time (ms)")
+    .with_samples(samples(250, 60.0, 7.0), Color::rgb8(0xFF, 0xA6, 0x57))
+}
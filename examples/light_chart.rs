@@ -0,0 +1,69 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A line chart on a light background. The chart picks its axis, grid and tooltip colors from
+//! `ChartTheme::light`, so the rest of the druid theme is left alone.
+
+use druid::widget::SizedBox;
+use druid::{theme, AppLauncher, Color, LocalizedString, Widget, WindowDesc};
+
+use birog::charts::line::{Line, LineChart, LineChartData};
+use birog::charts::theme::ChartTheme;
+
+fn main() {
+  let window = WindowDesc::new(ui_builder)
+    .window_size((800., 500.))
+    .title(LocalizedString::new("light-demo-window-title").with_placeholder("Light Chart"));
+
+  AppLauncher::with_window(window)
+    .configure_env(|env, _| {
+      env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0xFA, 0xFA, 0xF7),
+      );
+    })
+    .use_simple_logger()
+    .launch(data_builder())
+    .expect("launch failed");
+}
+
+fn ui_builder() -> impl Widget<LineChartData<i32, f64>> {
+  SizedBox::new(
+    LineChart::new()
+      .with_legend(true)
+      .with_theme(ChartTheme::light()),
+  )
+  .expand_height()
+  .expand_width()
+}
+
+fn data_builder() -> LineChartData<i32, f64> {
+  // Hours of daylight on the 21st of each month
+  let reykjavik = [
+    5.0, 8.3, 12.1, 15.9, 19.6, 21.1, 19.3, 15.5, 12.2, 8.5, 5.1, 4.1,
+  ];
+  let lisbon = [
+    9.9, 10.9, 12.1, 13.3, 14.3, 14.8, 14.3, 13.3, 12.1, 10.9, 9.9, 9.4,
+  ];
+
+  LineChartData::new()
+    .with_title("Daylight hours")
+    .with_line(
+      Line::from_ys(reykjavik.iter().copied(), Color::rgb8(0x1F, 0x77, 0xB4))
+        .with_name("Reykjavik"),
+    )
+    .with_line(
+      Line::from_ys(lisbon.iter().copied(), Color::rgb8(0xD6, 0x27, 0x28)).with_name("Lisbon"),
+    )
+}
@@ -17,7 +17,7 @@ use druid::{
   theme, AppLauncher, Color, Data, Lens, LocalizedString, Widget, WidgetExt, WindowDesc,
 };
 
-use birog::charts::line::{Line, LineChart, LineChartData};
+use birog::charts::line::{AverageKind, LeadingPoints, Line, LineChart, LineChartData};
 use std::sync::Arc;
 
 #[derive(Clone, Data, Lens)]
@@ -54,7 +54,7 @@ fn ui_builder() -> impl Widget<AppData> {
         .expand_height(),
     )
     .with_flex_child(
-      SizedBox::new(LineChart::new().lens(AppData::chart_data))
+      SizedBox::new(LineChart::new().with_legend(true).lens(AppData::chart_data))
         .expand_height()
         .expand_width(),
       1.0
@@ -126,25 +126,33 @@ fn data_builder() -> AppData {
     8.47, 8.32, 8.26, 8.37, 8.41, 8.33, 8.37, 8.23, 8.19,
   ];
 
-  let points_a: Vec<(i32, f64)> = series_a
-    .iter()
-    .take(200)
-    .enumerate()
-    .map(|(idx, price)| (-(idx as i32), *price))
-    .collect();
 
-  let points_b: Vec<(i32, f64)> = series_b
-    .iter()
-    .take(200)
-    .enumerate()
-    .map(|(idx, price)| (-(idx as i32), *price))
-    .collect();
+
+  let line_a = Line::from_iter(
+    series_a.iter().take(200).enumerate().map(|(idx, price)| (-(idx as i32), *price)),
+    Color::rgb8(0x73, 0xD0, 0xFF),
+  )
+  .with_name("A");
+  let line_b = Line::from_iter(
+    series_b.iter().take(200).enumerate().map(|(idx, price)| (-(idx as i32), *price)),
+    Color::rgb8(0xF2, 0x87, 0x79),
+  )
+  .with_name("B");
+  let average_a = line_a
+    .moving_average(
+      20,
+      AverageKind::Simple,
+      LeadingPoints::Omit,
+      Color::rgb8(0xFF, 0xD5, 0x80),
+    )
+    .with_name("A (MA 20)");
 
   AppData {
     chart_data: LineChartData::new()
       .with_title("The quick brown fox jumped over the lazy dog.")
-      .with_line(Line::new(points_a, Color::rgb8(0x73, 0xD0, 0xFF)))
-      .with_line(Line::new(points_b, Color::rgb8(0xF2, 0x87, 0x79))),
-    left: Arc::new(vec!["A".to_string(), "B".to_string()]),
+      .with_line(line_a)
+      .with_line(line_b)
+      .with_line(average_a),
+    left: Arc::new(vec!["A".to_string(), "B".to_string(), "A (MA 20)".to_string()]),
   }
 }
@@ -0,0 +1,53 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use druid::widget::SizedBox;
+use druid::{theme, AppLauncher, Color, LocalizedString, Widget, WindowDesc};
+
+use birog::charts::pie::{PieChart, PieChartData, Slice};
+
+fn main() {
+  let window = WindowDesc::new(ui_builder)
+    .window_size((800., 500.))
+    .title(LocalizedString::new("pie-demo-window-title").with_placeholder("Pie Chart"));
+
+  AppLauncher::with_window(window)
+    .configure_env(|env, _| {
+      env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0x1F, 0x24, 0x30),
+      );
+      env.set(theme::FOREGROUND_DARK, Color::rgb8(0xCB, 0xCC, 0xC6));
+    })
+    .use_simple_logger()
+    .launch(data_builder())
+    .expect("launch failed");
+}
+
+fn ui_builder() -> impl Widget<PieChartData<f64>> {
+  SizedBox::new(PieChart::new().with_inner_radius(0.5))
+    .expand_height()
+    .expand_width()
+}
+
+fn data_builder() -> PieChartData<f64> {
+  PieChartData::new()
+    .with_title("Energy sources")
+    .with_slice(Slice::new("Oil", 31.2, Color::rgb8(0x73, 0xD0, 0xFF)))
+    .with_slice(Slice::new("Coal", 27.2, Color::rgb8(0xFF, 0xA6, 0x59)))
+    .with_slice(Slice::new("Gas", 24.7, Color::rgb8(0xBA, 0xE6, 0x7E)))
+    .with_slice(Slice::new("Hydro", 6.9, Color::rgb8(0xF2, 0x87, 0xB3)))
+    .with_slice(Slice::new("Nuclear", 4.3, Color::rgb8(0xD4, 0xBF, 0xFF)))
+    .with_slice(Slice::new("Renewables", 5.7, Color::rgb8(0x95, 0xE6, 0xCB)))
+}
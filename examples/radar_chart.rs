@@ -0,0 +1,64 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use druid::widget::SizedBox;
+use druid::{theme, AppLauncher, Color, LocalizedString, Widget, WindowDesc};
+
+use birog::charts::radar::{RadarChart, RadarChartData, RadarScale, RadarSeries};
+
+fn main() {
+  let window = WindowDesc::new(ui_builder)
+    .window_size((800., 500.))
+    .title(LocalizedString::new("radar-demo-window-title").with_placeholder("Radar Chart"));
+
+  AppLauncher::with_window(window)
+    .configure_env(|env, _| {
+      env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0x1F, 0x24, 0x30),
+      );
+      env.set(theme::FOREGROUND_DARK, Color::rgb8(0xCB, 0xCC, 0xC6));
+    })
+    .use_simple_logger()
+    .launch(data_builder())
+    .expect("launch failed");
+}
+
+fn ui_builder() -> impl Widget<RadarChartData<f64>> {
+  SizedBox::new(RadarChart::new().with_scale(RadarScale::PerAxis))
+    .expand_height()
+    .expand_width()
+}
+
+fn data_builder() -> RadarChartData<f64> {
+  let axes = ["Price", "Range (km)", "Speed (km/h)", "Seats", "Cargo (l)"];
+
+  RadarChartData::new(axes.iter().map(|axis| axis.to_string()).collect())
+    .with_title("Car comparison")
+    .with_series(RadarSeries::new(
+      "Hatchback",
+      vec![21000.0, 450.0, 180.0, 5.0, 380.0],
+      Color::rgb8(0x73, 0xD0, 0xFF),
+    ))
+    .with_series(RadarSeries::new(
+      "Van",
+      vec![34000.0, 600.0, 160.0, 8.0, 1100.0],
+      Color::rgb8(0xFF, 0xA6, 0x59),
+    ))
+    .with_series(RadarSeries::new(
+      "Coupe",
+      vec![52000.0, 380.0, 250.0, 4.0, 270.0],
+      Color::rgb8(0xBA, 0xE6, 0x7E),
+    ))
+}
@@ -0,0 +1,64 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use druid::widget::SizedBox;
+use druid::{theme, AppLauncher, Color, LocalizedString, Widget, WindowDesc};
+
+use birog::charts::scatter::{ScatterChart, ScatterChartData, Series};
+
+fn main() {
+  let window = WindowDesc::new(ui_builder)
+    .window_size((800., 500.))
+    .title(LocalizedString::new("scatter-demo-window-title").with_placeholder("Scatter Chart"));
+
+  AppLauncher::with_window(window)
+    .configure_env(|env, _| {
+      env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0x1F, 0x24, 0x30),
+      );
+      env.set(theme::FOREGROUND_DARK, Color::rgb8(0xCB, 0xCC, 0xC6));
+    })
+    .use_simple_logger()
+    .launch(data_builder())
+    .expect("launch failed");
+}
+
+fn ui_builder() -> impl Widget<ScatterChartData<f64, f64>> {
+  SizedBox::new(ScatterChart::new())
+    .expand_height()
+    .expand_width()
+}
+
+fn data_builder() -> ScatterChartData<f64, f64> {
+  // Height (cm) and weight (kg) samples
+  let heights = vec![
+    151.0, 174.0, 138.0, 186.0, 128.0, 136.0, 179.0, 163.0, 152.0, 131.0, 165.0, 171.0, 158.0,
+  ];
+  let weights = vec![
+    63.0, 81.0, 56.0, 91.0, 47.0, 57.0, 76.0, 72.0, 62.0, 48.0, 70.0, 77.0, 64.0,
+  ];
+
+  let points: Vec<(f64, f64)> = heights.into_iter().zip(weights).collect();
+
+  // Ages, drawn as the bubble radius
+  let ages = vec![
+    30.0, 42.0, 12.0, 51.0, 9.0, 14.0, 35.0, 27.0, 22.0, 10.0, 45.0, 38.0, 24.0,
+  ];
+  let sizes = ages.iter().map(|age| age / 3.0).collect();
+
+  ScatterChartData::new()
+    .with_title("Height x Weight")
+    .with_series(Series::new(points, Color::rgb8(0x73, 0xD0, 0xFF)).with_sizes(sizes))
+}
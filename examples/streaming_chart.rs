@@ -0,0 +1,105 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Streams points into a line chart from a timer. Each tick appends to the lines in place with
+//! `push_point`, so the chart only measures the new points instead of rebuilding the whole data.
+
+use std::time::Duration;
+
+use druid::widget::{Controller, SizedBox};
+use druid::{
+  theme, AppLauncher, Color, Env, Event, EventCtx, LocalizedString, TimerToken, Widget, WidgetExt,
+  WindowDesc,
+};
+
+use birog::charts::line::{Line, LineChart, LineChartData};
+
+const TICK: Duration = Duration::from_millis(100);
+
+/// Points drawn before the lines start over.
+const WINDOW: i32 = 200;
+
+/// Appends a sample to every line on each timer tick.
+struct Stream {
+  timer: TimerToken,
+  tick: i32,
+}
+
+impl<W: Widget<LineChartData<i32, f64>>> Controller<LineChartData<i32, f64>, W> for Stream {
+  fn event(
+    &mut self,
+    child: &mut W,
+    ctx: &mut EventCtx,
+    event: &Event,
+    data: &mut LineChartData<i32, f64>,
+    env: &Env,
+  ) {
+    match event {
+      Event::WindowConnected => self.timer = ctx.request_timer(TICK),
+      Event::Timer(token) if *token == self.timer => {
+        self.tick += 1;
+
+        // Start over once the window is full, rather than growing forever
+        if self.tick % WINDOW == 0 {
+          data.set_line_points(0, Vec::new());
+          data.set_line_points(1, Vec::new());
+        }
+
+        let x = f64::from(self.tick) / 10.0;
+        data.push_point(0, (self.tick, x.sin() * 10.0 + 20.0));
+        data.push_point(1, (self.tick, (x * 0.7).cos() * 6.0 + 12.0));
+
+        self.timer = ctx.request_timer(TICK);
+      }
+      _ => (),
+    }
+
+    child.event(ctx, event, data, env);
+  }
+}
+
+fn main() {
+  let window = WindowDesc::new(ui_builder)
+    .window_size((800., 500.))
+    .title(LocalizedString::new("streaming-demo-window-title").with_placeholder("Streaming Chart"));
+
+  AppLauncher::with_window(window)
+    .configure_env(|env, _| {
+      env.set(
+        theme::WINDOW_BACKGROUND_COLOR,
+        Color::rgb8(0x1F, 0x24, 0x30),
+      );
+      env.set(theme::FOREGROUND_DARK, Color::rgb8(0xCB, 0xCC, 0xC6));
+    })
+    .use_simple_logger()
+    .launch(data_builder())
+    .expect("launch failed");
+}
+
+fn ui_builder() -> impl Widget<LineChartData<i32, f64>> {
+  SizedBox::new(LineChart::new().with_legend(true))
+    .expand_height()
+    .expand_width()
+    .controller(Stream {
+      timer: TimerToken::INVALID,
+      tick: 0,
+    })
+}
+
+fn data_builder() -> LineChartData<i32, f64> {
+  LineChartData::new()
+    .with_title("Sensors")
+    .with_line(Line::new(Vec::new(), Color::rgb8(0x73, 0xD0, 0xFF)).with_name("Temperature"))
+    .with_line(Line::new(Vec::new(), Color::rgb8(0xFF, 0xA6, 0x57)).with_name("Humidity"))
+}
@@ -1,3 +1,4 @@
+use birog::charts::spark::Sparkline;
 use birog::table::Table;
 use druid::widget::{CrossAxisAlignment, Flex, Label};
 use druid::{AppLauncher, Data, Lens, LocalizedString, Widget, WidgetExt, WindowDesc};
@@ -10,9 +11,12 @@ struct AppData {
 
 #[derive(Clone, Data, Lens)]
 struct Person {
+  selected: bool,
   first_name: String,
   last_name: String,
   phone: String,
+  calls: Arc<Vec<f64>>,
+  notes: String,
 }
 
 fn main() {
@@ -28,8 +32,14 @@ fn main() {
 
 fn ui_builder() -> impl Widget<AppData> {
   Table::new()
+    .with_indexed_column(
+      Label::new("#"),
+      |index| Label::new(format!("{}", index + 1)),
+      40.0,
+    )
+    .with_checkbox_column(Label::new(""), Person::selected, 30.0)
     .with_column(
-      "First Name",
+      Label::new("First Name"),
       || {
         Flex::column()
           .with_child(Label::new(|person: &Person, _env: &_| {
@@ -43,15 +53,21 @@ fn ui_builder() -> impl Widget<AppData> {
       200.0,
     )
     .with_column(
-      "Last Name",
+      Label::new("Last Name"),
       || Label::new(|person: &Person, _env: &_| person.last_name.clone()),
       200.0,
     )
+    .with_editable_column(Label::new("Phone Number"), Person::phone, 120.0)
     .with_column(
-      "Phone Number",
-      || Label::new(|person: &Person, _env: &_| person.phone.clone()),
+      Label::new("Calls"),
+      || Sparkline::new().with_last_point(true).lens(Person::calls),
       100.0,
     )
+    .with_wrapped_column(Label::new("Notes"), |person| person.notes.clone(), 160.0)
+    .with_footer(Label::new(|people: &Arc<Vec<Person>>, _env: &_| {
+      let selected = people.iter().filter(|person| person.selected).count();
+      format!("{} people, {} selected", people.len(), selected)
+    }))
     .lens(AppData::data)
     .debug_paint_layout()
 }
@@ -60,19 +76,28 @@ fn data_builder() -> AppData {
   AppData {
     data: Arc::new(vec![
       Person {
+        selected: false,
         first_name: "John".to_string(),
         last_name: "Doe".to_string(),
         phone: "555-1234".to_string(),
+        calls: Arc::new(vec![3.0, 5.0, 4.0, 6.0, 8.0, 7.0]),
+        notes: "Prefers to be called in the morning, never on weekends".to_string(),
       },
       Person {
+        selected: true,
         first_name: "Jane".to_string(),
         last_name: "Smith".to_string(),
         phone: "555-0000".to_string(),
+        calls: Arc::new(vec![2.0, 2.0, 1.0, 3.0, 2.0, 1.0]),
+        notes: String::new(),
       },
       Person {
+        selected: false,
         first_name: "Jane".to_string(),
         last_name: "Smith".to_string(),
         phone: "555-0000".to_string(),
+        calls: Arc::new(vec![9.0, 7.0, 8.0, 5.0, 4.0, 4.0]),
+        notes: "Same name as the previous entry".to_string(),
       },
     ]),
   }
@@ -0,0 +1,2066 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Plot area layout and axis painting shared by the cartesian charts.
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+
+use druid::piet::{FontBuilder, PietTextLayout, Text, TextLayout, TextLayoutBuilder};
+use druid::widget::prelude::*;
+use druid::{kurbo::Affine, kurbo::BezPath, theme, Color, Insets, Point, Rect};
+use num_traits::{AsPrimitive, Num};
+
+use crate::charts::svg::{SvgDocument, TextAnchor};
+use crate::charts::theme::{self as chart_theme, ChartTheme};
+use crate::charts::time::{self, TimeUnit};
+use crate::charts::wilkinson;
+
+/// Foreground color of druid's default theme, used when rendering outside a widget tree.
+pub(crate) const DEFAULT_FOREGROUND: Color = Color::rgb8(0xbf, 0xbf, 0xbf);
+
+/// Size taken along the directions the constraints leave unbounded, as in a scroll or flex.
+const DEFAULT_SIZE: Size = Size::new(400.0, 300.0);
+
+/// Visibility and color of the gridlines drawn across the plot area.
+///
+/// Ticks and labels are drawn regardless of these settings.
+#[derive(Clone, Debug)]
+pub struct GridConfig {
+  /// Draw vertical lines at each X axis tick.
+  pub show_x: bool,
+  /// Draw horizontal lines at each Y axis tick.
+  pub show_y: bool,
+  /// Color of the gridlines, `None` to use `charts::theme::GRID_COLOR`.
+  pub color: Option<Color>,
+  /// Opacity applied on top of the gridline color.
+  pub alpha: f64,
+}
+
+/// Minor gridlines are drawn at this fraction of the major gridline opacity.
+const MINOR_GRID_ALPHA: f64 = 0.4;
+
+/// Gridlines of a time axis on a boundary coarser than its step, such as month starts among
+/// daily ticks, are drawn this many times more opaque than the others, and wider.
+const MAJOR_TIME_GRID_ALPHA: f64 = 2.5;
+const MAJOR_TIME_GRID_WIDTH: f64 = 2.0;
+
+/// Opacity, over the axis color, and width of the lines through zero.
+const ZERO_LINE_ALPHA: f64 = 0.5;
+const ZERO_LINE_WIDTH: f64 = 1.5;
+
+/// Time labels are wider than numbers, so they are spaced further apart.
+const MIN_TIME_LABEL_SPACING: f64 = 5.0;
+
+/// Multiples of each power of ten labeled on a log axis, when they fit.
+const LOG_STEPS: [f64; 3] = [1.0, 2.0, 5.0];
+
+/// Decades shown below the top of a log axis when the data reaches zero or below, which the
+/// axis can't show.
+const LOG_FALLBACK_DECADES: f64 = 3.0;
+
+/// Significant digits of values read off a log axis between its labels.
+const LOG_SIGNIFICANT_DIGITS: i32 = 3;
+
+/// Values this large or small are labeled in scientific notation on a log axis.
+const LOG_SCIENTIFIC_ABOVE: f64 = 1e6;
+const LOG_SCIENTIFIC_BELOW: f64 = 1e-3;
+
+/// Rotated X labels only need to clear each other across their lines, this many font sizes high.
+const ROTATED_LABEL_LINE: f64 = 1.5;
+
+/// Offset of the baseline of a rotated label from the line through its tick, in font sizes, so
+/// the text is centered on that line.
+const ROTATED_LABEL_BASELINE: f64 = 0.35;
+
+/// Sides of the plot on which the X axis tick labels are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum XAxisSide {
+  Bottom,
+  Top,
+  Both,
+}
+
+/// Sides of the plot on which the Y axis tick labels are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum YAxisSide {
+  Left,
+  Right,
+  Both,
+}
+
+/// Placement of the tick labels for both axes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisSides {
+  pub x: XAxisSide,
+  pub y: YAxisSide,
+}
+
+/// Layout and styling of the plot frame, common to every cartesian chart.
+pub(crate) struct AxisSettings {
+  pub font_size: f64,
+  pub padding_top: f64,
+  pub padding_bottom: f64,
+  pub padding_left: f64,
+  pub padding_right: f64,
+  /// Space left empty between the edges of the widget and everything the chart draws, title and
+  /// labels included.
+  pub margin: Insets,
+  pub header_height: f64,
+  pub footer_height: f64,
+  pub tick_length: f64,
+  pub grid: GridConfig,
+  /// Number of minor gridlines between consecutive major ticks.
+  pub minor_gridlines: usize,
+  pub axis_sides: AxisSides,
+  /// Fill of the plot area, `None` to leave the window background visible.
+  pub plot_background: Option<Color>,
+  /// Draw the X axis with its values decreasing from left to right.
+  pub reversed_x: bool,
+  /// Draw the Y axis with its values decreasing from bottom to top.
+  pub reversed_y: bool,
+  /// Show the Y values as percentages, followed by a percent sign.
+  pub percent_y: bool,
+  /// Show the values under the cursor in boxes at the ends of the crosshair.
+  pub cursor_readouts: bool,
+  /// Read the X values as seconds since the Unix epoch, and label them with UTC dates and times.
+  pub time_x: bool,
+  /// Counter-clockwise rotation of the X labels in degrees, from 0 (horizontal) to 90.
+  pub x_label_rotation: f64,
+  /// Space the Y values by their logarithm, with labels at 1, 2 and 5 times each power of ten.
+  pub log_y: bool,
+  /// Decimals of the numeric X labels and readouts, in place of the precision of the labels.
+  pub x_decimals: Option<usize>,
+  /// Decimals of the Y labels and readouts, in place of the precision of the labels. Log axes
+  /// keep formatting each label on its own.
+  pub y_decimals: Option<usize>,
+  /// Emphasize the lines at X = 0 and Y = 0 when the ranges cross zero.
+  pub zero_line: bool,
+  /// Draw the title, the plot frame, the ticks, the labels and the gridlines. Without them the
+  /// plot fills the whole size, with no padding.
+  pub chrome: bool,
+  /// Labels picked for the latest axes, so repainting at the same size and bounds, as every
+  /// cursor move does, skips the search for them.
+  pub label_cache: LabelCache,
+}
+
+/// Number of axes whose labels a `LabelCache` keeps: X, Y and secondary Y, with room to spare.
+const LABEL_CACHE_SIZE: usize = 6;
+
+/// The most recently picked labels, keyed by the range and the number of labels that fit. A
+/// change of bounds or size changes the key, so stale labels are never returned; they just age
+/// out of the cache.
+#[derive(Default)]
+pub(crate) struct LabelCache {
+  /// Most recently used first.
+  entries: RefCell<Vec<CachedLabels>>,
+}
+
+struct CachedLabels {
+  /// Bits of the minimum, maximum and number of labels, compared exactly.
+  key: (u64, u64, u64),
+  labels: Vec<f64>,
+  precision: usize,
+}
+
+/// Extremes of the plotted data and the precision of its Y values, before any snapping to the
+/// axis labels.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DataBounds {
+  pub min_x: f64,
+  pub max_x: f64,
+  pub min_y: f64,
+  pub max_y: f64,
+  pub precision_y: usize,
+}
+
+/// Plot area and axis scales resolved for a given widget size.
+///
+/// Everything painted by a chart is positioned from this, so the widget and the SVG export
+/// agree on where things go.
+#[derive(Clone, Debug)]
+pub struct ChartGeometry {
+  /// The widget less its margin, that the title, the plot and its labels are laid out in.
+  pub(crate) area: Rect,
+  pub(crate) plot: Rect,
+  pub(crate) header_height: f64,
+  pub(crate) min_x: f64,
+  pub(crate) max_x: f64,
+  pub(crate) min_y: f64,
+  pub(crate) max_y: f64,
+  pub(crate) proportion_x: f64,
+  pub(crate) proportion_y: f64,
+  pub(crate) x_axis: Vec<f64>,
+  pub(crate) x_axis_precision: usize,
+  pub(crate) y_axis: Vec<f64>,
+  pub(crate) y_axis_precision: usize,
+  pub(crate) reversed_x: bool,
+  pub(crate) reversed_y: bool,
+  pub(crate) percent_y: bool,
+  /// Step between the X labels when the X axis shows times.
+  pub(crate) time_x: Option<TimeUnit>,
+  /// Names of the whole X values from zero, empty when the X axis is numeric.
+  pub(crate) categories_x: Vec<String>,
+  /// The Y values are placed by their logarithm, between positive `min_y` and `max_y`.
+  pub(crate) log_y: bool,
+  /// Same plot and X scale with the Y scale of the secondary axis, when there is one.
+  pub(crate) secondary: Option<Box<ChartGeometry>>,
+}
+
+/// A tick label as drawn along an axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AxisLabel {
+  pub value: f64,
+  /// The value formatted as it is drawn.
+  pub text: String,
+  /// Pixel position of the tick along its axis: X for the X axis, Y for the Y axes.
+  pub position: f64,
+}
+
+/// A resolved numeric scale: its labels, and the range it spans once grown to include them.
+pub(crate) struct AxisScale {
+  pub labels: Vec<f64>,
+  pub precision: usize,
+  pub min: f64,
+  pub max: f64,
+  /// Pixels per unit along the axis.
+  pub proportion: f64,
+}
+
+impl XAxisSide {
+  pub(crate) fn top(self) -> bool {
+    self != XAxisSide::Bottom
+  }
+
+  pub(crate) fn bottom(self) -> bool {
+    self != XAxisSide::Top
+  }
+}
+
+impl YAxisSide {
+  pub(crate) fn left(self) -> bool {
+    self != YAxisSide::Right
+  }
+
+  pub(crate) fn right(self) -> bool {
+    self != YAxisSide::Left
+  }
+}
+
+impl Default for AxisSides {
+  fn default() -> Self {
+    Self {
+      x: XAxisSide::Both,
+      y: YAxisSide::Both,
+    }
+  }
+}
+
+impl Default for GridConfig {
+  fn default() -> Self {
+    Self {
+      show_x: true,
+      show_y: true,
+      color: None,
+      alpha: 0.1,
+    }
+  }
+}
+
+impl Default for AxisSettings {
+  fn default() -> Self {
+    Self {
+      font_size: 12.0,
+      padding_top: 40.0,
+      padding_bottom: 40.0,
+      padding_left: 40.0,
+      padding_right: 40.0,
+      margin: Insets::ZERO,
+      header_height: 40.0,
+      footer_height: 0.0,
+      tick_length: 5.0,
+      grid: GridConfig::default(),
+      minor_gridlines: 0,
+      axis_sides: AxisSides::default(),
+      plot_background: None,
+      reversed_x: false,
+      reversed_y: false,
+      percent_y: false,
+      cursor_readouts: true,
+      time_x: false,
+      x_label_rotation: 0.0,
+      log_y: false,
+      x_decimals: None,
+      y_decimals: None,
+      zero_line: true,
+      chrome: true,
+      label_cache: LabelCache::default(),
+    }
+  }
+}
+
+impl AxisSettings {
+  pub fn set_padding(&mut self, padding: f64) {
+    self.padding_top = padding;
+    self.padding_bottom = padding;
+    self.padding_left = padding;
+    self.padding_right = padding;
+  }
+
+  /// The part of a widget of the given size inside the margin. Margins adding up to more than
+  /// the size are scaled down to fit it, so the area is empty rather than inverted.
+  pub fn inset(&self, size: Size) -> Rect {
+    let fit = |before: f64, after: f64, length: f64| {
+      let (before, after) = (before.max(0.0), after.max(0.0));
+      let total = before + after;
+      if total > length && total > 0.0 {
+        let scale = length.max(0.0) / total;
+        (before * scale, after * scale)
+      } else {
+        (before, after)
+      }
+    };
+
+    let (left, right) = fit(self.margin.x0, self.margin.x1, size.width);
+    let (top, bottom) = fit(self.margin.y0, self.margin.y1, size.height);
+    Rect::new(left, top, size.width - right, size.height - bottom)
+  }
+
+  /// Sides carrying the primary Y labels. A secondary axis takes over the right side.
+  pub fn primary_y_side(&self, has_secondary: bool) -> YAxisSide {
+    if has_secondary {
+      YAxisSide::Left
+    } else {
+      self.axis_sides.y
+    }
+  }
+
+  /// Padding around the plot area, reduced on the sides that carry no tick labels. With a
+  /// secondary axis, the right side grows to fit labels of the given width, and the sides with
+  /// X labels grow to at least `x_label_extent`.
+  fn padding(&self, secondary_label_width: Option<f64>, x_label_extent: f64) -> Insets {
+    if !self.chrome {
+      return Insets::ZERO;
+    }
+
+    let sides = AxisSides {
+      x: self.axis_sides.x,
+      y: self.primary_y_side(secondary_label_width.is_some()),
+    };
+    let unlabeled = self.tick_length * 2.0;
+    let shrink = |padding: f64, labeled: bool| {
+      if labeled {
+        padding
+      } else {
+        padding.min(unlabeled)
+      }
+    };
+
+    let right = match secondary_label_width {
+      Some(width) => self
+        .padding_right
+        .max(width + self.tick_length + self.font_size),
+      None => shrink(self.padding_right, sides.y.right()),
+    };
+
+    let vertical = |padding: f64, labeled: bool| {
+      if labeled {
+        padding.max(x_label_extent)
+      } else {
+        padding.min(unlabeled)
+      }
+    };
+
+    Insets::new(
+      shrink(self.padding_left, sides.y.left()),
+      vertical(self.padding_top, sides.x.top()),
+      right,
+      vertical(self.padding_bottom, sides.x.bottom()),
+    )
+  }
+
+  /// Resolves the plot area and axis scales for the given size.
+  pub fn geometry(&self, size: Size, bounds: &DataBounds, has_title: bool) -> ChartGeometry {
+    self.resolve_geometry(size, bounds, None, &[], has_title)
+  }
+
+  /// Resolves the plot area and axis scales for the given size, with an optional secondary Y
+  /// axis. Unless `categories` is empty, the X axis is labeled with them instead of numbers, the
+  /// first at X = 0 and each next one at the next whole X.
+  pub fn resolve_geometry(
+    &self,
+    size: Size,
+    bounds: &DataBounds,
+    secondary: Option<&DataBounds>,
+    categories: &[String],
+    has_title: bool,
+  ) -> ChartGeometry {
+    let geometry = self.layout_geometry(size, bounds, secondary, categories, has_title, 0.0);
+
+    // The X labels only depend on the plot width, so making room for them keeps the same labels
+    match self.rotated_label_extent(&geometry) {
+      Some(extent) => self.layout_geometry(size, bounds, secondary, categories, has_title, extent),
+      None => geometry,
+    }
+  }
+
+  /// Space from the plot edge to the far end of the widest rotated X label, `None` when the
+  /// labels are horizontal. Text isn't measured here, so the widths are estimated as in
+  /// `estimate_label_width`.
+  fn rotated_label_extent(&self, geometry: &ChartGeometry) -> Option<f64> {
+    if !self.chrome || self.x_label_rotation() == 0.0 {
+      return None;
+    }
+
+    let angle = self.x_label_rotation().to_radians();
+    let width = geometry
+      .x_labels()
+      .iter()
+      .map(|label| label.text.len())
+      .max()
+      .unwrap_or(0) as f64
+      * self.font_size
+      * 0.6;
+
+    Some(self.tick_length + 2.0 + width * angle.sin() + self.font_size * angle.cos())
+  }
+
+  /// Rotation of the X labels in degrees, within 0 to 90.
+  fn x_label_rotation(&self) -> f64 {
+    self.x_label_rotation.clamp(0.0, 90.0)
+  }
+
+  /// Length of the tick of a Y label, halved for the in-between labels of a log axis.
+  fn y_tick_length(&self, minor: bool) -> f64 {
+    if minor {
+      self.tick_length / 2.0
+    } else {
+      self.tick_length
+    }
+  }
+
+  /// Least horizontal distance between X labels that are `unrotated` apart when horizontal.
+  fn x_label_spacing(&self, unrotated: f64) -> f64 {
+    if self.x_label_rotation() == 0.0 {
+      return unrotated;
+    }
+
+    let angle = self.x_label_rotation().to_radians();
+    unrotated.min(self.font_size * ROTATED_LABEL_LINE / angle.sin())
+  }
+
+  fn layout_geometry(
+    &self,
+    size: Size,
+    bounds: &DataBounds,
+    secondary: Option<&DataBounds>,
+    categories: &[String],
+    has_title: bool,
+    x_label_extent: f64,
+  ) -> ChartGeometry {
+    let area = self.inset(size);
+    let header_height = if has_title && self.chrome {
+      self.header_height
+    } else {
+      0.0
+    };
+
+    let min_label_spacing_h = self.x_label_spacing(self.font_size / 0.3);
+    let min_label_spacing_v = self.font_size / 0.4;
+
+    // The vertical padding doesn't depend on the secondary axis, whose labels size the right one
+    let vertical = self.padding(None, x_label_extent);
+    // Padding beyond the area leaves an empty plot rather than an inverted one
+    let bounds_v =
+      (area.height() - vertical.y0 - vertical.y1 - header_height - self.footer_height).max(0.0);
+    let max_labels_y = (bounds_v / min_label_spacing_v).floor().max(1.0);
+
+    // The data window is the bounds whatever the size, only the pixel mapping follows it
+    let cache = &self.label_cache;
+    let y_axis = |min: f64, max: f64| {
+      if self.log_y {
+        AxisScale::log(min, max, bounds_v, max_labels_y)
+      } else {
+        let scale = AxisScale::within(cache, min, max, bounds_v, max_labels_y);
+        AxisScale {
+          precision: self.y_decimals.unwrap_or(scale.precision),
+          ..scale
+        }
+      }
+    };
+    let y_scale = y_axis(bounds.min_y, bounds.max_y);
+    let secondary_scale = secondary.map(|secondary| y_axis(secondary.min_y, secondary.max_y));
+
+    let padding = self.padding(
+      secondary_scale
+        .as_ref()
+        .map(|scale| self.estimate_label_width(scale)),
+      x_label_extent,
+    );
+    let bounds_h = (area.width() - padding.x0 - padding.x1).max(0.0);
+    let max_labels_x = (bounds_h / min_label_spacing_h).floor().max(1.0);
+
+    let (x_axis, x_axis_precision, time_x) = if !categories.is_empty() {
+      let widest = categories
+        .iter()
+        .map(|category| category.chars().count())
+        .max()
+        .unwrap_or(0) as f64;
+      let spacing = self.x_label_spacing((widest * 0.6 + 1.0) * self.font_size);
+      let proportion = bounds_h / (bounds.max_x - bounds.min_x).abs();
+      let ticks = category_ticks(categories.len(), bounds, proportion, spacing);
+      (ticks, 0, None)
+    } else if self.time_x {
+      let spacing = self.x_label_spacing(self.font_size * MIN_TIME_LABEL_SPACING);
+      let max_labels = (bounds_h / spacing).floor();
+      let (ticks, step) = time::ticks(bounds.min_x, bounds.max_x, max_labels as usize);
+      (ticks.iter().map(|tick| tick.value).collect(), 0, Some(step))
+    } else {
+      let (x_axis, precision) = self
+        .label_cache
+        .get_axis(bounds.min_x, bounds.max_x, max_labels_x);
+      (x_axis, self.x_decimals.unwrap_or(precision), None)
+    };
+
+    let plot = Rect::from_origin_size(
+      (area.x0 + padding.x0, area.y0 + padding.y0 + header_height),
+      (bounds_h, bounds_v),
+    );
+
+    let geometry = ChartGeometry {
+      area,
+      plot,
+      header_height,
+      min_x: bounds.min_x,
+      max_x: bounds.max_x,
+      min_y: y_scale.min,
+      max_y: y_scale.max,
+      proportion_x: bounds_h / (bounds.max_x - bounds.min_x).abs(),
+      proportion_y: y_scale.proportion,
+      x_axis,
+      x_axis_precision,
+      y_axis: y_scale.labels,
+      y_axis_precision: y_scale.precision,
+      reversed_x: self.reversed_x,
+      reversed_y: self.reversed_y,
+      percent_y: self.percent_y,
+      time_x,
+      categories_x: categories.to_vec(),
+      log_y: self.log_y,
+      secondary: None,
+    };
+
+    let secondary = secondary_scale.map(|scale| {
+      Box::new(ChartGeometry {
+        min_y: scale.min,
+        max_y: scale.max,
+        proportion_y: scale.proportion,
+        y_axis: scale.labels,
+        y_axis_precision: scale.precision,
+        ..geometry.clone()
+      })
+    });
+
+    ChartGeometry {
+      secondary,
+      ..geometry
+    }
+  }
+
+  /// Text isn't measured here, so the width of the widest label is estimated from its length.
+  fn estimate_label_width(&self, scale: &AxisScale) -> f64 {
+    scale
+      .labels
+      .iter()
+      .map(|value| format!("{:.prec$}", value, prec = scale.precision).len())
+      .max()
+      .unwrap_or(0) as f64
+      * self.font_size
+      * 0.6
+  }
+
+  /// Paints the title, the plot frame, the tick labels and the gridlines.
+  pub fn paint_axes(
+    &self,
+    ctx: &mut PaintCtx,
+    title: Option<&str>,
+    geometry: &ChartGeometry,
+    env: &Env,
+  ) {
+    if let Some(title) = title {
+      paint_title(ctx, title, geometry.header(), env);
+    }
+
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), self.font_size)
+      .build()
+      .unwrap();
+
+    let origin_left = geometry.plot.x0;
+    let origin_right = geometry.plot.x1;
+    let origin_top = geometry.plot.y0;
+    let origin_bottom = geometry.plot.y1;
+
+    let sides = AxisSides {
+      x: self.axis_sides.x,
+      y: self.primary_y_side(geometry.secondary.is_some()),
+    };
+    let base_grid_color = self
+      .grid
+      .color
+      .clone()
+      .unwrap_or_else(|| chart_theme::grid_color(env));
+    let grid_color = base_grid_color.clone().with_alpha(self.grid.alpha);
+    let major_grid_color = base_grid_color
+      .clone()
+      .with_alpha((self.grid.alpha * MAJOR_TIME_GRID_ALPHA).min(1.0));
+    let minor_grid_color = base_grid_color
+      .clone()
+      .with_alpha(self.grid.alpha * MINOR_GRID_ALPHA);
+
+    if let Some(background) = &self.plot_background {
+      ctx.fill(geometry.plot, background);
+    }
+
+    // Draw chart rectangle
+    ctx.stroke(geometry.plot, &chart_theme::axis_color(env), 1.0);
+
+    if self.minor_gridlines > 0 {
+      ctx.stroke(self.minor_grid_path(geometry), &minor_grid_color, 1.0);
+    }
+
+    // Piet rotates clockwise, the setting is counter-clockwise
+    let rotation = -self.x_label_rotation().to_radians();
+    let baseline = self.font_size * ROTATED_LABEL_BASELINE;
+
+    for value_x in geometry.visible_x_labels() {
+      let label = geometry.format_x(value_x);
+
+      let layout = ctx
+        .text()
+        .new_text_layout(&label_font, &label, f64::INFINITY)
+        .build()
+        .unwrap();
+
+      let position_x = geometry.to_pixel_x(value_x);
+      let mut tick_line = BezPath::new();
+
+      if sides.x.top() && rotation != 0.0 {
+        // Rotated about the tick, starting there and rising to the right
+        let anchor = Point::new(position_x, origin_top - self.tick_length - 2.0);
+        ctx.with_save(|ctx| {
+          ctx.transform(Affine::translate(anchor.to_vec2()) * Affine::rotate(rotation));
+          ctx.draw_text(&layout, (0.0, baseline), &chart_theme::axis_color(env));
+        });
+      } else if sides.x.top() {
+        ctx.draw_text(
+          &layout,
+          (
+            position_x - layout.width() / 2.0,
+            origin_top - self.tick_length - 2.0,
+          ),
+          &chart_theme::axis_color(env),
+        );
+      }
+
+      if sides.x.top() {
+        tick_line.move_to((position_x, origin_top));
+        tick_line.line_to((position_x, origin_top - self.tick_length));
+      }
+
+      if sides.x.bottom() && rotation != 0.0 {
+        // Rotated about the tick, ending there so the text hangs down to the left
+        let anchor = Point::new(position_x, origin_bottom + self.tick_length + 2.0);
+        ctx.with_save(|ctx| {
+          ctx.transform(Affine::translate(anchor.to_vec2()) * Affine::rotate(rotation));
+          ctx.draw_text(
+            &layout,
+            (-layout.width(), baseline),
+            &chart_theme::axis_color(env),
+          );
+        });
+      } else if sides.x.bottom() {
+        ctx.draw_text(
+          &layout,
+          (
+            position_x - layout.width() / 2.0,
+            origin_bottom + self.tick_length + self.font_size,
+          ),
+          &chart_theme::axis_color(env),
+        );
+      }
+
+      if sides.x.bottom() {
+        tick_line.move_to((position_x, origin_bottom));
+        tick_line.line_to((position_x, origin_bottom + self.tick_length));
+      }
+
+      ctx.stroke(tick_line, &chart_theme::axis_color(env), 1.0);
+
+      // Grid line
+      if self.grid.show_x {
+        let mut grid_line = BezPath::new();
+        grid_line.move_to((position_x, origin_top));
+        grid_line.line_to((position_x, origin_bottom));
+
+        if geometry.is_major_x(value_x) {
+          ctx.stroke(grid_line, &major_grid_color, MAJOR_TIME_GRID_WIDTH);
+        } else {
+          ctx.stroke(grid_line, &grid_color, 1.0);
+        }
+      }
+    }
+
+    for value_y in geometry.visible_y_labels() {
+      let label = geometry.format_y(value_y);
+
+      let layout = ctx
+        .text()
+        .new_text_layout(&label_font, &label, f64::INFINITY)
+        .build()
+        .unwrap();
+
+      let position_y = geometry.to_pixel_y(value_y);
+      let text_height_adjustment = if let Some(metric) = layout.line_metric(0) {
+        metric.cumulative_height - metric.baseline.floor()
+      } else {
+        self.font_size / 2.2
+      };
+      let minor = geometry.is_minor_y(value_y);
+      let tick_length = self.y_tick_length(minor);
+
+      let mut tick_line = BezPath::new();
+
+      if sides.y.left() {
+        ctx.draw_text(
+          &layout,
+          (
+            origin_left - layout.width() - self.tick_length - 2.0,
+            position_y + text_height_adjustment,
+          ),
+          &chart_theme::axis_color(env),
+        );
+
+        tick_line.move_to((origin_left, position_y));
+        tick_line.line_to((origin_left - tick_length, position_y));
+      }
+
+      if sides.y.right() {
+        ctx.draw_text(
+          &layout,
+          (
+            origin_right + self.tick_length + 2.0,
+            position_y + text_height_adjustment,
+          ),
+          &chart_theme::axis_color(env),
+        );
+
+        tick_line.move_to((origin_right, position_y));
+        tick_line.line_to((origin_right + tick_length, position_y));
+      }
+
+      ctx.stroke(tick_line, &chart_theme::axis_color(env), 1.0);
+
+      // Grid line
+      if self.grid.show_y {
+        let mut grid_line = BezPath::new();
+        grid_line.move_to((origin_left, position_y));
+        grid_line.line_to((origin_right, position_y));
+
+        if minor {
+          ctx.stroke(grid_line, &minor_grid_color, 1.0);
+        } else {
+          ctx.stroke(grid_line, &grid_color, 1.0);
+        }
+      }
+    }
+
+    // The secondary axis only gets labels and ticks, its gridlines wouldn't line up
+    if let Some(secondary) = &geometry.secondary {
+      for value_y in secondary.visible_y_labels() {
+        let layout = ctx
+          .text()
+          .new_text_layout(&label_font, &secondary.format_y(value_y), f64::INFINITY)
+          .build()
+          .unwrap();
+
+        let position_y = secondary.to_pixel_y(value_y);
+        let text_height_adjustment = if let Some(metric) = layout.line_metric(0) {
+          metric.cumulative_height - metric.baseline.floor()
+        } else {
+          self.font_size / 2.2
+        };
+
+        ctx.draw_text(
+          &layout,
+          (
+            origin_right + self.tick_length + 2.0,
+            position_y + text_height_adjustment,
+          ),
+          &chart_theme::axis_color(env),
+        );
+
+        let mut tick_line = BezPath::new();
+        tick_line.move_to((origin_right, position_y));
+        tick_line.line_to((origin_right + self.tick_length, position_y));
+        ctx.stroke(tick_line, &chart_theme::axis_color(env), 1.0);
+      }
+    }
+
+    // Over the gridlines, as in the exports
+    if self.zero_line {
+      ctx.stroke(
+        zero_line_path(geometry),
+        &chart_theme::axis_color(env).with_alpha(ZERO_LINE_ALPHA),
+        ZERO_LINE_WIDTH,
+      );
+    }
+  }
+
+  /// Paints the dashed crosshair through `cursor` and the axis values it points at, when the
+  /// mouse at `cursor_pos` is over the plot area.
+  pub fn paint_cursor_reference(
+    &self,
+    ctx: &mut PaintCtx,
+    geometry: &ChartGeometry,
+    cursor_pos: Point,
+    cursor: Point,
+    env: &Env,
+  ) {
+    let origin_left = geometry.plot.x0;
+    let origin_right = geometry.plot.x1;
+    let origin_top = geometry.plot.y0;
+    let origin_bottom = geometry.plot.y1;
+
+    if !geometry.contains(cursor_pos) {
+      return;
+    }
+
+    // Y
+    let mut line_path = BezPath::new();
+
+    let mut x = origin_left;
+    while x <= origin_right {
+      line_path.move_to((x.min(origin_right), cursor.y));
+      line_path.line_to(((x + 5.0).min(origin_right), cursor.y));
+
+      x += 10.0;
+    }
+
+    ctx.stroke(
+      line_path,
+      &chart_theme::axis_color(env).with_alpha(0.3),
+      1.0,
+    );
+
+    // X
+    let mut line_path = BezPath::new();
+
+    let mut y = origin_top;
+    while y <= origin_bottom {
+      line_path.move_to((cursor.x, y.min(origin_bottom)));
+      line_path.line_to((cursor.x, (y + 5.0).min(origin_bottom)));
+
+      y += 10.0;
+    }
+
+    ctx.stroke(
+      line_path,
+      &chart_theme::axis_color(env).with_alpha(0.3),
+      1.0,
+    );
+
+    if self.cursor_readouts {
+      self.paint_cursor_readouts(ctx, geometry, cursor, env);
+    }
+  }
+
+  /// Draws the values under the cursor at the ends of the crosshair, beside the axis labels.
+  fn paint_cursor_readouts(
+    &self,
+    ctx: &mut PaintCtx,
+    geometry: &ChartGeometry,
+    cursor: Point,
+    env: &Env,
+  ) {
+    let origin_left = geometry.plot.x0;
+    let origin_right = geometry.plot.x1;
+    let origin_top = geometry.plot.y0;
+    let origin_bottom = geometry.plot.y1;
+    let sides = self.axis_sides;
+
+    // Draw reference value at the end
+    let value = geometry.to_value_y(cursor.y);
+
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), self.font_size)
+      .build()
+      .unwrap();
+
+    let layout = ctx
+      .text()
+      .new_text_layout(&label_font, &geometry.format_y(value), f64::INFINITY)
+      .build()
+      .unwrap();
+
+    let text_height = if let Some(metric) = layout.line_metric(0) {
+      self.font_size - (metric.cumulative_height - metric.baseline.floor())
+    } else {
+      self.font_size
+    };
+
+    // The readout sits on the side that carries the Y labels
+    let box_left = if self.primary_y_side(geometry.secondary.is_some()).right() {
+      origin_right
+    } else {
+      origin_left - layout.width() - 10.0
+    };
+    self.paint_y_readout(ctx, &layout, box_left, cursor.y, text_height, env);
+
+    if let Some(secondary) = &geometry.secondary {
+      let value = secondary.to_value_y(cursor.y);
+      let layout = ctx
+        .text()
+        .new_text_layout(&label_font, &secondary.format_y(value), f64::INFINITY)
+        .build()
+        .unwrap();
+
+      self.paint_y_readout(ctx, &layout, origin_right, cursor.y, text_height, env);
+    }
+
+    let value = geometry.to_value_x(cursor.x);
+
+    let layout = ctx
+      .text()
+      .new_text_layout(
+        &label_font,
+        &geometry.format_x_readout(value),
+        f64::INFINITY,
+      )
+      .build()
+      .unwrap();
+
+    // The readout sits on the side that carries the X labels
+    let box_top = if sides.x.bottom() {
+      origin_bottom
+    } else {
+      origin_top - text_height - 10.0
+    };
+
+    let rect = Rect::from_points(
+      Point::new(cursor.x - layout.width() / 2.0 - 5.0, box_top),
+      Point::new(
+        cursor.x + layout.width() / 2.0 + 5.0,
+        box_top + text_height + 10.0,
+      ),
+    );
+
+    ctx.fill(rect, &chart_theme::tooltip_bg(env));
+
+    ctx.draw_text(
+      &layout,
+      (cursor.x - layout.width() / 2.0, box_top + text_height + 5.0),
+      &chart_theme::tooltip_fg(env),
+    );
+  }
+
+  /// Draws a Y value in a box starting at `left`, vertically centered on `y`.
+  fn paint_y_readout(
+    &self,
+    ctx: &mut PaintCtx,
+    layout: &PietTextLayout,
+    left: f64,
+    y: f64,
+    text_height: f64,
+    env: &Env,
+  ) {
+    let rect = Rect::from_points(
+      Point::new(left, y - (10.0 + text_height) / 2.0),
+      Point::new(left + layout.width() + 10.0, y + (10.0 + text_height) / 2.0),
+    );
+
+    ctx.fill(rect, &chart_theme::tooltip_bg(env));
+
+    ctx.draw_text(
+      layout,
+      (left + 5.0, y + (self.font_size * 0.334)),
+      &chart_theme::tooltip_fg(env),
+    );
+  }
+
+  /// Writes the title, the plot frame, the tick labels and the gridlines to an SVG document.
+  ///
+  /// Since no druid context is available, colors come from `theme` and text is aligned with SVG
+  /// anchors instead of measured text layouts.
+  pub fn render_svg_axes(
+    &self,
+    svg: &mut SvgDocument,
+    title: Option<&str>,
+    geometry: &ChartGeometry,
+    theme: &ChartTheme,
+  ) {
+    let font_size = self.font_size;
+    let tick_length = self.tick_length;
+    let sides = AxisSides {
+      x: self.axis_sides.x,
+      y: self.primary_y_side(geometry.secondary.is_some()),
+    };
+    let plot = geometry.plot;
+
+    if let Some(title) = title {
+      render_svg_title(svg, title, geometry.header(), &theme.axis);
+    }
+
+    if let Some(background) = &self.plot_background {
+      svg.fill_rect(plot, background);
+    }
+    svg.stroke_rect(plot, &theme.axis, 1.0);
+
+    let mut ticks = BezPath::new();
+    let mut grid = BezPath::new();
+    let mut major_grid = BezPath::new();
+    let mut minor_grid = BezPath::new();
+    let rotation = self.x_label_rotation();
+    let baseline = font_size * ROTATED_LABEL_BASELINE;
+
+    for value_x in geometry.visible_x_labels() {
+      let label = geometry.format_x(value_x);
+      let position_x = geometry.to_pixel_x(value_x);
+
+      if sides.x.top() && rotation != 0.0 {
+        svg.rotated_text(
+          &label,
+          Point::new(position_x, plot.y0 - tick_length - 2.0),
+          baseline,
+          rotation,
+          TextAnchor::Start,
+          font_size,
+          &theme.axis,
+        );
+      } else if sides.x.top() {
+        svg.text(
+          &label,
+          Point::new(position_x, plot.y0 - tick_length - 2.0),
+          TextAnchor::Middle,
+          font_size,
+          &theme.axis,
+        );
+      }
+
+      if sides.x.top() {
+        ticks.move_to((position_x, plot.y0));
+        ticks.line_to((position_x, plot.y0 - tick_length));
+      }
+
+      if sides.x.bottom() && rotation != 0.0 {
+        svg.rotated_text(
+          &label,
+          Point::new(position_x, plot.y1 + tick_length + 2.0),
+          baseline,
+          rotation,
+          TextAnchor::End,
+          font_size,
+          &theme.axis,
+        );
+      } else if sides.x.bottom() {
+        svg.text(
+          &label,
+          Point::new(position_x, plot.y1 + tick_length + font_size),
+          TextAnchor::Middle,
+          font_size,
+          &theme.axis,
+        );
+      }
+
+      if sides.x.bottom() {
+        ticks.move_to((position_x, plot.y1));
+        ticks.line_to((position_x, plot.y1 + tick_length));
+      }
+
+      if self.grid.show_x {
+        let grid = if geometry.is_major_x(value_x) {
+          &mut major_grid
+        } else {
+          &mut grid
+        };
+        grid.move_to((position_x, plot.y0));
+        grid.line_to((position_x, plot.y1));
+      }
+    }
+
+    for value_y in geometry.visible_y_labels() {
+      let label = geometry.format_y(value_y);
+      let position_y = geometry.to_pixel_y(value_y);
+      let baseline = position_y + font_size / 2.2;
+      let minor = geometry.is_minor_y(value_y);
+      let y_tick_length = self.y_tick_length(minor);
+
+      if sides.y.left() {
+        svg.text(
+          &label,
+          Point::new(plot.x0 - tick_length - 2.0, baseline),
+          TextAnchor::End,
+          font_size,
+          &theme.axis,
+        );
+        ticks.move_to((plot.x0, position_y));
+        ticks.line_to((plot.x0 - y_tick_length, position_y));
+      }
+
+      if sides.y.right() {
+        svg.text(
+          &label,
+          Point::new(plot.x1 + tick_length + 2.0, baseline),
+          TextAnchor::Start,
+          font_size,
+          &theme.axis,
+        );
+        ticks.move_to((plot.x1, position_y));
+        ticks.line_to((plot.x1 + y_tick_length, position_y));
+      }
+
+      if self.grid.show_y {
+        let grid = if minor { &mut minor_grid } else { &mut grid };
+        grid.move_to((plot.x0, position_y));
+        grid.line_to((plot.x1, position_y));
+      }
+    }
+
+    if let Some(secondary) = &geometry.secondary {
+      for value_y in secondary.visible_y_labels() {
+        let position_y = secondary.to_pixel_y(value_y);
+
+        svg.text(
+          &secondary.format_y(value_y),
+          Point::new(plot.x1 + tick_length + 2.0, position_y + font_size / 2.2),
+          TextAnchor::Start,
+          font_size,
+          &theme.axis,
+        );
+        ticks.move_to((plot.x1, position_y));
+        ticks.line_to((plot.x1 + tick_length, position_y));
+      }
+    }
+
+    svg.stroke_path(&ticks, &theme.axis, 1.0);
+    let grid_color = self
+      .grid
+      .color
+      .clone()
+      .unwrap_or_else(|| theme.grid.clone());
+    let minor_grid_color = grid_color
+      .clone()
+      .with_alpha(self.grid.alpha * MINOR_GRID_ALPHA);
+    if self.minor_gridlines > 0 {
+      svg.stroke_path(&self.minor_grid_path(geometry), &minor_grid_color, 1.0);
+    }
+    svg.stroke_path(&minor_grid, &minor_grid_color, 1.0);
+    svg.stroke_path(
+      &major_grid,
+      &grid_color
+        .clone()
+        .with_alpha((self.grid.alpha * MAJOR_TIME_GRID_ALPHA).min(1.0)),
+      MAJOR_TIME_GRID_WIDTH,
+    );
+    svg.stroke_path(&grid, &grid_color.with_alpha(self.grid.alpha), 1.0);
+
+    if self.zero_line {
+      svg.stroke_path(
+        &zero_line_path(geometry),
+        &theme.axis.clone().with_alpha(ZERO_LINE_ALPHA),
+        ZERO_LINE_WIDTH,
+      );
+    }
+  }
+
+  /// Lines subdividing each interval between consecutive visible major ticks.
+  fn minor_grid_path(&self, geometry: &ChartGeometry) -> BezPath {
+    let plot = geometry.plot;
+    let mut path = BezPath::new();
+
+    if self.grid.show_x {
+      let labels: Vec<f64> = geometry.visible_x_labels().collect();
+      for value_x in subdivide(&labels, self.minor_gridlines) {
+        let position_x = geometry.to_pixel_x(value_x);
+        path.move_to((position_x, plot.y0));
+        path.line_to((position_x, plot.y1));
+      }
+    }
+
+    if self.grid.show_y {
+      let labels: Vec<f64> = geometry.visible_y_labels().collect();
+      for value_y in subdivide(&labels, self.minor_gridlines) {
+        let position_y = geometry.to_pixel_y(value_y);
+        path.move_to((plot.x0, position_y));
+        path.line_to((plot.x1, position_y));
+      }
+    }
+
+    path
+  }
+}
+
+/// Positions of the categories labeled along the X axis: every category within the bounds, or
+/// every second, third and so on when categories `proportion` pixels apart are closer than
+/// `spacing`. The first category is always labeled.
+fn category_ticks(count: usize, bounds: &DataBounds, proportion: f64, spacing: f64) -> Vec<f64> {
+  let step = if proportion.is_finite() && proportion > 0.0 {
+    (spacing / proportion).ceil().max(1.0) as usize
+  } else {
+    1
+  };
+  let (min, max) = (
+    bounds.min_x.min(bounds.max_x),
+    bounds.min_x.max(bounds.max_x),
+  );
+
+  (0..count)
+    .step_by(step)
+    .map(|index| index as f64)
+    .filter(|position| *position >= min && *position <= max)
+    .collect()
+}
+
+/// The lines through X = 0 and Y = 0 across the plot, for the ranges that cross zero.
+fn zero_line_path(geometry: &ChartGeometry) -> BezPath {
+  let plot = geometry.plot;
+  let mut path = BezPath::new();
+
+  if let Some(position_x) = geometry.zero_x() {
+    path.move_to((position_x, plot.y0));
+    path.line_to((position_x, plot.y1));
+  }
+
+  if let Some(position_y) = geometry.zero_y() {
+    path.move_to((plot.x0, position_y));
+    path.line_to((plot.x1, position_y));
+  }
+
+  path
+}
+
+/// Fraction of the Y range added below and above the data, unless a chart sets its own.
+pub(crate) const DEFAULT_Y_PADDING: f64 = 0.05;
+
+impl DataBounds {
+  /// Computes the bounds of a set of `(x, y)` points. The Y range is widened outwards by
+  /// `DEFAULT_Y_PADDING` of its span, so the extremes don't sit on the plot frame.
+  pub fn from_points<I>(points: I) -> Self
+  where
+    I: Iterator<Item = (f64, f64)> + Clone,
+  {
+    Self::extent(points).padded_y(DEFAULT_Y_PADDING)
+  }
+
+  /// The extremes of a set of `(x, y)` points, without any padding. Both ranges are 1 to 1
+  /// without points.
+  pub fn extent<I>(points: I) -> Self
+  where
+    I: Iterator<Item = (f64, f64)> + Clone,
+  {
+    let x_iter = points.clone().map(|(x, _)| x);
+
+    let min_x = x_iter
+      .clone()
+      .min_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+      .unwrap_or(1.0);
+
+    let max_x = x_iter
+      .clone()
+      .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+      .unwrap_or(1.0);
+
+    let y_iter = points.map(|(_, y)| y);
+
+    let min_y = y_iter
+      .clone()
+      .min_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+      .unwrap_or(1.0);
+
+    let max_y = y_iter
+      .clone()
+      .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+      .unwrap_or(1.0);
+
+    let precision_y = y_iter
+      .map(get_precision)
+      .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+      .unwrap_or(1);
+
+    Self {
+      min_x,
+      max_x,
+      min_y,
+      max_y,
+      precision_y,
+    }
+  }
+
+  /// Smallest bounds holding both, at the greater precision.
+  pub fn union(self, other: Self) -> Self {
+    Self {
+      min_x: self.min_x.min(other.min_x),
+      max_x: self.max_x.max(other.max_x),
+      min_y: self.min_y.min(other.min_y),
+      max_y: self.max_y.max(other.max_y),
+      precision_y: self.precision_y.max(other.precision_y),
+    }
+  }
+
+  /// Like `padded_y`, with the padding a fraction of the span of the logarithms of the values, as
+  /// a log axis shows them. Bounds reaching zero or below are left as they are.
+  pub fn padded_log_y(self, fraction: f64) -> Self {
+    if self.min_y <= 0.0 {
+      return self;
+    }
+
+    let (min, max) = (self.min_y.log10(), self.max_y.log10());
+    let pad = if max > min {
+      (max - min) * fraction
+    } else {
+      fraction.max(DEFAULT_Y_PADDING)
+    };
+
+    Self {
+      min_y: 10f64.powf(min - pad),
+      max_y: 10f64.powf(max + pad),
+      ..self
+    }
+  }
+
+  /// Widens the Y range by `fraction` of its span at both ends. A flat range has no span, so it
+  /// is widened by a fraction of its value, at least `DEFAULT_Y_PADDING`, to still have height.
+  /// A flat range at zero becomes -1 to 1, keeping the line in the middle of the plot.
+  pub fn padded_y(self, fraction: f64) -> Self {
+    let span = self.max_y - self.min_y;
+    let pad = if span > 0.0 {
+      span * fraction
+    } else if self.max_y == 0.0 {
+      1.0
+    } else {
+      self.max_y.abs() * fraction.max(DEFAULT_Y_PADDING)
+    };
+
+    Self {
+      min_y: self.min_y - pad,
+      max_y: self.max_y + pad,
+      ..self
+    }
+  }
+}
+
+impl AxisScale {
+  /// Picks the labels of an axis `length` pixels long and grows the range to include the
+  /// outermost ones, so the axis starts and ends on a tick. Inverted bounds are swapped.
+  pub fn new(
+    cache: &LabelCache,
+    min_value: f64,
+    max_value: f64,
+    length: f64,
+    max_labels: f64,
+  ) -> Self {
+    let (min_value, max_value) = spanned(ordered(min_value, max_value));
+    let (labels, precision) = cache.get_axis(min_value, max_value, max_labels);
+
+    let min = labels.first().map_or(min_value, |v| min_value.min(*v));
+    let max = labels.last().map_or(max_value, |v| max_value.max(*v));
+
+    Self {
+      labels,
+      precision,
+      min,
+      max,
+      proportion: length / (max - min).abs(),
+    }
+  }
+
+  /// Picks the labels of an axis `length` pixels long, leaving out any outside the range. The
+  /// range stays as given, so it doesn't change with the number of labels that fit.
+  pub fn within(
+    cache: &LabelCache,
+    min_value: f64,
+    max_value: f64,
+    length: f64,
+    max_labels: f64,
+  ) -> Self {
+    let (min_value, max_value) = spanned(ordered(min_value, max_value));
+    let (labels, precision) = cache.get_axis(min_value, max_value, max_labels);
+
+    Self {
+      labels: labels
+        .into_iter()
+        .filter(|v| *v >= min_value && *v <= max_value)
+        .collect(),
+      precision,
+      min: min_value,
+      max: max_value,
+      proportion: length / (max_value - min_value).abs(),
+    }
+  }
+}
+
+impl AxisScale {
+  /// Picks the labels of a log axis `length` pixels long, keeping the range like `within`. Only
+  /// positive values can be shown, so a range reaching zero or below starts
+  /// `LOG_FALLBACK_DECADES` below its top instead.
+  pub fn log(min_value: f64, max_value: f64, length: f64, max_labels: f64) -> Self {
+    let (min_value, max_value) = log_range(min_value, max_value);
+
+    Self {
+      labels: log_ticks(min_value, max_value, max_labels as usize),
+      precision: 0,
+      min: min_value,
+      max: max_value,
+      proportion: length / (max_value.log10() - min_value.log10()),
+    }
+  }
+}
+
+impl LabelCache {
+  /// The labels `get_axis` picks for the range, from the cache when they were picked recently.
+  pub fn get_axis(&self, min_value: f64, max_value: f64, max_labels: f64) -> (Vec<f64>, usize) {
+    let key = (
+      min_value.to_bits(),
+      max_value.to_bits(),
+      max_labels.to_bits(),
+    );
+    let mut entries = self.entries.borrow_mut();
+
+    if let Some(index) = entries.iter().position(|entry| entry.key == key) {
+      let entry = entries.remove(index);
+      let result = (entry.labels.clone(), entry.precision);
+      entries.insert(0, entry);
+      return result;
+    }
+
+    let (labels, precision) = get_axis(min_value, max_value, max_labels);
+    entries.insert(
+      0,
+      CachedLabels {
+        key,
+        labels: labels.clone(),
+        precision,
+      },
+    );
+    entries.truncate(LABEL_CACHE_SIZE);
+
+    (labels, precision)
+  }
+}
+
+impl ChartGeometry {
+  /// The band above the plot the title is centered in, empty without a title.
+  pub(crate) fn header(&self) -> Rect {
+    Rect::new(
+      self.area.x0,
+      self.area.y0,
+      self.area.x1,
+      self.area.y0 + self.header_height,
+    )
+  }
+
+  /// Area the data is plotted in, inside the axes.
+  pub fn plot(&self) -> Rect {
+    self.plot
+  }
+
+  /// The geometry a line plotted against the secondary Y axis uses, or this one for the
+  /// primary axis.
+  pub fn y_axis_for(&self, secondary: bool) -> &ChartGeometry {
+    match &self.secondary {
+      Some(geometry) if secondary => geometry,
+      _ => self,
+    }
+  }
+
+  pub fn to_pixel_x(&self, x: f64) -> f64 {
+    if self.reversed_x {
+      self.plot.x1 - (x - self.min_x) * self.proportion_x
+    } else {
+      self.plot.x0 + (x - self.min_x) * self.proportion_x
+    }
+  }
+
+  pub fn to_pixel_y(&self, y: f64) -> f64 {
+    // Values a log axis can't show fall far below it
+    let offset = if self.log_y {
+      y.max(f64::MIN_POSITIVE).log10() - self.min_y.log10()
+    } else {
+      y - self.min_y
+    };
+
+    if self.reversed_y {
+      self.plot.y0 + offset * self.proportion_y
+    } else {
+      self.plot.y1 - offset * self.proportion_y
+    }
+  }
+
+  pub fn to_value_x(&self, pixel_x: f64) -> f64 {
+    if self.reversed_x {
+      (self.plot.x1 - pixel_x) / self.proportion_x + self.min_x
+    } else {
+      (pixel_x - self.plot.x0) / self.proportion_x + self.min_x
+    }
+  }
+
+  pub fn to_value_y(&self, pixel_y: f64) -> f64 {
+    let offset = if self.reversed_y {
+      (pixel_y - self.plot.y0) / self.proportion_y
+    } else {
+      (self.plot.y1 - pixel_y) / self.proportion_y
+    };
+
+    if self.log_y {
+      10f64.powf(self.min_y.log10() + offset)
+    } else {
+      offset + self.min_y
+    }
+  }
+
+  /// The data X and Y values at a pixel position, which may lie outside the plot.
+  pub fn pixel_to_data(&self, point: Point) -> (f64, f64) {
+    (self.to_value_x(point.x), self.to_value_y(point.y))
+  }
+
+  /// The pixel position data values are plotted at.
+  pub fn data_to_pixel(&self, x: f64, y: f64) -> Point {
+    Point::new(self.to_pixel_x(x), self.to_pixel_y(y))
+  }
+
+  pub fn contains(&self, point: Point) -> bool {
+    point.x > self.plot.x0
+      && point.x < self.plot.x1
+      && point.y > self.plot.y0
+      && point.y < self.plot.y1
+  }
+
+  /// Formats an X value with the precision of the X axis labels.
+  pub fn format_x(&self, value: f64) -> String {
+    if !self.categories_x.is_empty() {
+      // Between two categories, the nearest one
+      let index = value.round();
+      return match self.categories_x.get(index as usize) {
+        Some(category) if index >= 0.0 => category.clone(),
+        _ => String::new(),
+      };
+    }
+
+    match self.time_x {
+      Some(_) => time::format_tick(value),
+      None => format!("{:.prec$}", value, prec = self.x_axis_precision),
+    }
+  }
+
+  /// Formats any X value for the crosshair readout. On a time axis, the full date is shown down
+  /// to the step between the labels, while labels only show what changes at their tick.
+  pub fn format_x_readout(&self, value: f64) -> String {
+    match self.time_x {
+      Some(step) => time::format_time(value, step),
+      None => self.format_x(value),
+    }
+  }
+
+  /// Whether the X label at `value` is on a calendar boundary coarser than the step of a time
+  /// axis, and gets an emphasized gridline.
+  pub(crate) fn is_major_x(&self, value: f64) -> bool {
+    matches!(self.time_x, Some(step) if time::granularity(value) > step)
+  }
+
+  /// Pixel X of the vertical line through X = 0, when the X range crosses zero. Time axes start
+  /// at the Unix epoch, which isn't worth a line, and category axes have no zero.
+  pub(crate) fn zero_x(&self) -> Option<f64> {
+    let crosses = self.min_x.min(self.max_x) < 0.0 && self.min_x.max(self.max_x) > 0.0;
+    let numeric = self.time_x.is_none() && self.categories_x.is_empty();
+    Some(self.to_pixel_x(0.0)).filter(|_| crosses && numeric)
+  }
+
+  /// Pixel Y of the horizontal line through Y = 0, when the Y range crosses zero. A log axis
+  /// never does.
+  pub(crate) fn zero_y(&self) -> Option<f64> {
+    let crosses = self.min_y < 0.0 && self.max_y > 0.0;
+    Some(self.to_pixel_y(0.0)).filter(|_| crosses && !self.log_y)
+  }
+
+  /// Whether the Y label at `value` falls between the powers of ten of a log axis, and gets a
+  /// lighter gridline and a shorter tick.
+  pub(crate) fn is_minor_y(&self, value: f64) -> bool {
+    self.log_y && (value.log10() - value.log10().round()).abs() > 1e-9
+  }
+
+  /// Formats a Y value with the precision of the Y axis labels. On a log axis, where the labels
+  /// span several magnitudes, each value gets its own precision instead.
+  pub fn format_y(&self, value: f64) -> String {
+    if self.log_y {
+      let text = format_log(value);
+      return if self.percent_y { text + "%" } else { text };
+    }
+
+    if self.percent_y {
+      format!("{:.prec$}%", value, prec = self.y_axis_precision)
+    } else {
+      format!("{:.prec$}", value, prec = self.y_axis_precision)
+    }
+  }
+
+  /// The labels of the X axis, left to right unless the axis is reversed.
+  pub fn x_labels(&self) -> Vec<AxisLabel> {
+    self
+      .visible_x_labels()
+      .map(|value| AxisLabel {
+        value,
+        text: self.format_x(value),
+        position: self.to_pixel_x(value),
+      })
+      .collect()
+  }
+
+  /// The labels of the Y axis, from the lowest value. Those of the secondary axis are found
+  /// through `y_axis_for(true)`.
+  pub fn y_labels(&self) -> Vec<AxisLabel> {
+    self
+      .visible_y_labels()
+      .map(|value| AxisLabel {
+        value,
+        text: self.format_y(value),
+        position: self.to_pixel_y(value),
+      })
+      .collect()
+  }
+
+  /// X axis labels that fall inside the plotted range.
+  pub fn visible_x_labels(&self) -> impl Iterator<Item = f64> + '_ {
+    self
+      .x_axis
+      .iter()
+      .copied()
+      .skip_while(move |v| *v < self.min_x)
+      .take_while(move |v| *v <= self.max_x)
+  }
+
+  /// Y axis labels that fall inside the plotted range.
+  pub fn visible_y_labels(&self) -> impl Iterator<Item = f64> + '_ {
+    self
+      .y_axis
+      .iter()
+      .copied()
+      .skip_while(move |v| *v < self.min_y)
+      .take_while(move |v| *v <= self.max_y)
+  }
+}
+
+/// All the space allowed, or `DEFAULT_SIZE` along unbounded directions, within the minimum.
+pub(crate) fn chart_size(bc: &BoxConstraints) -> Size {
+  let max = bc.max();
+  let size = Size::new(
+    if max.width.is_finite() {
+      max.width
+    } else {
+      DEFAULT_SIZE.width
+    },
+    if max.height.is_finite() {
+      max.height
+    } else {
+      DEFAULT_SIZE.height
+    },
+  );
+
+  bc.constrain(size)
+}
+
+/// Paints the title centered in the `header` rectangle.
+pub(crate) fn paint_title(ctx: &mut PaintCtx, title: &str, header: Rect, env: &Env) {
+  let header_label_font = ctx
+    .text()
+    .new_font_by_name(env.get(theme::FONT_NAME), 25.0)
+    .build()
+    .unwrap();
+
+  let header_layout = ctx
+    .text()
+    .new_text_layout(&header_label_font, title, f64::INFINITY)
+    .build()
+    .unwrap();
+
+  let pos_x = header.center().x - header_layout.width() / 2.0;
+  let pos_y = if let Some(metrics) = header_layout.line_metric(0) {
+    header.y0 + (header.height() + metrics.baseline) / 2.0
+  } else {
+    header.y0 + (header.height() + 25.0) / 2.0
+  };
+
+  ctx.draw_text(
+    &header_layout,
+    (pos_x, pos_y),
+    &chart_theme::axis_color(env),
+  );
+}
+
+/// Writes the title centered in the `header` rectangle of an SVG document.
+pub(crate) fn render_svg_title(svg: &mut SvgDocument, title: &str, header: Rect, color: &Color) {
+  svg.text(
+    title,
+    Point::new(
+      header.center().x,
+      header.y0 + (header.height() + 25.0) / 2.0,
+    ),
+    TextAnchor::Middle,
+    25.0,
+    color,
+  );
+}
+
+/// The bounds of a range, lowest first. Reversed axes are drawn from the `reversed_x` and
+/// `reversed_y` settings, never from inverted bounds.
+fn ordered(min: f64, max: f64) -> (f64, f64) {
+  if min > max {
+    (max, min)
+  } else {
+    (min, max)
+  }
+}
+
+/// An ordered range, widened around its value when it has no span so the axis can still divide
+/// it: by `DEFAULT_Y_PADDING` of the value, or to -1 and 1 at zero.
+fn spanned((min, max): (f64, f64)) -> (f64, f64) {
+  if max > min {
+    return (min, max);
+  }
+
+  let pad = if min == 0.0 {
+    1.0
+  } else {
+    min.abs() * DEFAULT_Y_PADDING
+  };
+  (min - pad, max + pad)
+}
+
+/// Evenly spaced values strictly between each pair of consecutive `ticks`.
+fn subdivide(ticks: &[f64], count: usize) -> Vec<f64> {
+  ticks
+    .windows(2)
+    .flat_map(|pair| {
+      let step = (pair[1] - pair[0]) / (count + 1) as f64;
+      (1..=count).map(move |index| pair[0] + step * index as f64)
+    })
+    .collect()
+}
+
+fn get_axis(min_value: f64, max_value: f64, max_labels: f64) -> (Vec<f64>, usize) {
+  let labels = wilkinson::generate_labels(
+    min_value,
+    max_value,
+    max_labels,
+    wilkinson::LabelRange::Included,
+    wilkinson::DEFAULT_MIN_TICKS,
+    wilkinson::DEFAULT_MAX_TICKS,
+  )
+  .unwrap_or_else(|| wilkinson::nice_ticks(min_value, max_value, max_labels as usize));
+
+  let precision = labels
+    .iter()
+    .map(|v| get_precision(*v))
+    .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+    .unwrap_or(1);
+
+  (labels, precision)
+}
+
+/// Positive bounds for a log axis from any range, ordered.
+fn log_range(min_value: f64, max_value: f64) -> (f64, f64) {
+  let (min_value, max_value) = ordered(min_value, max_value);
+
+  if max_value <= 0.0 {
+    (1.0, 10.0)
+  } else if min_value <= 0.0 {
+    (max_value / 10f64.powf(LOG_FALLBACK_DECADES), max_value)
+  } else if min_value == max_value {
+    (min_value / 10.0, max_value * 10.0)
+  } else {
+    (min_value, max_value)
+  }
+}
+
+/// Labels of a log axis between positive `min` and `max`: 1, 2 and 5 times each power of ten
+/// when at most `max_count` of them fit, or else only powers of ten, every so many decades.
+fn log_ticks(min: f64, max: f64, max_count: usize) -> Vec<f64> {
+  let first = min.log10().floor() as i32;
+  let last = max.log10().ceil() as i32;
+  let max_count = max_count.max(2);
+
+  let in_range = |value: &f64| *value >= min * (1.0 - 1e-9) && *value <= max * (1.0 + 1e-9);
+  let with_steps = |steps: &[f64], every: i32| -> Vec<f64> {
+    (first..=last)
+      .filter(|exponent| exponent.rem_euclid(every) == 0)
+      .flat_map(|exponent| {
+        let power = 10f64.powi(exponent);
+        steps.iter().map(move |step| step * power)
+      })
+      .filter(in_range)
+      .collect()
+  };
+
+  let ticks = with_steps(&LOG_STEPS, 1);
+  if ticks.len() <= max_count {
+    return ticks;
+  }
+
+  let decades = (last - first + 1) as usize;
+  let every = decades.div_ceil(max_count).max(1) as i32;
+  with_steps(&LOG_STEPS[..1], every)
+}
+
+/// Formats a value of a log axis: labels exactly, and other values to `LOG_SIGNIFICANT_DIGITS`,
+/// in scientific notation when very large or small.
+fn format_log(value: f64) -> String {
+  let magnitude = value.abs();
+  if magnitude >= LOG_SCIENTIFIC_ABOVE || magnitude > 0.0 && magnitude < LOG_SCIENTIFIC_BELOW {
+    let exponent = magnitude.log10().floor() as i32;
+    let mantissa = value / 10f64.powi(exponent);
+    let digits = (LOG_SIGNIFICANT_DIGITS - 1) as usize;
+    let mantissa = format!(
+      "{:.prec$}",
+      mantissa,
+      prec = get_precision(mantissa).min(digits)
+    );
+    return format!("{}e{}", mantissa, exponent);
+  }
+
+  let significant = if magnitude > 0.0 {
+    (LOG_SIGNIFICANT_DIGITS - 1 - magnitude.log10().floor() as i32).max(0) as usize
+  } else {
+    0
+  };
+  format!(
+    "{:.prec$}",
+    value,
+    prec = get_precision(value).min(significant)
+  )
+}
+
+/// Most decimals a value is ever shown with, reached by values that aren't exact in decimal.
+const MAX_PRECISION: usize = 15;
+
+/// Number of decimals needed to show a value exactly, capped to `MAX_PRECISION`. Non-finite
+/// values need none.
+pub(crate) fn get_precision<N>(i: N) -> usize
+where
+  N: Num + AsPrimitive<f64>,
+{
+  let i = i.as_();
+  if !i.is_finite() {
+    return 0;
+  }
+
+  let mut e = 1.0f64;
+  let mut precision = 0;
+  while precision < MAX_PRECISION && (i * e).round() / e != i {
+    e *= 10.;
+    precision += 1;
+  }
+
+  precision
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_unbounded_layout() {
+    let unbounded = BoxConstraints::new(Size::ZERO, Size::new(f64::INFINITY, f64::INFINITY));
+    assert_eq!(chart_size(&unbounded), DEFAULT_SIZE);
+
+    let tall = BoxConstraints::new(Size::ZERO, Size::new(f64::INFINITY, 120.0));
+    assert_eq!(chart_size(&tall), Size::new(400.0, 120.0));
+
+    let tight = BoxConstraints::tight(Size::new(200.0, 100.0));
+    assert_eq!(chart_size(&tight), Size::new(200.0, 100.0));
+  }
+
+  #[test]
+  fn test_reversed_axes() {
+    let bounds = DataBounds::from_points(vec![(-10.0, 1.0), (0.0, 9.0)].into_iter());
+    let settings = AxisSettings {
+      reversed_x: true,
+      reversed_y: true,
+      ..AxisSettings::default()
+    };
+    let geometry = settings.geometry(Size::new(400.0, 300.0), &bounds, false);
+    let plot = geometry.plot;
+
+    assert_eq!(geometry.to_pixel_x(-10.0), plot.x1);
+    assert_eq!(geometry.to_pixel_x(0.0), plot.x0);
+    assert_eq!(geometry.to_pixel_y(geometry.min_y), plot.y0);
+    assert_eq!(geometry.to_pixel_y(geometry.max_y), plot.y1);
+
+    let point = Point::new(130.0, 70.0);
+    let x = geometry.to_value_x(point.x);
+    let y = geometry.to_value_y(point.y);
+    assert!((geometry.to_pixel_x(x) - point.x).abs() < 1e-9);
+    assert!((geometry.to_pixel_y(y) - point.y).abs() < 1e-9);
+    assert_eq!(geometry.pixel_to_data(point), (x, y));
+    assert!(geometry.data_to_pixel(x, y).distance(point) < 1e-9);
+  }
+
+  #[test]
+  fn test_rotated_x_labels() {
+    // A month of daily timestamps, whose labels are too wide to fit one per day horizontally
+    let bounds =
+      DataBounds::extent(vec![(1_583_020_800.0, 0.0), (1_585_612_800.0, 1.0)].into_iter());
+    let horizontal = AxisSettings {
+      time_x: true,
+      ..AxisSettings::default()
+    };
+    let rotated = AxisSettings {
+      time_x: true,
+      x_label_rotation: 60.0,
+      ..AxisSettings::default()
+    };
+    let size = Size::new(600.0, 400.0);
+    let flat = horizontal.geometry(size, &bounds, false);
+    let steep = rotated.geometry(size, &bounds, false);
+
+    assert!(steep.x_labels().len() > flat.x_labels().len());
+
+    // The bottom grows to fit the rotated text, which doesn't move the ticks horizontally
+    assert!(steep.plot.y1 < flat.plot.y1);
+    assert_eq!(steep.plot.x0, flat.plot.x0);
+    assert_eq!(steep.plot.x1, flat.plot.x1);
+    for label in steep.x_labels() {
+      assert_eq!(label.position, steep.to_pixel_x(label.value));
+    }
+  }
+
+  #[test]
+  fn test_label_cache() {
+    let cache = LabelCache::default();
+    assert_eq!(cache.get_axis(0.0, 10.0, 5.0), get_axis(0.0, 10.0, 5.0));
+    assert_eq!(cache.get_axis(0.0, 10.0, 5.0), get_axis(0.0, 10.0, 5.0));
+    assert_eq!(cache.entries.borrow().len(), 1);
+
+    // Another size or range is another entry, and the oldest ones make way for new ones
+    assert_eq!(cache.get_axis(0.0, 10.0, 3.0), get_axis(0.0, 10.0, 3.0));
+    assert_eq!(cache.entries.borrow().len(), 2);
+    for max in 0..LABEL_CACHE_SIZE {
+      cache.get_axis(0.0, 20.0 + max as f64, 5.0);
+    }
+    assert_eq!(cache.entries.borrow().len(), LABEL_CACHE_SIZE);
+    assert!(cache
+      .entries
+      .borrow()
+      .iter()
+      .all(|entry| entry.key.1 != 10f64.to_bits()));
+  }
+
+  #[test]
+  fn test_log_ticks() {
+    assert_eq!(
+      log_ticks(1.0, 100.0, 20),
+      vec![1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0]
+    );
+    // Only powers of ten when the in-between labels don't fit, every other one if need be
+    assert_eq!(log_ticks(1.0, 1e4, 5), vec![1.0, 10.0, 100.0, 1e3, 1e4]);
+    assert_eq!(log_ticks(1.0, 1e8, 5), vec![1.0, 100.0, 1e4, 1e6, 1e8]);
+    assert_eq!(log_ticks(0.03, 0.3, 10), vec![0.05, 0.1, 0.2]);
+
+    assert_eq!(format_log(0.05), "0.05");
+    assert_eq!(format_log(20.0), "20");
+    assert_eq!(format_log(123.456), "123");
+    assert_eq!(format_log(0.012345), "0.0123");
+    assert_eq!(format_log(2e7), "2e7");
+    assert_eq!(format_log(0.0005), "5e-4");
+
+    let bounds = DataBounds::extent(vec![(0.0, 1.0), (1.0, 1000.0)].into_iter());
+    let settings = AxisSettings {
+      log_y: true,
+      ..AxisSettings::default()
+    };
+    let geometry = settings.geometry(Size::new(400.0, 300.0), &bounds, false);
+    // Each decade takes the same height
+    let decade = geometry.to_pixel_y(1.0) - geometry.to_pixel_y(10.0);
+    assert!((geometry.to_pixel_y(100.0) - geometry.to_pixel_y(1000.0) - decade).abs() < 1e-9);
+    assert!((geometry.to_value_y(geometry.to_pixel_y(42.0)) - 42.0).abs() < 1e-9);
+    assert!(geometry.is_minor_y(20.0));
+    assert!(!geometry.is_minor_y(100.0));
+  }
+
+  #[test]
+  fn test_category_axis() {
+    let months: Vec<String> = ["Jan", "Feb", "Mar", "Apr", "May", "Jun"]
+      .iter()
+      .map(|month| month.to_string())
+      .collect();
+    let bounds = DataBounds::extent(vec![(0.0, 1.0), (5.0, 2.0)].into_iter());
+    let settings = AxisSettings::default();
+
+    let wide = settings.resolve_geometry(Size::new(600.0, 300.0), &bounds, None, &months, false);
+    let texts: Vec<String> = wide
+      .x_labels()
+      .into_iter()
+      .map(|label| label.text)
+      .collect();
+    assert_eq!(texts, months);
+    assert_eq!(wide.format_x_readout(1.2), "Feb");
+    assert_eq!(wide.format_x(-3.0), "");
+
+    // Too narrow for every label, so only every other one is drawn
+    let narrow = settings.resolve_geometry(Size::new(180.0, 300.0), &bounds, None, &months, false);
+    let texts: Vec<String> = narrow
+      .x_labels()
+      .into_iter()
+      .map(|label| label.text)
+      .collect();
+    assert_eq!(texts, ["Jan", "Mar", "May"]);
+  }
+
+  #[test]
+  fn test_zero_line() {
+    let size = Size::new(400.0, 300.0);
+    let settings = AxisSettings::default();
+
+    let crossing = DataBounds::extent(vec![(-5.0, -2.0), (5.0, 8.0)].into_iter());
+    let geometry = settings.geometry(size, &crossing, false);
+    assert_eq!(geometry.zero_x(), Some(geometry.plot.center().x));
+    assert_eq!(geometry.zero_y(), Some(geometry.to_pixel_y(0.0)));
+
+    // Ranges starting at zero are already framed by the plot
+    let positive = DataBounds::extent(vec![(0.0, 0.0), (5.0, 8.0)].into_iter());
+    let geometry = settings.geometry(size, &positive, false);
+    assert_eq!((geometry.zero_x(), geometry.zero_y()), (None, None));
+  }
+
+  #[test]
+  fn test_negative_padding() {
+    // Scaling the extremes would pull -10 up to -9.5 and clip it
+    let bounds = DataBounds::from_points(vec![(0.0, -10.0), (1.0, -2.0)].into_iter());
+    assert!(bounds.min_y <= -10.0);
+    assert!(bounds.max_y >= -2.0);
+    assert!((bounds.min_y + 10.4).abs() < 1e-9);
+    assert!((bounds.max_y + 1.6).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_get_precision() {
+    assert_eq!(get_precision(2.0), 0);
+    assert_eq!(get_precision(0.25), 2);
+    assert_eq!(get_precision(1.0 / 3.0), MAX_PRECISION);
+    assert_eq!(get_precision(1e300), 0);
+    assert_eq!(get_precision(1e-300), MAX_PRECISION);
+    assert_eq!(get_precision(f64::NAN), 0);
+    assert_eq!(get_precision(f64::INFINITY), 0);
+  }
+}
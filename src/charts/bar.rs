@@ -0,0 +1,902 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A chart comparing one value per category, with the categories listed down the Y axis.
+
+use std::fmt::Display;
+
+use druid::piet::{FontBuilder, PietTextLayout, Text, TextLayout, TextLayoutBuilder};
+use druid::widget::prelude::*;
+use druid::{kurbo::BezPath, theme, Color, Data, Point, Rect};
+use num_traits::{AsPrimitive, Num};
+
+use crate::charts::axis::{
+  self, chart_size, AxisScale, AxisSettings, DEFAULT_FOREGROUND, DEFAULT_Y_PADDING,
+};
+#[cfg(feature = "png")]
+use crate::charts::raster;
+use crate::charts::svg::{SvgDocument, TextAnchor};
+use crate::charts::theme as chart_theme;
+
+pub use crate::charts::axis::GridConfig;
+
+/// Fraction of the height of each bar in a group left empty between the bars of a group.
+const SERIES_GAP: f64 = 0.15;
+
+/// A category and its value, or one value per series when the bars are grouped.
+#[derive(Clone, Debug)]
+pub struct Bar<V> {
+  label: String,
+  values: Vec<V>,
+}
+
+/// A set of values, one per category, drawn side by side with the other series in each group.
+#[derive(Clone, Debug)]
+pub struct BarSeries {
+  name: String,
+  color: Color,
+}
+
+#[derive(Clone, Debug)]
+pub struct BarChartData<V>
+where
+  V: Data,
+{
+  title: Option<String>,
+  bars: Vec<Bar<V>>,
+  color: Color,
+  /// Named series of grouped bars, empty for a single unnamed series in `color`.
+  series: Vec<BarSeries>,
+  /// Colors each bar from its value, in place of the color of its series.
+  color_fn: Option<fn(&V) -> Color>,
+}
+
+pub struct HorizontalBarChart {
+  cursor_pos: Point,
+  settings: BarChartSettings,
+}
+
+struct BarChartSettings {
+  axis: AxisSettings,
+  /// Fraction of each category's band left empty between bars.
+  bar_gap: f64,
+  data_labels: bool,
+}
+
+/// Plot area and value scale resolved for a given widget size.
+struct BarGeometry {
+  plot: Rect,
+  header_height: f64,
+  scale: AxisScale,
+  /// Number of categories, each taking an equal band of the plot height.
+  count: usize,
+  /// Number of bars in each category.
+  series: usize,
+}
+
+impl HorizontalBarChart {
+  pub fn new() -> Self {
+    Self {
+      cursor_pos: Point::new(-1.0, -1.0),
+      settings: BarChartSettings {
+        axis: AxisSettings::default(),
+        bar_gap: 0.2,
+        data_labels: false,
+      },
+    }
+  }
+
+  pub fn with_padding(mut self, padding: f64) -> Self {
+    self.settings.axis.set_padding(padding);
+    self
+  }
+
+  pub fn with_padding_top(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_top = padding;
+    self
+  }
+
+  pub fn with_padding_bottom(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_bottom = padding;
+    self
+  }
+
+  /// Minimum padding left of the plot. It grows to fit the category labels.
+  pub fn with_padding_left(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_left = padding;
+    self
+  }
+
+  pub fn with_padding_right(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_right = padding;
+    self
+  }
+
+  pub fn with_font_size(mut self, font_size: f64) -> Self {
+    self.settings.axis.font_size = font_size;
+    self
+  }
+
+  /// Fraction of each category's height left empty around its bar, between 0 and 1.
+  pub fn with_bar_gap(mut self, gap: f64) -> Self {
+    self.settings.bar_gap = gap.clamp(0.0, 1.0);
+    self
+  }
+
+  /// Prints the value of every bar past its end. Labels that would cross the edge of the plot
+  /// are left out.
+  pub fn with_data_labels(mut self, show: bool) -> Self {
+    self.settings.data_labels = show;
+    self
+  }
+
+  /// Only `show_x` applies, drawing a vertical line at each value tick.
+  pub fn with_grid(mut self, grid: GridConfig) -> Self {
+    self.settings.axis.grid = grid;
+    self
+  }
+
+  /// Fills the plot area with `color`, underneath the gridlines and the bars.
+  pub fn with_plot_background(mut self, color: Color) -> Self {
+    self.settings.axis.plot_background = Some(color);
+    self
+  }
+
+  /// Resolves the plot area and value scale, with the left padding grown to fit category labels
+  /// `label_width` wide.
+  fn geometry<V>(&self, size: Size, data: &BarChartData<V>, label_width: f64) -> BarGeometry
+  where
+    V: Data + AsPrimitive<f64>,
+  {
+    let axis = &self.settings.axis;
+    let header_height = if data.title.is_some() {
+      axis.header_height
+    } else {
+      0.0
+    };
+
+    let left = axis.padding_left.max(label_width + axis.tick_length * 3.0);
+    let plot = Rect::new(
+      left,
+      axis.padding_top + header_height,
+      size.width - axis.padding_right,
+      size.height - axis.footer_height - axis.padding_bottom,
+    );
+
+    let (min, max) = value_range(&data.bars);
+    let max_labels = (plot.width() / (axis.font_size / 0.3)).floor().max(1.0);
+
+    BarGeometry {
+      plot,
+      header_height,
+      scale: AxisScale::new(&axis.label_cache, min, max, plot.width(), max_labels),
+      count: data.bars.len(),
+      series: data.series_count(),
+    }
+  }
+
+  /// Paints the frame, the value ticks and gridlines along the bottom, and the category labels.
+  fn paint_axes(
+    &self,
+    ctx: &mut PaintCtx,
+    labels: &[PietTextLayout],
+    geometry: &BarGeometry,
+    env: &Env,
+  ) {
+    let axis = &self.settings.axis;
+    let plot = geometry.plot;
+    let foreground = chart_theme::axis_color(env);
+
+    if let Some(background) = &axis.plot_background {
+      ctx.fill(plot, background);
+    }
+    ctx.stroke(plot, &foreground, 1.0);
+
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), axis.font_size)
+      .build()
+      .unwrap();
+
+    let mut ticks = BezPath::new();
+    let mut grid = BezPath::new();
+
+    for value in geometry.scale.labels.iter() {
+      let position_x = geometry.to_pixel(*value);
+      let layout = ctx
+        .text()
+        .new_text_layout(
+          &label_font,
+          &format!("{:.prec$}", value, prec = geometry.scale.precision),
+          f64::INFINITY,
+        )
+        .build()
+        .unwrap();
+
+      ctx.draw_text(
+        &layout,
+        (
+          position_x - layout.width() / 2.0,
+          plot.y1 + axis.tick_length + axis.font_size,
+        ),
+        &foreground,
+      );
+
+      ticks.move_to((position_x, plot.y1));
+      ticks.line_to((position_x, plot.y1 + axis.tick_length));
+
+      if axis.grid.show_x {
+        grid.move_to((position_x, plot.y0));
+        grid.line_to((position_x, plot.y1));
+      }
+    }
+
+    ctx.stroke(ticks, &foreground, 1.0);
+    let grid_color = axis
+      .grid
+      .color
+      .clone()
+      .unwrap_or_else(|| foreground.clone());
+    ctx.stroke(grid, &grid_color.with_alpha(axis.grid.alpha), 1.0);
+
+    // Category labels are left aligned, each centered on its band
+    for (index, layout) in labels.iter().enumerate() {
+      let (top, height) = geometry.band(index);
+      ctx.draw_text(
+        layout,
+        (
+          axis.tick_length,
+          top + height / 2.0 + axis.font_size * 0.334,
+        ),
+        &foreground,
+      );
+    }
+  }
+
+  fn paint_bars<V>(
+    &self,
+    ctx: &mut PaintCtx,
+    data: &BarChartData<V>,
+    geometry: &BarGeometry,
+    env: &Env,
+  ) where
+    V: Data + AsPrimitive<f64> + Display,
+  {
+    for (index, series, value) in bar_values(&data.bars) {
+      let rect = geometry.bar_rect(index, series, value.as_(), self.settings.bar_gap);
+      ctx.fill(rect, &data.bar_color(series, value).with_alpha(0.8));
+    }
+
+    if self.settings.data_labels {
+      self.paint_data_labels(ctx, data, geometry, env);
+    }
+
+    let (hovered, series) =
+      match hovered_bar(&data.bars, geometry, self.cursor_pos, self.settings.bar_gap) {
+        Some(hovered) => hovered,
+        None => return,
+      };
+
+    // Emphasize the hovered bar
+    let bar = &data.bars[hovered];
+    let value = &bar.values[series];
+    let rect = geometry.bar_rect(hovered, series, value.as_(), self.settings.bar_gap);
+    ctx.fill(rect, &data.bar_color(series, value));
+    ctx.stroke(rect, &chart_theme::axis_color(env), 1.0);
+
+    let font_size = self.settings.axis.font_size;
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+
+    // Grouped bars also name the category and series, which the color alone doesn't tell
+    let precision = value_precision(&data.bars);
+    let text = match data.series.get(series) {
+      Some(series) => format!(
+        "{}, {}: {:.prec$}",
+        bar.label,
+        series.name,
+        value,
+        prec = precision
+      ),
+      None => format!("{:.prec$}", value, prec = precision),
+    };
+    let layout = ctx
+      .text()
+      .new_text_layout(&label_font, &text, f64::INFINITY)
+      .build()
+      .unwrap();
+
+    let text_height = if let Some(metric) = layout.line_metric(0) {
+      font_size - (metric.cumulative_height - metric.baseline.floor())
+    } else {
+      font_size
+    };
+
+    // Beside the end of the bar, flipping inside near the right edge
+    let end = if value.as_() < 0.0 { rect.x0 } else { rect.x1 };
+    let width = layout.width() + 10.0;
+    let left = if end + 4.0 + width < geometry.plot.x1 {
+      end + 4.0
+    } else {
+      end - 4.0 - width
+    };
+    let center_y = rect.center().y;
+
+    let tooltip = Rect::from_points(
+      Point::new(left, center_y - 5.0 - text_height / 2.0),
+      Point::new(left + width, center_y + 5.0 + text_height / 2.0),
+    );
+
+    ctx.fill(tooltip, &chart_theme::tooltip_bg(env));
+
+    ctx.draw_text(
+      &layout,
+      (left + 5.0, center_y + (font_size * 0.334)),
+      &chart_theme::tooltip_fg(env),
+    );
+  }
+
+  fn paint_data_labels<V>(
+    &self,
+    ctx: &mut PaintCtx,
+    data: &BarChartData<V>,
+    geometry: &BarGeometry,
+    env: &Env,
+  ) where
+    V: Data + AsPrimitive<f64> + Display,
+  {
+    let font_size = self.settings.axis.font_size;
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+    let precision = value_precision(&data.bars);
+
+    for (index, series, value) in bar_values(&data.bars) {
+      let layout = ctx
+        .text()
+        .new_text_layout(
+          &label_font,
+          &format!("{:.prec$}", value, prec = precision),
+          f64::INFINITY,
+        )
+        .build()
+        .unwrap();
+
+      let rect = geometry.bar_rect(index, series, value.as_(), self.settings.bar_gap);
+      let size = Size::new(layout.width(), font_size);
+      if let Some(label) = data_label_rect(rect, value.as_(), size, geometry.plot) {
+        ctx.draw_text(
+          &layout,
+          (label.x0, label.center().y + font_size * 0.334),
+          &chart_theme::axis_color(env),
+        );
+      }
+    }
+  }
+
+  /// Renders the chart as a standalone SVG document of the given size.
+  pub fn render_svg<V>(&self, data: &BarChartData<V>, size: Size) -> String
+  where
+    V: Data + AsPrimitive<f64> + Display,
+  {
+    self.export(data, size).finish()
+  }
+
+  /// Renders the chart as a PNG image of the given size, laid out as `render_svg`.
+  #[cfg(feature = "png")]
+  pub fn render_png<V>(&self, data: &BarChartData<V>, size: Size) -> Vec<u8>
+  where
+    V: Data + AsPrimitive<f64> + Display,
+  {
+    raster::rasterize(&self.export(data, size))
+  }
+
+  /// Draws the chart into a document shared by the SVG and PNG exports.
+  fn export<V>(&self, data: &BarChartData<V>, size: Size) -> SvgDocument
+  where
+    V: Data + AsPrimitive<f64> + Display,
+  {
+    let axis = &self.settings.axis;
+    let font_size = axis.font_size;
+    // Category labels aren't measured, so the left padding fits an estimate of their width
+    let label_width = data
+      .bars
+      .iter()
+      .map(|bar| bar.label.chars().count())
+      .max()
+      .unwrap_or(0) as f64
+      * font_size
+      * 0.6;
+
+    let geometry = self.geometry(size, data, label_width);
+    let plot = geometry.plot;
+    let mut svg = SvgDocument::new(size);
+
+    if let Some(title) = &data.title {
+      axis::render_svg_title(
+        &mut svg,
+        title,
+        Rect::new(0.0, 0.0, size.width, geometry.header_height),
+        &DEFAULT_FOREGROUND,
+      );
+    }
+
+    if let Some(background) = &axis.plot_background {
+      svg.fill_rect(plot, background);
+    }
+    svg.stroke_rect(plot, &DEFAULT_FOREGROUND, 1.0);
+
+    let mut ticks = BezPath::new();
+    let mut grid = BezPath::new();
+
+    for value in geometry.scale.labels.iter() {
+      let position_x = geometry.to_pixel(*value);
+
+      svg.text(
+        &format!("{:.prec$}", value, prec = geometry.scale.precision),
+        Point::new(position_x, plot.y1 + axis.tick_length + font_size),
+        TextAnchor::Middle,
+        font_size,
+        &DEFAULT_FOREGROUND,
+      );
+      ticks.move_to((position_x, plot.y1));
+      ticks.line_to((position_x, plot.y1 + axis.tick_length));
+
+      if axis.grid.show_x {
+        grid.move_to((position_x, plot.y0));
+        grid.line_to((position_x, plot.y1));
+      }
+    }
+
+    svg.stroke_path(&ticks, &DEFAULT_FOREGROUND, 1.0);
+    let grid_color = axis.grid.color.clone().unwrap_or(DEFAULT_FOREGROUND);
+    svg.stroke_path(&grid, &grid_color.with_alpha(axis.grid.alpha), 1.0);
+
+    for (index, bar) in data.bars.iter().enumerate() {
+      let (top, height) = geometry.band(index);
+      svg.text(
+        &bar.label,
+        Point::new(axis.tick_length, top + height / 2.0 + font_size * 0.334),
+        TextAnchor::Start,
+        font_size,
+        &DEFAULT_FOREGROUND,
+      );
+    }
+
+    for (index, series, value) in bar_values(&data.bars) {
+      let rect = geometry.bar_rect(index, series, value.as_(), self.settings.bar_gap);
+      svg.fill_rect(rect, &data.bar_color(series, value).with_alpha(0.8));
+    }
+
+    if self.settings.data_labels {
+      let precision = value_precision(&data.bars);
+
+      for (index, series, value) in bar_values(&data.bars) {
+        let text = format!("{:.prec$}", value, prec = precision);
+        let size = Size::new(text.chars().count() as f64 * font_size * 0.6, font_size);
+        let rect = geometry.bar_rect(index, series, value.as_(), self.settings.bar_gap);
+
+        if let Some(label) = data_label_rect(rect, value.as_(), size, plot) {
+          svg.text(
+            &text,
+            Point::new(label.x0, label.center().y + font_size * 0.334),
+            TextAnchor::Start,
+            font_size,
+            &DEFAULT_FOREGROUND,
+          );
+        }
+      }
+    }
+
+    svg
+  }
+}
+
+impl Default for HorizontalBarChart {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl BarGeometry {
+  fn to_pixel(&self, value: f64) -> f64 {
+    self.plot.x0 + (value - self.scale.min) * self.scale.proportion
+  }
+
+  /// Top and height of the band of a category.
+  fn band(&self, index: usize) -> (f64, f64) {
+    let height = self.plot.height() / self.count.max(1) as f64;
+    (self.plot.y0 + height * index as f64, height)
+  }
+
+  /// Rectangle of the bar of a series in a category, extending from zero to its value. The
+  /// bars of a category share its band, less `gap` around them, with a smaller gap in between.
+  fn bar_rect(&self, index: usize, series: usize, value: f64, gap: f64) -> Rect {
+    let (top, height) = self.band(index);
+    let inset = height * gap / 2.0;
+
+    let slot = (height - inset * 2.0) / self.series.max(1) as f64;
+    let slot_top = top + inset + slot * series as f64;
+    let inner = if self.series > 1 {
+      slot * SERIES_GAP / 2.0
+    } else {
+      0.0
+    };
+
+    Rect::new(
+      self.to_pixel(0.0),
+      slot_top + inner,
+      self.to_pixel(value),
+      slot_top + slot - inner,
+    )
+    .abs()
+  }
+}
+
+impl<V> Bar<V> {
+  pub fn new(label: impl Into<String>, value: V) -> Self {
+    Self {
+      label: label.into(),
+      values: vec![value],
+    }
+  }
+
+  /// A category with one value per series, in the order the series were added. Missing values
+  /// leave a gap in the group.
+  pub fn grouped(label: impl Into<String>, values: Vec<V>) -> Self {
+    Self {
+      label: label.into(),
+      values,
+    }
+  }
+}
+
+impl BarSeries {
+  pub fn new(name: impl Into<String>, color: Color) -> Self {
+    Self {
+      name: name.into(),
+      color,
+    }
+  }
+}
+
+impl<V> BarChartData<V>
+where
+  V: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+{
+  pub fn new(color: Color) -> Self {
+    Self {
+      title: None,
+      bars: Vec::new(),
+      color,
+      series: Vec::new(),
+      color_fn: None,
+    }
+  }
+
+  pub fn with_title(mut self, title: impl Into<String>) -> Self {
+    self.title = Some(title.into());
+    self
+  }
+
+  /// Adds a category below the previous ones.
+  pub fn with_bar(mut self, bar: Bar<V>) -> Self {
+    self.bars.push(bar);
+    self
+  }
+
+  /// Adds a series, drawn below the previous ones within each category. Its values are those at
+  /// the same position in every `Bar::grouped`. Values past the last series are drawn in the
+  /// color given to `new`.
+  pub fn with_series(mut self, series: BarSeries) -> Self {
+    self.series.push(series);
+    self
+  }
+
+  /// Colors every bar by its value, e.g. green above a target and red below it, instead of by
+  /// its series.
+  pub fn with_color_fn(mut self, color_fn: fn(&V) -> Color) -> Self {
+    self.color_fn = Some(color_fn);
+    self
+  }
+}
+
+impl<V> BarChartData<V>
+where
+  V: Data,
+{
+  fn series_count(&self) -> usize {
+    let values = self.bars.iter().map(|bar| bar.values.len()).max();
+    values.unwrap_or(0).max(self.series.len()).max(1)
+  }
+
+  fn series_color(&self, series: usize) -> Color {
+    self
+      .series
+      .get(series)
+      .map_or_else(|| self.color.clone(), |series| series.color.clone())
+  }
+
+  fn bar_color(&self, series: usize, value: &V) -> Color {
+    match self.color_fn {
+      Some(color_fn) => color_fn(value),
+      None => self.series_color(series),
+    }
+  }
+}
+
+impl<V> Data for BarChartData<V>
+where
+  V: AsPrimitive<f64> + Data + PartialEq,
+{
+  fn same(&self, other: &Self) -> bool {
+    self.title == other.title
+      && self.color.as_rgba_u32() == other.color.as_rgba_u32()
+      && self.color_fn.map(|f| f as usize) == other.color_fn.map(|f| f as usize)
+      && self.bars.len() == other.bars.len()
+      && self
+        .bars
+        .iter()
+        .zip(other.bars.iter())
+        .all(|(a, b)| a.label == b.label && a.values == b.values)
+      && self.series.len() == other.series.len()
+      && self
+        .series
+        .iter()
+        .zip(other.series.iter())
+        .all(|(a, b)| a.name == b.name && a.color.as_rgba_u32() == b.color.as_rgba_u32())
+  }
+}
+
+impl<V> Widget<BarChartData<V>> for HorizontalBarChart
+where
+  V: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+{
+  fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut BarChartData<V>, _env: &Env) {
+    if let Event::MouseMove(e) = event {
+      self.cursor_pos = e.pos;
+      ctx.request_paint();
+    }
+  }
+
+  fn lifecycle(
+    &mut self,
+    _ctx: &mut LifeCycleCtx,
+    _event: &LifeCycle,
+    _data: &BarChartData<V>,
+    _env: &Env,
+  ) {
+  }
+
+  fn update(
+    &mut self,
+    ctx: &mut UpdateCtx,
+    old_data: &BarChartData<V>,
+    data: &BarChartData<V>,
+    _env: &Env,
+  ) {
+    if !old_data.same(data) {
+      ctx.request_paint();
+    }
+  }
+
+  fn layout(
+    &mut self,
+    _layout_ctx: &mut LayoutCtx,
+    bc: &BoxConstraints,
+    _data: &BarChartData<V>,
+    _env: &Env,
+  ) -> Size {
+    chart_size(bc)
+  }
+
+  fn paint(&mut self, ctx: &mut PaintCtx, data: &BarChartData<V>, env: &Env) {
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), self.settings.axis.font_size)
+      .build()
+      .unwrap();
+
+    let labels: Vec<PietTextLayout> = data
+      .bars
+      .iter()
+      .map(|bar| {
+        ctx
+          .text()
+          .new_text_layout(&label_font, &bar.label, f64::INFINITY)
+          .build()
+          .unwrap()
+      })
+      .collect();
+    let label_width = labels
+      .iter()
+      .map(|layout| layout.width())
+      .fold(0.0, f64::max);
+
+    let geometry = self.geometry(ctx.size(), data, label_width);
+
+    if let Some(title) = &data.title {
+      let header = Rect::new(0.0, 0.0, ctx.size().width, geometry.header_height);
+      axis::paint_title(ctx, title, header, env);
+    }
+    self.paint_axes(ctx, &labels, &geometry, env);
+    self.paint_bars(ctx, data, &geometry, env);
+  }
+}
+
+/// Every value with the index of its category and series.
+fn bar_values<V>(bars: &[Bar<V>]) -> impl Iterator<Item = (usize, usize, &V)> + Clone {
+  bars.iter().enumerate().flat_map(|(index, bar)| {
+    bar
+      .values
+      .iter()
+      .enumerate()
+      .map(move |(series, value)| (index, series, value))
+  })
+}
+
+/// Range of the value axis, across every series, always including zero so every bar starts at
+/// the axis origin. The ends away from zero are padded outwards by a fraction of the span.
+fn value_range<V: AsPrimitive<f64>>(bars: &[Bar<V>]) -> (f64, f64) {
+  let values = bar_values(bars).map(|(_, _, value)| value.as_());
+  let min = values.clone().fold(0.0, f64::min);
+  let max = values.fold(0.0, f64::max);
+
+  if min == max {
+    return (0.0, 1.0);
+  }
+
+  let pad = (max - min) * DEFAULT_Y_PADDING;
+  (
+    if min < 0.0 { min - pad } else { min },
+    if max > 0.0 { max + pad } else { max },
+  )
+}
+
+/// Decimals the values are shown with, enough for the most precise one.
+fn value_precision<V: AsPrimitive<f64>>(bars: &[Bar<V>]) -> usize {
+  bar_values(bars)
+    .map(|(_, _, value)| axis::get_precision(value.as_()))
+    .max()
+    .unwrap_or(0)
+}
+
+/// Where the label of a bar goes: past its end, right of positive bars and left of negative
+/// ones. `None` when the label wouldn't fit inside `plot`.
+fn data_label_rect(bar: Rect, value: f64, size: Size, plot: Rect) -> Option<Rect> {
+  let gap = 4.0;
+  let left = if value < 0.0 {
+    bar.x0 - gap - size.width
+  } else {
+    bar.x1 + gap
+  };
+  let label = Rect::from_origin_size((left, bar.center().y - size.height / 2.0), size);
+
+  Some(label).filter(|label| {
+    label.x0 >= plot.x0 && label.x1 <= plot.x1 && label.y0 >= plot.y0 && label.y1 <= plot.y1
+  })
+}
+
+/// Finds the bar under `cursor`, as the index of its category and of its series.
+fn hovered_bar<V: AsPrimitive<f64>>(
+  bars: &[Bar<V>],
+  geometry: &BarGeometry,
+  cursor: Point,
+  gap: f64,
+) -> Option<(usize, usize)> {
+  bar_values(bars)
+    .find(|(index, series, value)| {
+      geometry
+        .bar_rect(*index, *series, value.as_(), gap)
+        .contains(cursor)
+    })
+    .map(|(index, series, _)| (index, series))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_hovered_bar() {
+    let data = BarChartData::new(Color::WHITE)
+      .with_bar(Bar::new("Apples", 12.0))
+      .with_bar(Bar::new("Bananas", -4.0))
+      .with_bar(Bar::new("Cherries", 30.0));
+    let chart = HorizontalBarChart::new();
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &data, 120.0);
+
+    // The left padding grew past its default to fit the labels
+    assert!(geometry.plot.x0 > 120.0);
+    assert!(geometry.scale.min <= -4.0 && geometry.scale.max >= 30.0);
+
+    let (top, height) = geometry.band(0);
+    let inside = Point::new(geometry.to_pixel(6.0), top + height / 2.0);
+    assert_eq!(
+      hovered_bar(&data.bars, &geometry, inside, 0.2),
+      Some((0, 0))
+    );
+
+    // Negative bars extend left of zero
+    let (top, height) = geometry.band(1);
+    let negative = Point::new(geometry.to_pixel(-2.0), top + height / 2.0);
+    assert_eq!(
+      hovered_bar(&data.bars, &geometry, negative, 0.2),
+      Some((1, 0))
+    );
+
+    let past_end = Point::new(geometry.to_pixel(20.0), top + height / 2.0);
+    assert_eq!(hovered_bar(&data.bars, &geometry, past_end, 0.2), None);
+  }
+
+  #[test]
+  fn test_grouped_bars() {
+    let data = BarChartData::new(Color::WHITE)
+      .with_series(BarSeries::new("2019", Color::WHITE))
+      .with_series(BarSeries::new("2020", Color::BLACK))
+      .with_bar(Bar::grouped("Apples", vec![12.0, 50.0]))
+      .with_bar(Bar::grouped("Bananas", vec![8.0]));
+    let chart = HorizontalBarChart::new();
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &data, 120.0);
+
+    // The scale spans the largest value of any series
+    assert!(geometry.scale.max >= 50.0);
+
+    // Bars of a group sit in turn within the band, apart from each other
+    let (top, height) = geometry.band(0);
+    let first = geometry.bar_rect(0, 0, 12.0, 0.2);
+    let second = geometry.bar_rect(0, 1, 50.0, 0.2);
+    assert!(first.y0 > top && second.y1 < top + height);
+    assert!(first.y1 < second.y0);
+
+    let cursor = Point::new(geometry.to_pixel(30.0), second.center().y);
+    assert_eq!(
+      hovered_bar(&data.bars, &geometry, cursor, 0.2),
+      Some((0, 1))
+    );
+
+    // A missing value leaves its slot empty
+    let slot = geometry.bar_rect(1, 1, 1.0, 0.2).center();
+    assert_eq!(hovered_bar(&data.bars, &geometry, slot, 0.2), None);
+  }
+
+  #[test]
+  fn test_color_fn() {
+    fn threshold(value: &f64) -> Color {
+      if *value >= 10.0 {
+        Color::rgb8(0, 255, 0)
+      } else {
+        Color::rgb8(255, 0, 0)
+      }
+    }
+
+    let data = BarChartData::new(Color::WHITE)
+      .with_bar(Bar::new("Apples", 12.0))
+      .with_bar(Bar::new("Bananas", 4.0));
+    assert_eq!(
+      data.bar_color(0, &4.0).as_rgba_u32(),
+      Color::WHITE.as_rgba_u32()
+    );
+
+    let colored = data.clone().with_color_fn(threshold);
+    assert_eq!(colored.bar_color(0, &12.0).as_rgba_u32(), 0x00FF00FF);
+    assert_eq!(colored.bar_color(0, &4.0).as_rgba_u32(), 0xFF0000FF);
+    assert!(!data.same(&colored));
+  }
+}
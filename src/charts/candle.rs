@@ -0,0 +1,590 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A candlestick chart of open, high, low and close prices.
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+use druid::piet::{FontBuilder, Text, TextLayout, TextLayoutBuilder};
+use druid::widget::prelude::*;
+use druid::{
+  kurbo::{BezPath, Line},
+  theme, Color, Data, Insets, Point, Rect,
+};
+use num_traits::{AsPrimitive, Num};
+
+use crate::charts::axis::{chart_size, AxisSettings, ChartGeometry, DataBounds};
+#[cfg(feature = "png")]
+use crate::charts::raster;
+use crate::charts::svg::SvgDocument;
+use crate::charts::theme::{self as chart_theme, ChartTheme};
+
+pub use crate::charts::axis::{AxisSides, GridConfig, XAxisSide, YAxisSide};
+
+/// Share of the distance between two neighbouring candles taken by a body.
+const BODY_FILL: f64 = 0.7;
+
+/// The prices of one period: `(x, open, high, low, close)`.
+pub type Candle<X, P> = (X, P, P, P, P);
+
+#[derive(Clone, Debug)]
+pub struct CandleChartData<X, P>
+where
+  X: Data,
+  P: Data,
+{
+  title: Option<String>,
+  candles: Vec<Candle<X, P>>,
+}
+
+pub struct CandleChart {
+  cursor_pos: Point,
+  settings: CandleChartSettings,
+  bounds: DataBounds,
+}
+
+struct CandleChartSettings {
+  axis: AxisSettings,
+  rising_color: Color,
+  falling_color: Color,
+}
+
+impl CandleChart {
+  pub fn new() -> Self {
+    Self {
+      cursor_pos: Point::new(-1.0, -1.0),
+      settings: CandleChartSettings {
+        axis: AxisSettings::default(),
+        rising_color: Color::rgb8(0x4C, 0xAF, 0x50),
+        falling_color: Color::rgb8(0xE5, 0x39, 0x35),
+      },
+      bounds: DataBounds::default(),
+    }
+  }
+
+  pub fn with_padding(mut self, padding: f64) -> Self {
+    self.settings.axis.set_padding(padding);
+    self
+  }
+
+  pub fn with_padding_top(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_top = padding;
+    self
+  }
+
+  pub fn with_padding_bottom(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_bottom = padding;
+    self
+  }
+
+  pub fn with_padding_left(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_left = padding;
+    self
+  }
+
+  pub fn with_padding_right(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_right = padding;
+    self
+  }
+
+  /// Leaves `margin` empty around everything the chart draws, outside the padding the axis
+  /// labels are drawn in, as when aligning charts in a grid. Takes one width for all sides or an
+  /// `Insets` for each.
+  pub fn with_margin(mut self, margin: impl Into<Insets>) -> Self {
+    self.settings.axis.margin = margin.into();
+    self
+  }
+
+  pub fn with_font_size(mut self, font_size: f64) -> Self {
+    self.settings.axis.font_size = font_size;
+    self
+  }
+
+  /// Colors of the candles closing above their open, and of those closing below it. Candles
+  /// closing at their open use the rising color.
+  pub fn with_colors(mut self, rising: Color, falling: Color) -> Self {
+    self.settings.rising_color = rising;
+    self.settings.falling_color = falling;
+    self
+  }
+
+  pub fn with_grid(mut self, grid: GridConfig) -> Self {
+    self.settings.axis.grid = grid;
+    self
+  }
+
+  /// Fills the plot area with `color`, underneath the gridlines and the candles.
+  pub fn with_plot_background(mut self, color: Color) -> Self {
+    self.settings.axis.plot_background = Some(color);
+    self
+  }
+
+  /// Chooses on which sides of the plot the tick labels are drawn. The padding of a side without
+  /// labels shrinks to a small margin.
+  pub fn with_axis_sides(mut self, sides: AxisSides) -> Self {
+    self.settings.axis.axis_sides = sides;
+    self
+  }
+
+  fn update_reference_data<X, P>(&mut self, data: &CandleChartData<X, P>)
+  where
+    X: Data + AsPrimitive<f64>,
+    P: Data + AsPrimitive<f64>,
+  {
+    self.bounds = candle_bounds(&data.candles);
+  }
+
+  fn color<X, P: AsPrimitive<f64>>(&self, candle: &Candle<X, P>) -> &Color {
+    let (_, open, _, _, close) = candle;
+    if close.as_() >= open.as_() {
+      &self.settings.rising_color
+    } else {
+      &self.settings.falling_color
+    }
+  }
+
+  fn paint_candles<X, P>(
+    &self,
+    ctx: &mut PaintCtx,
+    candles: &[Candle<X, P>],
+    geometry: &ChartGeometry,
+    env: &Env,
+  ) where
+    X: AsPrimitive<f64> + Display,
+    P: AsPrimitive<f64> + Display,
+  {
+    let body_width = body_width(candles, geometry);
+
+    let hovered = if geometry.contains(self.cursor_pos) {
+      hovered_candle(candles, geometry, self.cursor_pos.x)
+    } else {
+      None
+    };
+
+    for (index, candle) in candles.iter().enumerate() {
+      let color = self.color(candle);
+      let (wick, body) = candle_shapes(candle, geometry, body_width);
+
+      ctx.stroke(wick, color, 1.0);
+      match body {
+        Some(body) => ctx.fill(body, color),
+        // Opened and closed at the same price
+        None => ctx.stroke(flat_body(candle, geometry, body_width), color, 1.0),
+      }
+
+      if hovered == Some(index) {
+        let outline = Rect::from_points(
+          Point::new(wick.p0.x - body_width / 2.0, wick.p0.y),
+          Point::new(wick.p1.x + body_width / 2.0, wick.p1.y),
+        )
+        .inflate(2.0, 2.0);
+        ctx.stroke(outline, &chart_theme::axis_color(env), 1.0);
+      }
+    }
+
+    if let Some(index) = hovered {
+      self.paint_tooltip(ctx, &candles[index], geometry, body_width, env);
+    }
+  }
+
+  /// Draws a box listing the four prices of a candle beside it.
+  fn paint_tooltip<X, P>(
+    &self,
+    ctx: &mut PaintCtx,
+    candle: &Candle<X, P>,
+    geometry: &ChartGeometry,
+    body_width: f64,
+    env: &Env,
+  ) where
+    X: AsPrimitive<f64> + Display,
+    P: AsPrimitive<f64> + Display,
+  {
+    let (x, open, high, low, close) = candle;
+    let font_size = self.settings.axis.font_size;
+
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+
+    let rows: Vec<_> = [
+      format!("{}", x),
+      format!("O {}", open),
+      format!("H {}", high),
+      format!("L {}", low),
+      format!("C {}", close),
+    ]
+    .iter()
+    .map(|row| {
+      ctx
+        .text()
+        .new_text_layout(&label_font, row, f64::INFINITY)
+        .build()
+        .unwrap()
+    })
+    .collect();
+
+    let padding = 5.0;
+    let row_height = font_size + 4.0;
+    let width = padding * 2.0 + rows.iter().map(|layout| layout.width()).fold(0.0, f64::max);
+    let height = padding * 2.0 + row_height * rows.len() as f64;
+
+    // Keep the box beside the candle, flipping to the left near the right edge
+    let center_x = geometry.to_pixel_x(x.as_());
+    let left = if center_x + body_width / 2.0 + 8.0 + width < geometry.plot.x1 {
+      center_x + body_width / 2.0 + 8.0
+    } else {
+      center_x - body_width / 2.0 - 8.0 - width
+    };
+    let top = (self.cursor_pos.y - height / 2.0)
+      .min(geometry.plot.y1 - height)
+      .max(geometry.plot.y0);
+
+    let rect = Rect::from_origin_size((left, top), (width, height));
+    ctx.fill(rect, &chart_theme::tooltip_bg(env));
+
+    for (index, layout) in rows.iter().enumerate() {
+      let center_y = top + padding + row_height * (index as f64 + 0.5);
+      ctx.draw_text(
+        layout,
+        (left + padding, center_y + font_size * 0.334),
+        &chart_theme::tooltip_fg(env),
+      );
+    }
+  }
+
+  /// Renders the chart as a standalone SVG document of the given size.
+  pub fn render_svg<X, P>(&self, data: &CandleChartData<X, P>, size: Size) -> String
+  where
+    X: Data + AsPrimitive<f64>,
+    P: Data + AsPrimitive<f64>,
+  {
+    self.export(data, size).finish()
+  }
+
+  /// Renders the chart as a PNG image of the given size, laid out as `render_svg`.
+  #[cfg(feature = "png")]
+  pub fn render_png<X, P>(&self, data: &CandleChartData<X, P>, size: Size) -> Vec<u8>
+  where
+    X: Data + AsPrimitive<f64>,
+    P: Data + AsPrimitive<f64>,
+  {
+    raster::rasterize(&self.export(data, size))
+  }
+
+  /// Draws the chart into a document shared by the SVG and PNG exports.
+  fn export<X, P>(&self, data: &CandleChartData<X, P>, size: Size) -> SvgDocument
+  where
+    X: Data + AsPrimitive<f64>,
+    P: Data + AsPrimitive<f64>,
+  {
+    let bounds = candle_bounds(&data.candles);
+    let geometry = self
+      .settings
+      .axis
+      .geometry(size, &bounds, data.title.is_some());
+    let mut svg = SvgDocument::new(size);
+
+    self.settings.axis.render_svg_axes(
+      &mut svg,
+      data.title.as_deref(),
+      &geometry,
+      &ChartTheme::druid_default(),
+    );
+
+    let body_width = body_width(&data.candles, &geometry);
+    for candle in data.candles.iter() {
+      let color = self.color(candle);
+      let (wick, body) = candle_shapes(candle, &geometry, body_width);
+
+      let mut path = BezPath::new();
+      path.move_to(wick.p0);
+      path.line_to(wick.p1);
+      svg.stroke_path(&path, color, 1.0);
+
+      match body {
+        Some(body) => svg.fill_rect(body, color),
+        None => {
+          let line = flat_body(candle, &geometry, body_width);
+          let mut path = BezPath::new();
+          path.move_to(line.p0);
+          path.line_to(line.p1);
+          svg.stroke_path(&path, color, 1.0);
+        }
+      }
+    }
+
+    svg
+  }
+}
+
+impl Default for CandleChart {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<X, P> CandleChartData<X, P>
+where
+  X: Data + AsPrimitive<f64>,
+  P: Data + AsPrimitive<f64>,
+{
+  /// Creates the data from candles given in increasing X order.
+  pub fn new(candles: Vec<Candle<X, P>>) -> Self {
+    Self {
+      title: None,
+      candles,
+    }
+  }
+
+  pub fn with_title(mut self, title: impl Into<String>) -> Self {
+    self.title = Some(title.into());
+    self
+  }
+}
+
+impl<X, P> Data for CandleChartData<X, P>
+where
+  X: Data + PartialEq,
+  P: Data + PartialEq,
+{
+  fn same(&self, other: &Self) -> bool {
+    self.title == other.title && self.candles == other.candles
+  }
+}
+
+impl<X, P> Widget<CandleChartData<X, P>> for CandleChart
+where
+  X: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+  P: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+{
+  fn event(
+    &mut self,
+    ctx: &mut EventCtx,
+    event: &Event,
+    _data: &mut CandleChartData<X, P>,
+    _env: &Env,
+  ) {
+    if let Event::MouseMove(e) = event {
+      self.cursor_pos = e.pos;
+      ctx.request_paint();
+    }
+  }
+
+  fn lifecycle(
+    &mut self,
+    _ctx: &mut LifeCycleCtx,
+    event: &LifeCycle,
+    data: &CandleChartData<X, P>,
+    _env: &Env,
+  ) {
+    if let LifeCycle::WidgetAdded = event {
+      self.update_reference_data(data);
+    }
+  }
+
+  fn update(
+    &mut self,
+    ctx: &mut UpdateCtx,
+    old_data: &CandleChartData<X, P>,
+    data: &CandleChartData<X, P>,
+    _env: &Env,
+  ) {
+    if !old_data.same(data) {
+      self.update_reference_data(data);
+      ctx.request_paint();
+    }
+  }
+
+  fn layout(
+    &mut self,
+    _layout_ctx: &mut LayoutCtx,
+    bc: &BoxConstraints,
+    _data: &CandleChartData<X, P>,
+    _env: &Env,
+  ) -> Size {
+    chart_size(bc)
+  }
+
+  fn paint(&mut self, ctx: &mut PaintCtx, data: &CandleChartData<X, P>, env: &Env) {
+    let geometry = self
+      .settings
+      .axis
+      .geometry(ctx.size(), &self.bounds, data.title.is_some());
+
+    self
+      .settings
+      .axis
+      .paint_axes(ctx, data.title.as_deref(), &geometry, env);
+    self.paint_candles(ctx, &data.candles, &geometry, env);
+  }
+}
+
+/// Bounds of the prices, with half a candle spacing of room on both ends of the X axis so the
+/// outermost bodies aren't cut by the plot frame.
+fn candle_bounds<X, P>(candles: &[Candle<X, P>]) -> DataBounds
+where
+  X: AsPrimitive<f64>,
+  P: AsPrimitive<f64>,
+{
+  let mut bounds = DataBounds::from_points(
+    candles
+      .iter()
+      .flat_map(|(x, _, high, low, _)| [(x.as_(), high.as_()), (x.as_(), low.as_())]),
+  );
+
+  let spacing = min_spacing(candles).unwrap_or(1.0);
+  bounds.min_x -= spacing / 2.0;
+  bounds.max_x += spacing / 2.0;
+
+  bounds
+}
+
+/// Smallest distance along X between two neighbouring candles, `None` with fewer than two.
+fn min_spacing<X, P>(candles: &[Candle<X, P>]) -> Option<f64>
+where
+  X: AsPrimitive<f64>,
+{
+  candles
+    .windows(2)
+    .map(|pair| (pair[1].0.as_() - pair[0].0.as_()).abs())
+    .filter(|spacing| *spacing > 0.0)
+    .min_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+}
+
+/// Width of the bodies in pixels. Each one takes a share of the distance to its closest
+/// neighbour, so they never overlap however densely the candles are packed.
+fn body_width<X, P>(candles: &[Candle<X, P>], geometry: &ChartGeometry) -> f64
+where
+  X: AsPrimitive<f64>,
+{
+  let spacing = match min_spacing(candles) {
+    Some(spacing) => (geometry.to_pixel_x(spacing) - geometry.to_pixel_x(0.0)).abs(),
+    None => geometry.plot.width() / 2.0,
+  };
+
+  (spacing * BODY_FILL).max(1.0)
+}
+
+/// The wick from the high to the low, and the body from the open to the close. There is no body
+/// when the open and close are at the same pixel.
+fn candle_shapes<X, P>(
+  candle: &Candle<X, P>,
+  geometry: &ChartGeometry,
+  body_width: f64,
+) -> (Line, Option<Rect>)
+where
+  X: AsPrimitive<f64>,
+  P: AsPrimitive<f64>,
+{
+  let (x, open, high, low, close) = candle;
+  let center_x = geometry.to_pixel_x(x.as_());
+
+  let wick = Line::new(
+    (center_x, geometry.to_pixel_y(high.as_())),
+    (center_x, geometry.to_pixel_y(low.as_())),
+  );
+
+  let open_y = geometry.to_pixel_y(open.as_());
+  let close_y = geometry.to_pixel_y(close.as_());
+  let body = if (open_y - close_y).abs() >= 1.0 {
+    Some(Rect::new(
+      center_x - body_width / 2.0,
+      open_y.min(close_y),
+      center_x + body_width / 2.0,
+      open_y.max(close_y),
+    ))
+  } else {
+    None
+  };
+
+  (wick, body)
+}
+
+/// The line drawn in place of the body of a candle opening and closing at the same price.
+fn flat_body<X, P>(candle: &Candle<X, P>, geometry: &ChartGeometry, body_width: f64) -> Line
+where
+  X: AsPrimitive<f64>,
+  P: AsPrimitive<f64>,
+{
+  let (x, open, _, _, _) = candle;
+  let center_x = geometry.to_pixel_x(x.as_());
+  let y = geometry.to_pixel_y(open.as_());
+
+  Line::new(
+    (center_x - body_width / 2.0, y),
+    (center_x + body_width / 2.0, y),
+  )
+}
+
+/// Finds the candle closest to `pixel_x`, if it's within half a spacing of it.
+fn hovered_candle<X, P>(
+  candles: &[Candle<X, P>],
+  geometry: &ChartGeometry,
+  pixel_x: f64,
+) -> Option<usize>
+where
+  X: AsPrimitive<f64>,
+{
+  let reach = body_width(candles, geometry) / BODY_FILL / 2.0;
+
+  candles
+    .iter()
+    .enumerate()
+    .map(|(index, (x, ..))| (index, (geometry.to_pixel_x(x.as_()) - pixel_x).abs()))
+    .filter(|(_, distance)| *distance <= reach)
+    .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+    .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_candle_shapes() {
+    let candles = vec![
+      (0, 10.0, 12.0, 9.0, 11.0),
+      (1, 11.0, 11.5, 10.0, 10.5),
+      (2, 10.5, 11.0, 10.0, 10.5),
+    ];
+    let geometry =
+      AxisSettings::default().geometry(Size::new(400.0, 300.0), &candle_bounds(&candles), false);
+
+    // Bodies take a share of the spacing, leaving a gap between neighbours
+    let width = body_width(&candles, &geometry);
+    let spacing = geometry.to_pixel_x(1.0) - geometry.to_pixel_x(0.0);
+    assert!(width < spacing);
+    assert!(geometry.plot.contains(Point::new(
+      geometry.to_pixel_x(0.0) - width / 2.0,
+      geometry.plot.center().y
+    )));
+
+    let (wick, body) = candle_shapes(&candles[0], &geometry, width);
+    let body = body.unwrap();
+    assert_eq!(wick.p0.y, geometry.to_pixel_y(12.0));
+    assert_eq!(body.y0, geometry.to_pixel_y(11.0));
+    assert_eq!(body.y1, geometry.to_pixel_y(10.0));
+
+    // Equal open and close have no body
+    assert!(candle_shapes(&candles[2], &geometry, width).1.is_none());
+
+    let near_second = geometry.to_pixel_x(1.0) + spacing * 0.4;
+    assert_eq!(hovered_candle(&candles, &geometry, near_second), Some(1));
+    let outside = geometry.to_pixel_x(2.0) + spacing * 0.6;
+    assert_eq!(hovered_candle(&candles, &geometry, outside), None);
+  }
+}
@@ -0,0 +1,170 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loading of numeric columns from comma separated files, and writing of text rows.
+//!
+//! Only plain values are supported when reading: fields are split on commas and trimmed, without
+//! quoting or escaping. Blank lines are ignored. Written fields are quoted as RFC 4180 has it.
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum CsvError {
+  /// The reader failed.
+  Io(io::Error),
+  /// A row has fewer fields than the highest requested column.
+  MissingColumn { line: usize, column: usize },
+  /// A field could not be parsed as a number.
+  InvalidValue {
+    line: usize,
+    column: usize,
+    value: String,
+  },
+}
+
+impl fmt::Display for CsvError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      CsvError::Io(err) => write!(f, "failed to read CSV: {}", err),
+      CsvError::MissingColumn { line, column } => {
+        write!(f, "line {}: missing column {}", line, column)
+      }
+      CsvError::InvalidValue {
+        line,
+        column,
+        value,
+      } => write!(
+        f,
+        "line {}, column {}: invalid number {:?}",
+        line, column, value
+      ),
+    }
+  }
+}
+
+impl Error for CsvError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      CsvError::Io(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for CsvError {
+  fn from(err: io::Error) -> Self {
+    CsvError::Io(err)
+  }
+}
+
+/// Reads the `x_col` column and each of the `y_cols` columns, all zero-based. Lines are reported
+/// one-based in errors, counting the header.
+pub(crate) fn read_columns<R, X, Y>(
+  reader: R,
+  x_col: usize,
+  y_cols: &[usize],
+  has_header: bool,
+) -> Result<(Vec<X>, Vec<Vec<Y>>), CsvError>
+where
+  R: Read,
+  X: FromStr,
+  Y: FromStr,
+{
+  let mut xs = Vec::new();
+  let mut ys: Vec<Vec<Y>> = y_cols.iter().map(|_| Vec::new()).collect();
+
+  let rows = BufReader::new(reader).lines().enumerate();
+  for (index, row) in rows.skip(if has_header { 1 } else { 0 }) {
+    let row = row?;
+    if row.trim().is_empty() {
+      continue;
+    }
+
+    let line = index + 1;
+    let fields: Vec<&str> = row.split(',').map(str::trim).collect();
+
+    xs.push(parse_field(&fields, line, x_col)?);
+    for (values, column) in ys.iter_mut().zip(y_cols) {
+      values.push(parse_field(&fields, line, *column)?);
+    }
+  }
+
+  Ok((xs, ys))
+}
+
+/// Appends `fields` to `csv` as a line, quoting those holding commas, quotes or line breaks
+/// and doubling their quotes. Lines end with CRLF, as in RFC 4180.
+pub(crate) fn write_row<I>(csv: &mut String, fields: I)
+where
+  I: IntoIterator,
+  I::Item: AsRef<str>,
+{
+  for (index, field) in fields.into_iter().enumerate() {
+    if index > 0 {
+      csv.push(',');
+    }
+    csv.push_str(&quote_field(field.as_ref()));
+  }
+  csv.push_str("\r\n");
+}
+
+fn quote_field(field: &str) -> Cow<'_, str> {
+  if field.contains([',', '"', '\n', '\r']) {
+    Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+  } else {
+    Cow::Borrowed(field)
+  }
+}
+
+fn parse_field<N: FromStr>(fields: &[&str], line: usize, column: usize) -> Result<N, CsvError> {
+  let value = fields
+    .get(column)
+    .ok_or(CsvError::MissingColumn { line, column })?;
+
+  value.parse().map_err(|_| CsvError::InvalidValue {
+    line,
+    column,
+    value: value.to_string(),
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_read_columns() {
+    let input = "day,open,close\n1, 10.5, 11\n2,11,10.25\n\n3,10.25,12\n";
+
+    let (xs, ys) = read_columns::<_, i32, f64>(input.as_bytes(), 0, &[2, 1], true).unwrap();
+    assert_eq!(xs, vec![1, 2, 3]);
+    assert_eq!(ys, vec![vec![11.0, 10.25, 12.0], vec![10.5, 11.0, 10.25]]);
+
+    match read_columns::<_, i32, f64>("1,2\n3\n".as_bytes(), 0, &[1], false) {
+      Err(CsvError::MissingColumn { line: 2, column: 1 }) => {}
+      other => panic!("unexpected result {:?}", other),
+    }
+
+    match read_columns::<_, i32, f64>("x,y\n1,abc\n".as_bytes(), 0, &[1], true) {
+      Err(CsvError::InvalidValue {
+        line: 2, column: 1, ..
+      }) => {}
+      other => panic!("unexpected result {:?}", other),
+    }
+  }
+}
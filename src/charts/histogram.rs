@@ -0,0 +1,677 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A histogram counting how many samples fall in each of a run of equal bins.
+
+use druid::piet::{FontBuilder, Text, TextLayout, TextLayoutBuilder};
+use druid::widget::prelude::*;
+use druid::{theme, Color, Data, Insets, Point, Rect};
+
+use crate::charts::axis::{chart_size, AxisSettings, ChartGeometry, DataBounds, DEFAULT_Y_PADDING};
+#[cfg(feature = "png")]
+use crate::charts::raster;
+use crate::charts::svg::SvgDocument;
+use crate::charts::theme::{self as chart_theme, ChartTheme};
+
+pub use crate::charts::axis::{AxisSides, GridConfig, XAxisSide, YAxisSide};
+
+/// Number of bins when neither a count nor a width is given.
+const DEFAULT_BIN_COUNT: usize = 10;
+
+/// Most bins a bin width can split the samples into, so a tiny width can't exhaust memory. Past
+/// it, the bins widen to split the span of the samples into this many.
+const MAX_BINS: usize = 10_000;
+
+/// Opacity of the columns of overlaid sample sets, so those behind show through.
+const OVERLAY_ALPHA: f64 = 0.6;
+
+/// Gap between neighbouring bins, in pixels.
+const BIN_GAP: f64 = 1.0;
+
+/// How the columns of several sample sets share a bin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HistogramMode {
+  /// Every set is drawn across the whole bin, translucent, in the order the sets were added.
+  Overlaid,
+  /// Each set is drawn on top of the sets before it, so the columns add up to the total count.
+  Stacked,
+  /// The bin is split between the sets, side by side.
+  Grouped,
+}
+
+/// How the range of the samples is divided into bins.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Binning {
+  /// This many bins spanning from the lowest to the highest sample.
+  Count(usize),
+  /// Bins of this width, with edges at its multiples.
+  Width(f64),
+}
+
+/// One or more sets of raw samples, counted into the same bins.
+#[derive(Clone, Debug)]
+pub struct HistogramData {
+  title: Option<String>,
+  sets: Vec<SampleSet>,
+}
+
+#[derive(Clone, Debug)]
+struct SampleSet {
+  values: Vec<f64>,
+  color: Color,
+}
+
+pub struct Histogram {
+  cursor_pos: Point,
+  settings: HistogramSettings,
+  bins: Option<Bins>,
+}
+
+struct HistogramSettings {
+  axis: AxisSettings,
+  binning: Binning,
+  mode: HistogramMode,
+}
+
+/// The counts of each sample set in equal bins starting at `start`.
+#[derive(Clone, Debug, PartialEq)]
+struct Bins {
+  start: f64,
+  width: f64,
+  /// The counts of every bin, by sample set.
+  counts: Vec<Vec<usize>>,
+}
+
+impl Histogram {
+  pub fn new() -> Self {
+    Self {
+      cursor_pos: Point::new(-1.0, -1.0),
+      settings: HistogramSettings {
+        axis: AxisSettings::default(),
+        binning: Binning::Count(DEFAULT_BIN_COUNT),
+        mode: HistogramMode::Overlaid,
+      },
+      bins: None,
+    }
+  }
+
+  pub fn with_padding(mut self, padding: f64) -> Self {
+    self.settings.axis.set_padding(padding);
+    self
+  }
+
+  pub fn with_padding_top(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_top = padding;
+    self
+  }
+
+  pub fn with_padding_bottom(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_bottom = padding;
+    self
+  }
+
+  pub fn with_padding_left(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_left = padding;
+    self
+  }
+
+  pub fn with_padding_right(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_right = padding;
+    self
+  }
+
+  /// Leaves `margin` empty around everything the chart draws, outside the padding the axis
+  /// labels are drawn in, as when aligning charts in a grid. Takes one width for all sides or an
+  /// `Insets` for each.
+  pub fn with_margin(mut self, margin: impl Into<Insets>) -> Self {
+    self.settings.axis.margin = margin.into();
+    self
+  }
+
+  pub fn with_font_size(mut self, font_size: f64) -> Self {
+    self.settings.axis.font_size = font_size;
+    self
+  }
+
+  /// Splits the range from the lowest to the highest sample into `count` bins of equal width.
+  /// Defaults to 10 bins.
+  pub fn with_bin_count(mut self, count: usize) -> Self {
+    self.settings.binning = Binning::Count(count.max(1));
+    self
+  }
+
+  /// Counts the samples in bins `width` wide, with edges at the multiples of the width, as for
+  /// bins of whole units. Widths that aren't positive leave the bin count in place, and widths
+  /// splitting the samples into more than 10,000 bins are widened to make 10,000.
+  pub fn with_bin_width(mut self, width: f64) -> Self {
+    if width > 0.0 && width.is_finite() {
+      self.settings.binning = Binning::Width(width);
+    }
+    self
+  }
+
+  /// Chooses how the columns of several sample sets share a bin. Overlaid by default.
+  pub fn with_mode(mut self, mode: HistogramMode) -> Self {
+    self.settings.mode = mode;
+    self
+  }
+
+  pub fn with_grid(mut self, grid: GridConfig) -> Self {
+    self.settings.axis.grid = grid;
+    self
+  }
+
+  /// Fills the plot area with `color`, underneath the gridlines and the columns.
+  pub fn with_plot_background(mut self, color: Color) -> Self {
+    self.settings.axis.plot_background = Some(color);
+    self
+  }
+
+  /// Chooses on which sides of the plot the tick labels are drawn. The padding of a side without
+  /// labels shrinks to a small margin.
+  pub fn with_axis_sides(mut self, sides: AxisSides) -> Self {
+    self.settings.axis.axis_sides = sides;
+    self
+  }
+
+  fn update_reference_data(&mut self, data: &HistogramData) {
+    self.bins = bin_samples(&data.sets, self.settings.binning);
+  }
+
+  /// Color of the columns of a sample set, translucent when overlaid.
+  fn color(&self, set: &SampleSet) -> Color {
+    match self.settings.mode {
+      HistogramMode::Overlaid => set.color.clone().with_alpha(OVERLAY_ALPHA),
+      HistogramMode::Stacked | HistogramMode::Grouped => set.color.clone(),
+    }
+  }
+
+  fn geometry(&self, size: Size, bins: Option<&Bins>, has_title: bool) -> ChartGeometry {
+    let bounds = histogram_bounds(bins, self.settings.mode);
+    self.settings.axis.geometry(size, &bounds, has_title)
+  }
+
+  /// Draws a box beside the hovered bin with its range and the count of each set, in the color
+  /// of the set.
+  fn paint_tooltip(
+    &self,
+    ctx: &mut PaintCtx,
+    data: &HistogramData,
+    bins: &Bins,
+    bin: usize,
+    geometry: &ChartGeometry,
+    env: &Env,
+  ) {
+    let font_size = self.settings.axis.font_size;
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+
+    let (low, high) = bins.edges(bin);
+    let rows: Vec<_> = std::iter::once((
+      format!("{} to {}", geometry.format_x(low), geometry.format_x(high)),
+      chart_theme::tooltip_fg(env),
+    ))
+    .chain(
+      data
+        .sets
+        .iter()
+        .zip(bins.counts.iter())
+        .map(|(set, counts)| (counts[bin].to_string(), set.color.clone())),
+    )
+    .map(|(text, color)| {
+      let layout = ctx
+        .text()
+        .new_text_layout(&label_font, &text, f64::INFINITY)
+        .build()
+        .unwrap();
+      (layout, color)
+    })
+    .collect();
+
+    let padding = 5.0;
+    let row_height = font_size + 4.0;
+    let width = padding * 2.0
+      + rows
+        .iter()
+        .map(|(layout, _)| layout.width())
+        .fold(0.0, f64::max);
+    let height = padding * 2.0 + row_height * rows.len() as f64;
+
+    // Keep the box beside the bin, flipping to the left near the right edge
+    let (left_x, right_x) = (geometry.to_pixel_x(low), geometry.to_pixel_x(high));
+    let (bin_left, bin_right) = (left_x.min(right_x), left_x.max(right_x));
+    let left = if bin_right + 8.0 + width < geometry.plot.x1 {
+      bin_right + 8.0
+    } else {
+      bin_left - 8.0 - width
+    };
+    let top = (self.cursor_pos.y - height / 2.0)
+      .min(geometry.plot.y1 - height)
+      .max(geometry.plot.y0);
+
+    let rect = Rect::from_origin_size((left, top), (width, height));
+    ctx.fill(rect, &chart_theme::tooltip_bg(env));
+
+    for (index, (layout, color)) in rows.iter().enumerate() {
+      let center_y = top + padding + row_height * (index as f64 + 0.5);
+      ctx.draw_text(
+        layout,
+        (left + padding, center_y + font_size * 0.334),
+        color,
+      );
+    }
+  }
+
+  /// Renders the chart as a standalone SVG document of the given size.
+  pub fn render_svg(&self, data: &HistogramData, size: Size) -> String {
+    self.export(data, size).finish()
+  }
+
+  /// Renders the chart as a PNG image of the given size, laid out as `render_svg`.
+  #[cfg(feature = "png")]
+  pub fn render_png(&self, data: &HistogramData, size: Size) -> Vec<u8> {
+    raster::rasterize(&self.export(data, size))
+  }
+
+  /// Draws the chart into a document shared by the SVG and PNG exports.
+  fn export(&self, data: &HistogramData, size: Size) -> SvgDocument {
+    let bins = bin_samples(&data.sets, self.settings.binning);
+    let geometry = self.geometry(size, bins.as_ref(), data.title.is_some());
+    let mut svg = SvgDocument::new(size);
+
+    self.settings.axis.render_svg_axes(
+      &mut svg,
+      data.title.as_deref(),
+      &geometry,
+      &ChartTheme::druid_default(),
+    );
+
+    if let Some(bins) = &bins {
+      for (set, _, rect) in column_rects(bins, self.settings.mode, &geometry) {
+        svg.fill_rect(rect, &self.color(&data.sets[set]));
+      }
+    }
+
+    svg
+  }
+}
+
+impl Default for Histogram {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl HistogramData {
+  /// Creates the data from a first set of samples, drawn in `color`. Samples that are NaN or
+  /// infinite aren't counted.
+  pub fn new(values: Vec<f64>, color: Color) -> Self {
+    Self {
+      title: None,
+      sets: vec![SampleSet { values, color }],
+    }
+  }
+
+  pub fn with_title(mut self, title: impl Into<String>) -> Self {
+    self.title = Some(title.into());
+    self
+  }
+
+  /// Adds another set of samples, counted into the same bins as the others and drawn in
+  /// `color`.
+  pub fn with_samples(mut self, values: Vec<f64>, color: Color) -> Self {
+    self.sets.push(SampleSet { values, color });
+    self
+  }
+}
+
+impl Data for HistogramData {
+  fn same(&self, other: &Self) -> bool {
+    self.title == other.title
+      && self.sets.len() == other.sets.len()
+      && self.sets.iter().zip(other.sets.iter()).all(|(a, b)| {
+        a.color.as_rgba_u32() == b.color.as_rgba_u32()
+          && a.values.len() == b.values.len()
+          && a
+            .values
+            .iter()
+            .zip(b.values.iter())
+            .all(|(a, b)| a.to_bits() == b.to_bits())
+      })
+  }
+}
+
+impl Widget<HistogramData> for Histogram {
+  fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut HistogramData, _env: &Env) {
+    if let Event::MouseMove(e) = event {
+      self.cursor_pos = e.pos;
+      ctx.request_paint();
+    }
+  }
+
+  fn lifecycle(
+    &mut self,
+    _ctx: &mut LifeCycleCtx,
+    event: &LifeCycle,
+    data: &HistogramData,
+    _env: &Env,
+  ) {
+    if let LifeCycle::WidgetAdded = event {
+      self.update_reference_data(data);
+    }
+  }
+
+  fn update(
+    &mut self,
+    ctx: &mut UpdateCtx,
+    old_data: &HistogramData,
+    data: &HistogramData,
+    _env: &Env,
+  ) {
+    if !old_data.same(data) {
+      self.update_reference_data(data);
+      ctx.request_paint();
+    }
+  }
+
+  fn layout(
+    &mut self,
+    _layout_ctx: &mut LayoutCtx,
+    bc: &BoxConstraints,
+    _data: &HistogramData,
+    _env: &Env,
+  ) -> Size {
+    chart_size(bc)
+  }
+
+  fn paint(&mut self, ctx: &mut PaintCtx, data: &HistogramData, env: &Env) {
+    let geometry = self.geometry(ctx.size(), self.bins.as_ref(), data.title.is_some());
+
+    self
+      .settings
+      .axis
+      .paint_axes(ctx, data.title.as_deref(), &geometry, env);
+
+    let bins = match &self.bins {
+      Some(bins) => bins,
+      None => return,
+    };
+
+    for (set, _, rect) in column_rects(bins, self.settings.mode, &geometry) {
+      ctx.fill(rect, &self.color(&data.sets[set]));
+    }
+
+    let hovered = if geometry.contains(self.cursor_pos) {
+      bins.bin_at(geometry.to_value_x(self.cursor_pos.x))
+    } else {
+      None
+    };
+    if let Some(bin) = hovered {
+      let (low, high) = bins.edges(bin);
+      let outline = Rect::new(
+        geometry.to_pixel_x(low),
+        geometry.plot.y0,
+        geometry.to_pixel_x(high),
+        geometry.plot.y1,
+      )
+      .abs();
+      ctx.stroke(outline, &chart_theme::axis_color(env), 1.0);
+      self.paint_tooltip(ctx, data, bins, bin, &geometry, env);
+    }
+  }
+}
+
+impl Bins {
+  fn len(&self) -> usize {
+    self.counts.first().map_or(0, Vec::len)
+  }
+
+  /// The lower and upper edges of a bin.
+  fn edges(&self, bin: usize) -> (f64, f64) {
+    let low = self.start + self.width * bin as f64;
+    (low, low + self.width)
+  }
+
+  /// The bin a value falls in, `None` outside them.
+  fn bin_at(&self, value: f64) -> Option<usize> {
+    let bin = ((value - self.start) / self.width).floor();
+    if bin >= 0.0 && (bin as usize) < self.len() {
+      Some(bin as usize)
+    } else {
+      None
+    }
+  }
+
+  /// Sum of the counts of all sets in each bin.
+  fn totals(&self) -> Vec<usize> {
+    (0..self.len())
+      .map(|bin| self.counts.iter().map(|counts| counts[bin]).sum())
+      .collect()
+  }
+}
+
+/// Counts the finite samples of every set into the same bins, spanning all of them. `None` when
+/// no set has any. Samples all of the same value are counted in a single bin centered on it.
+fn bin_samples(sets: &[SampleSet], binning: Binning) -> Option<Bins> {
+  let finite = || {
+    sets
+      .iter()
+      .flat_map(|set| set.values.iter())
+      .copied()
+      .filter(|value| value.is_finite())
+  };
+  let min = finite().fold(None, |min: Option<f64>, value| {
+    Some(min.map_or(value, |min| min.min(value)))
+  })?;
+  let max = finite().fold(min, f64::max);
+
+  let (start, width, count) = match binning {
+    Binning::Width(width) => {
+      let start = (min / width).floor() * width;
+      let count = ((max - start) / width).floor() + 1.0;
+      if count <= MAX_BINS as f64 {
+        (start, width, count as usize)
+      } else {
+        (min, (max - min) / MAX_BINS as f64, MAX_BINS)
+      }
+    }
+    Binning::Count(_) if max == min => (min - 0.5, 1.0, 1),
+    Binning::Count(count) => (min, (max - min) / count as f64, count),
+  };
+
+  let counts = sets
+    .iter()
+    .map(|set| {
+      let mut counts = vec![0; count];
+      for value in set.values.iter().filter(|value| value.is_finite()) {
+        // The highest sample closes the last bin rather than opening another
+        let bin = (((value - start) / width).floor() as usize).min(count - 1);
+        counts[bin] += 1;
+      }
+      counts
+    })
+    .collect();
+
+  Some(Bins {
+    start,
+    width,
+    counts,
+  })
+}
+
+/// Bounds spanning the bins, with counts from zero to the tallest column and a little room above
+/// it. Without bins, the counts span 0 to 1.
+fn histogram_bounds(bins: Option<&Bins>, mode: HistogramMode) -> DataBounds {
+  let bins = match bins {
+    Some(bins) => bins,
+    None => {
+      return DataBounds {
+        min_x: 0.0,
+        max_x: 1.0,
+        min_y: 0.0,
+        max_y: 1.0,
+        precision_y: 0,
+      }
+    }
+  };
+
+  let tallest = match mode {
+    HistogramMode::Stacked => bins.totals().into_iter().max(),
+    HistogramMode::Overlaid | HistogramMode::Grouped => bins.counts.iter().flatten().copied().max(),
+  }
+  .unwrap_or(0)
+  .max(1);
+
+  let (start, end) = (bins.start, bins.edges(bins.len() - 1).1);
+  DataBounds {
+    min_x: start,
+    max_x: end,
+    min_y: 0.0,
+    max_y: tallest as f64 * (1.0 + DEFAULT_Y_PADDING),
+    precision_y: 0,
+  }
+}
+
+/// The rectangle of every column with a count, along with its set and bin.
+fn column_rects(
+  bins: &Bins,
+  mode: HistogramMode,
+  geometry: &ChartGeometry,
+) -> Vec<(usize, usize, Rect)> {
+  let sets = bins.counts.len();
+  let mut rects = Vec::new();
+
+  for bin in 0..bins.len() {
+    let (low, high) = bins.edges(bin);
+    let (x0, x1) = (geometry.to_pixel_x(low), geometry.to_pixel_x(high));
+    let (left, right) = (x0.min(x1), x0.max(x1));
+    // Narrow bins keep at least a pixel of column
+    let gap = BIN_GAP.min((right - left - 1.0).max(0.0));
+    let (left, right) = (left + gap / 2.0, right - gap / 2.0);
+
+    let mut below = 0;
+    for (set, counts) in bins.counts.iter().enumerate() {
+      let count = counts[bin];
+      if count == 0 {
+        continue;
+      }
+
+      let (bottom, top, left, right) = match mode {
+        HistogramMode::Overlaid => (0, count, left, right),
+        HistogramMode::Stacked => {
+          below += count;
+          (below - count, below, left, right)
+        }
+        HistogramMode::Grouped => {
+          let slot = (right - left) / sets as f64;
+          let slot_left = left + slot * set as f64;
+          (0, count, slot_left, slot_left + slot)
+        }
+      };
+
+      let rect = Rect::new(
+        left,
+        geometry.to_pixel_y(top as f64),
+        right,
+        geometry.to_pixel_y(bottom as f64),
+      )
+      .abs();
+      rects.push((set, bin, rect));
+    }
+  }
+
+  rects
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn sets(values: &[&[f64]]) -> Vec<SampleSet> {
+    values
+      .iter()
+      .map(|values| SampleSet {
+        values: values.to_vec(),
+        color: Color::WHITE,
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_bin_samples() {
+    // The highest sample falls in the last bin, and every set shares the bins
+    let bins = bin_samples(
+      &sets(&[&[0.0, 1.0, 2.5, 4.0], &[3.9, f64::NAN]]),
+      Binning::Count(4),
+    )
+    .unwrap();
+    assert_eq!((bins.start, bins.width), (0.0, 1.0));
+    assert_eq!(bins.counts, vec![vec![1, 1, 1, 1], vec![0, 0, 0, 1]]);
+    assert_eq!(bins.totals(), vec![1, 1, 1, 2]);
+    assert_eq!(bins.bin_at(2.2), Some(2));
+    assert_eq!(bins.bin_at(4.5), None);
+
+    // Widths put the edges on their multiples
+    let bins = bin_samples(&sets(&[&[1.5, 2.2, 7.0]]), Binning::Width(2.0)).unwrap();
+    assert_eq!((bins.start, bins.len()), (0.0, 4));
+    assert_eq!(bins.counts[0], vec![1, 1, 0, 1]);
+
+    // Too narrow for the span, so widened to the most bins, still reaching the highest sample
+    let bins = bin_samples(&sets(&[&[0.0, 5.0, 100.0]]), Binning::Width(1e-3)).unwrap();
+    assert_eq!((bins.start, bins.width, bins.len()), (0.0, 0.01, MAX_BINS));
+    assert_eq!(bins.counts[0][500], 1);
+    assert_eq!(bins.counts[0].iter().sum::<usize>(), 3);
+    assert!((bins.edges(MAX_BINS - 1).1 - 100.0).abs() < 1e-9);
+
+    // Nothing to count, or a single value
+    assert!(bin_samples(&sets(&[&[], &[f64::INFINITY]]), Binning::Count(10)).is_none());
+    let single = bin_samples(&sets(&[&[3.0, 3.0]]), Binning::Count(10)).unwrap();
+    assert_eq!(single.counts, vec![vec![2]]);
+    assert_eq!(single.edges(0), (2.5, 3.5));
+  }
+
+  #[test]
+  fn test_column_rects() {
+    let bins = bin_samples(&sets(&[&[0.0, 0.5, 1.5], &[0.2, 1.8]]), Binning::Count(2)).unwrap();
+    let size = Size::new(400.0, 300.0);
+
+    let bounds = histogram_bounds(Some(&bins), HistogramMode::Stacked);
+    assert_eq!((bounds.min_x, bounds.max_x, bounds.min_y), (0.0, 1.8, 0.0));
+    assert!(bounds.max_y > 3.0);
+    let geometry = AxisSettings::default().geometry(size, &bounds, false);
+
+    // The second set sits on the first, up to the total of the bin
+    let stacked = column_rects(&bins, HistogramMode::Stacked, &geometry);
+    assert_eq!(stacked.len(), 4);
+    let (_, _, first) = stacked[0];
+    let (_, _, second) = stacked[1];
+    assert_eq!(first.y0, second.y1);
+    assert_eq!(second.y0, geometry.to_pixel_y(3.0));
+
+    // Side by side, each set takes half of the bin
+    let grouped = column_rects(&bins, HistogramMode::Grouped, &geometry);
+    let (_, _, first) = grouped[0];
+    let (_, _, second) = grouped[1];
+    assert_eq!(first.x1, second.x0);
+    assert_eq!(first.y1, geometry.to_pixel_y(0.0));
+    assert_eq!(second.y0, geometry.to_pixel_y(1.0));
+
+    // Empty data still lays out
+    let empty = HistogramData::new(Vec::new(), Color::WHITE);
+    assert!(Histogram::new().render_svg(&empty, size).contains("<svg"));
+  }
+}
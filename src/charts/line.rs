@@ -12,24 +12,156 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::any::Any;
 use std::cmp::Ordering;
-use std::fmt::Display;
+use std::collections::HashSet;
+use std::fmt::{self, Debug, Display};
+use std::io::Read;
+use std::str::FromStr;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use druid::piet::{FontBuilder, Text, TextLayout, TextLayoutBuilder};
+use druid::piet::{FontBuilder, GradientStop, Text, TextLayout, TextLayoutBuilder};
 use druid::widget::prelude::*;
 use druid::{
-  kurbo::BezPath, kurbo::Circle, theme, Color, Data, LinearGradient, Point, Rect, UnitPoint,
+  kurbo::BezPath, kurbo::Circle, theme, Color, Data, Insets, KeyCode, LinearGradient, Point, Rect,
+  UnitPoint,
 };
 use num_traits::{AsPrimitive, Num};
 
-use crate::charts::wilkinson;
+use crate::charts::axis::{chart_size, AxisSettings, DataBounds, DEFAULT_Y_PADDING};
+use crate::charts::csv::{self, CsvError};
+use crate::charts::palette;
+#[cfg(feature = "png")]
+use crate::charts::raster;
+use crate::charts::svg::{SvgDocument, TextAnchor};
+use crate::charts::theme::{self as chart_theme, ChartTheme};
+
+pub use crate::charts::axis::{
+  AxisLabel, AxisSides, ChartGeometry, GridConfig, XAxisSide, YAxisSide,
+};
+
+/// A line of `f64` points, which meets every bound the chart puts on its values.
+pub type LineF64 = Line<f64, f64>;
+
+/// Data of a chart of `f64` lines. See `LineChartData` for the bounds other types need.
+pub type LineChartDataF64 = LineChartData<f64, f64>;
+
+/// How close to a point, in pixels, a click has to land to hit it.
+const CLICK_TOLERANCE: f64 = 8.0;
+
+/// Default radius of the dot marking a highlighted point.
+const DEFAULT_HOVER_RADIUS: f64 = 4.0;
+
+/// Gap between the dot of a highlighted point and the ring around it, and opacity of the ring,
+/// when the chart has point callbacks.
+const HOVER_RING_GAP: f64 = 4.0;
+const HOVER_RING_ALPHA: f64 = 0.5;
+
+/// Source of line revisions, unique across every line of the program.
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(1);
+
+fn next_revision() -> u64 {
+  NEXT_REVISION.fetch_add(1, atomic::Ordering::Relaxed)
+}
+
+/// Formats the Y values of a line, shared by the copies of the line.
+#[derive(Clone)]
+struct ValueFormatter<Y>(Arc<dyn Fn(&Y) -> String>);
+
+impl<Y> Debug for ValueFormatter<Y> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("ValueFormatter")
+  }
+}
+
+type PointCallback<X, Y> = Box<dyn Fn(&mut EventCtx, usize, usize, &(X, Y))>;
+type HoverCallback<X, Y> = Box<dyn Fn(&mut EventCtx, usize, Option<&(X, Y)>)>;
+type BrushCallback = Box<dyn Fn(&mut EventCtx, (f64, f64), (f64, f64))>;
 
 #[derive(Clone, Debug)]
 pub struct Line<X, Y> {
   points: Vec<(X, Y)>,
   color: Color,
+  width: Option<f64>,
+  name: Option<String>,
+  secondary: bool,
+  area_outline: bool,
+  area_fill: AreaFill,
+  /// Draws the line itself, over the area under it.
+  stroke: bool,
+  /// Formats the values of this line in place of the chart's precision.
+  formatter: Option<ValueFormatter<Y>>,
+  /// Identifies the points: new for every line created and every change to its points, and kept
+  /// by clones, so lines with the same revision have the same points.
+  revision: u64,
+}
+
+/// How the area under a line is filled, in the color of the line.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AreaFill {
+  /// The same opacity over the whole area.
+  Solid(f64),
+  /// A linear gradient through opacities at offsets from 0 to 1, as `(offset, alpha)` pairs.
+  /// Vertical gradients start on the side of the line, at the top unless the Y axis is
+  /// reversed, and horizontal ones at the start of the X axis.
+  Gradient {
+    direction: FillDirection,
+    stops: Vec<(f64, f64)>,
+  },
+}
+
+impl Default for AreaFill {
+  fn default() -> Self {
+    AreaFill::Gradient {
+      direction: FillDirection::Vertical,
+      stops: vec![(0.0, 0.5), (1.0, 0.0)],
+    }
+  }
+}
+
+/// The direction of a gradient filling the area under a line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FillDirection {
+  Vertical,
+  Horizontal,
+}
+
+/// A shaded region between a lower and an upper series, as for a confidence interval. Drawn
+/// under the lines, on the primary Y axis, and counted in its bounds like a line.
+///
+/// The two series must share the same X values, point for point: the region is closed by
+/// joining the upper points left to right, then the lower points right to left.
+#[derive(Clone, Debug)]
+pub struct Band<X, Y> {
+  lower: Vec<(X, Y)>,
+  upper: Vec<(X, Y)>,
+  color: Color,
+  /// Strokes the lower and upper series over the fill.
+  outline: bool,
 }
 
+/// The lines drawn by a `LineChart`, under an optional title.
+///
+/// The X and Y values can be of any numeric type the chart can work with, which needs:
+///
+/// - `Data`, to tell when the lines changed;
+/// - `Num`, to add up stacked lines and count X values from zero;
+/// - `AsPrimitive<f64>`, to place the values on the axes;
+/// - `Display` and `PartialOrd`, to show and compare values.
+///
+/// The primitive integers and floats all qualify, so integer X values work as they are. For the
+/// common case of `f64` values, `LineChartDataF64` and `LineF64` spell the types out:
+///
+/// ```
+/// use birog::charts::line::{LineChartDataF64, LineF64};
+/// use druid::Color;
+///
+/// let data = LineChartDataF64::new()
+///   .with_title("Load")
+///   .with_line(LineF64::from_xy(&[0.0, 0.5, 1.0], &[2.0, 3.5, 3.0], Color::WHITE));
+/// ```
 #[derive(Clone, Debug)]
 pub struct LineChartData<X, Y>
 where
@@ -38,31 +170,197 @@ where
 {
   title: Option<String>,
   lines: Vec<Line<X, Y>>,
+  bands: Vec<Band<X, Y>>,
+  /// Labels of the X axis at each whole X from zero, which is numeric when empty.
+  categories: Vec<String>,
+  /// Colors given to the lines added by `with_line_auto`, `OKABE_ITO` when empty.
+  palette: Vec<Color>,
+  /// Changes with every edit through `push_point` or `set_line_points`, 0 before the first, so
+  /// `same` can tell copies of an edit from other data without comparing their points.
+  generation: u64,
 }
 
 pub struct LineChart {
   cursor_pos: Point,
   settings: LineChartSettings,
-  min_x: f64,
-  max_x: f64,
-  min_y: f64,
-  max_y: f64,
-  precision_x: usize,
-  precision_y: usize,
-  proportion_x: f64,
-  proportion_y: f64,
+  bounds: DataBounds,
+  /// Bounds of the lines on the secondary Y axis, `None` when no visible line uses it.
+  secondary_bounds: Option<DataBounds>,
+  /// Indices of the lines toggled off from the legend.
+  hidden_lines: HashSet<usize>,
+  /// Clickable area of each legend entry, by line index, as of the last paint.
+  legend_entries: Vec<Rect>,
+  /// Geometry of the last paint, `None` before the first.
+  painted: Option<ChartGeometry>,
+  /// A `PointCallback<X, Y>`, boxed again since the chart itself isn't generic over the data.
+  on_point_click: Option<Box<dyn Any>>,
+  /// A `HoverCallback<X, Y>`, boxed like `on_point_click`.
+  on_hover: Option<Box<dyn Any>>,
+  /// Index of the point last reported to `on_hover`, by line index.
+  hovered_points: Vec<Option<usize>>,
+  /// The point stepped to with the arrow keys, as `(line index, point index)`, shown while the
+  /// chart has focus.
+  focused_point: Option<(usize, usize)>,
+  on_brush: Option<BrushCallback>,
+  /// Replaces the view reset done on a double-click.
+  on_double_click: Option<Box<dyn Fn()>>,
+  /// The selection being dragged, or left on the plot after the last drag.
+  brush: Option<Brush>,
+  /// Extremes of the points of each line, by line index, measured again only when its revision
+  /// changes.
+  line_extents: Vec<LineExtent>,
+  /// The animation in progress from the previous data, if any.
+  transition: Option<Transition>,
+  /// Limits the repaints following the cursor, when set.
+  throttle: Option<Throttle>,
+}
+
+/// A selection rectangle, between the pixel positions the drag started and is at.
+#[derive(Clone, Copy, Debug)]
+struct Brush {
+  start: Point,
+  end: Point,
+  /// Set once the mouse is released, until a click clears the selection.
+  done: bool,
+}
+
+/// Cursor repaints held back to at most one per interval.
+struct Throttle {
+  interval: Duration,
+  /// When the cursor last repainted the chart.
+  last_paint: Option<Instant>,
+  /// A cursor move is waiting for an animation frame to be repainted.
+  pending: bool,
+}
+
+impl Throttle {
+  /// Whether the cursor may repaint the chart at `now`, a whole interval after it last did.
+  /// Otherwise the repaint is left pending.
+  fn try_paint(&mut self, now: Instant) -> bool {
+    let due = !matches!(self.last_paint, Some(last) if now.duration_since(last) < self.interval);
+
+    if due {
+      self.last_paint = Some(now);
+    }
+    self.pending = !due;
+
+    due
+  }
+}
+
+/// Where an animated change of data started from, and how far along it is.
+struct Transition {
+  from: DisplayedState,
+  elapsed: Duration,
+}
+
+/// What the lines are drawn against: the bounds of each Y axis and the plotted values.
+struct DisplayedState {
+  bounds: DataBounds,
+  secondary_bounds: Option<DataBounds>,
+  plotted: Vec<Vec<(f64, f64)>>,
+}
+
+/// How the values under the cursor are presented.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TooltipMode {
+  /// Each line highlights its own closest point, with a separate value box.
+  PerSeries,
+  /// All lines are read at the data X closest to the cursor and listed together in one box.
+  Unified,
+}
+
+/// Look of the boxes showing the values of hovered points.
+#[derive(Clone, Debug)]
+pub struct TooltipStyle {
+  /// Color of the box, `None` to use `charts::theme::TOOLTIP_BG`.
+  pub background: Option<Color>,
+  /// Color of the text, `None` to use `charts::theme::TOOLTIP_FG`.
+  pub text_color: Option<Color>,
+  /// Radius of the corners of the box, square when zero.
+  pub corner_radius: f64,
+  /// Space between the text and the edges of the box.
+  pub padding: f64,
+  /// Size of the text, `None` to use the font size of the axes.
+  pub font_size: Option<f64>,
+}
+
+/// Gap between a hovered point and the box showing its value.
+const TOOLTIP_OFFSET: f64 = 8.0;
+
+/// Gap between a point and its data label.
+const DATA_LABEL_OFFSET: f64 = 6.0;
+
+/// Opacity of the fill of a band, over the color of the band.
+const BAND_ALPHA: f64 = 0.25;
+
+/// Opacity of the inside and the outline of the selection rectangle.
+const BRUSH_FILL_ALPHA: f64 = 0.15;
+const BRUSH_STROKE_ALPHA: f64 = 0.6;
+
+/// How lines are placed relative to each other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StackMode {
+  /// Every line is drawn at its own values, filled down to the bottom of the plot.
+  Overlapping,
+  /// Each line is offset by the sum of the lines before it at the same X, and filled down to the
+  /// previous line. All lines are expected to share the same X values.
+  Stacked,
+  /// Stacked, with the values at each X divided by their total so the lines add up to 100%. The
+  /// Y axis spans 0% to 100%, and X values where all lines are zero are left empty.
+  PercentStacked,
+}
+
+/// How `Line::moving_average` weighs the points of a window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AverageKind {
+  /// Every point of the window counts the same.
+  Simple,
+  /// Recent points count more, with a smoothing factor of `2 / (window + 1)`.
+  Exponential,
+}
+
+/// What `Line::moving_average` does with the first points, before a whole window is available.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LeadingPoints {
+  /// The derived line starts at the first full window.
+  Omit,
+  /// The first points are averaged over the shorter windows available.
+  Partial,
 }
 
 struct LineChartSettings {
-  font_size: f64,
-  padding_top: f64,
-  padding_bottom: f64,
-  padding_left: f64,
-  padding_right: f64,
-  header_height: f64,
-  footer_height: f64,
-  tick_length: f64,
+  axis: AxisSettings,
   path_stroke_width: f64,
+  tooltip_mode: TooltipMode,
+  stack_mode: StackMode,
+  show_legend: bool,
+  animation: Duration,
+  /// Locks the vertical crosshair to the closest data X, always on in unified tooltip mode.
+  crosshair_snap: bool,
+  tooltip_style: TooltipStyle,
+  data_labels: bool,
+  /// Caption below the X axis labels.
+  footer: Option<String>,
+  /// Fraction of the Y range left empty below and above the data.
+  y_padding: f64,
+  /// Highlights the points nearest to the cursor and shows their values.
+  point_tooltip: bool,
+  /// Radius of the dot marking a highlighted point.
+  hover_radius: f64,
+  /// Farthest the cursor can be from a point, in pixels, for it to be highlighted. Any distance
+  /// when `None`.
+  hover_tolerance: Option<f64>,
+  /// Colors replacing the chart colors of the environment.
+  theme: Option<ChartTheme>,
+}
+
+/// Extremes of a line's points, `None` without points, as of the line revision they were
+/// measured at.
+#[derive(Clone, Copy)]
+struct LineExtent {
+  revision: u64,
+  bounds: Option<DataBounds>,
 }
 
 impl LineChart {
@@ -70,57 +368,83 @@ impl LineChart {
     Self {
       cursor_pos: Point::new(-1.0, -1.0),
       settings: LineChartSettings {
-        font_size: 12.0,
-        padding_top: 40.0,
-        padding_bottom: 40.0,
-        padding_left: 40.0,
-        padding_right: 40.0,
-        header_height: 40.0,
-        footer_height: 0.0,
-        tick_length: 5.0,
+        axis: AxisSettings::default(),
         path_stroke_width: 2.0,
+        tooltip_mode: TooltipMode::PerSeries,
+        stack_mode: StackMode::Overlapping,
+        show_legend: false,
+        animation: Duration::ZERO,
+        crosshair_snap: false,
+        tooltip_style: TooltipStyle::default(),
+        data_labels: false,
+        footer: None,
+        y_padding: DEFAULT_Y_PADDING,
+        point_tooltip: true,
+        hover_radius: DEFAULT_HOVER_RADIUS,
+        hover_tolerance: None,
+        theme: None,
       },
-      min_x: 0.0,
-      max_x: 0.0,
-      min_y: 0.0,
-      max_y: 0.0,
-      precision_x: 0,
-      precision_y: 0,
-      proportion_x: 0.0,
-      proportion_y: 0.0,
+      bounds: DataBounds::default(),
+      secondary_bounds: None,
+      hidden_lines: HashSet::new(),
+      legend_entries: Vec::new(),
+      painted: None,
+      on_point_click: None,
+      on_hover: None,
+      hovered_points: Vec::new(),
+      focused_point: None,
+      on_brush: None,
+      on_double_click: None,
+      brush: None,
+      line_extents: Vec::new(),
+      transition: None,
+      throttle: None,
     }
   }
 
   pub fn with_padding(mut self, padding: f64) -> Self {
-    self.settings.padding_top = padding;
-    self.settings.padding_bottom = padding;
-    self.settings.padding_left = padding;
-    self.settings.padding_right = padding;
+    self.settings.axis.set_padding(padding);
     self
   }
 
   pub fn with_padding_top(mut self, padding: f64) -> Self {
-    self.settings.padding_top = padding;
+    self.settings.axis.padding_top = padding;
     self
   }
 
   pub fn with_padding_bottom(mut self, padding: f64) -> Self {
-    self.settings.padding_bottom = padding;
+    self.settings.axis.padding_bottom = padding;
     self
   }
 
   pub fn with_padding_left(mut self, padding: f64) -> Self {
-    self.settings.padding_left = padding;
+    self.settings.axis.padding_left = padding;
     self
   }
 
   pub fn with_padding_right(mut self, padding: f64) -> Self {
-    self.settings.padding_right = padding;
+    self.settings.axis.padding_right = padding;
+    self
+  }
+
+  /// Leaves `margin` empty around everything the chart draws, outside the padding the axis
+  /// labels are drawn in, as when aligning charts in a grid. Takes one width for all sides or an
+  /// `Insets` for each.
+  pub fn with_margin(mut self, margin: impl Into<Insets>) -> Self {
+    self.settings.axis.margin = margin.into();
     self
   }
 
   pub fn with_font_size(mut self, font_size: f64) -> Self {
-    self.settings.font_size = font_size;
+    self.settings.axis.font_size = font_size;
+    self.settings.axis.footer_height = self.footer_height();
+    self
+  }
+
+  /// Writes a caption centered below the chart, under the X axis labels and above the legend.
+  pub fn with_footer(mut self, caption: impl Into<String>) -> Self {
+    self.settings.footer = Some(caption.into());
+    self.settings.axis.footer_height = self.footer_height();
     self
   }
 
@@ -129,677 +453,3913 @@ impl LineChart {
     self
   }
 
-  fn update_reference_data<X, Y>(&mut self, data: &LineChartData<X, Y>)
-  where
-    X: Num + Data + AsPrimitive<f64>,
-    Y: Num + Data + AsPrimitive<f64>,
-  {
-    let x_iter = data
-      .lines
-      .iter()
-      .flat_map(|l| l.points.iter())
-      .map(|(x, _)| x.as_());
-
-    self.min_x = x_iter
-      .clone()
-      .min_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
-      .unwrap_or(1.0);
-
-    self.max_x = x_iter
-      .clone()
-      .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
-      .unwrap_or(1.0);
+  pub fn with_tooltip_mode(mut self, mode: TooltipMode) -> Self {
+    self.settings.tooltip_mode = mode;
+    self
+  }
 
-    self.precision_x = x_iter
-      .clone()
-      .map(|x| get_precision(x))
-      .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
-      .unwrap_or(1);
+  /// Moves the vertical crosshair to the data X closest to the cursor, across all lines, so the
+  /// X readout shows a value of the data. The unified tooltip mode always snaps.
+  pub fn with_crosshair_snap(mut self, snap: bool) -> Self {
+    self.settings.crosshair_snap = snap;
+    self
+  }
 
-    let y_iter = data
-      .lines
-      .iter()
-      .flat_map(|l| l.points.iter())
-      .map(|(_, y)| y.as_());
+  /// Shows the points nearest to the cursor, circled, with their values. Without them, hovering
+  /// only draws the crosshair and its axis readouts. On by default.
+  pub fn with_point_tooltip(mut self, show: bool) -> Self {
+    self.settings.point_tooltip = show;
+    self
+  }
 
-    self.min_y = y_iter
-      .clone()
-      .min_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
-      .unwrap_or(1.0)
-      * 0.95;
+  /// Sets the radius of the dot marking the points nearest to the cursor. Defaults to 4 pixels.
+  pub fn with_hover_radius(mut self, radius: f64) -> Self {
+    self.settings.hover_radius = radius.max(0.0);
+    self
+  }
 
-    self.max_y = y_iter
-      .clone()
-      .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
-      .unwrap_or(1.0)
-      * 1.05;
+  /// Only highlights a point, shows its value and reports it to `on_hover` while the cursor is
+  /// within `tolerance` pixels of it. By default the point nearest in X is highlighted however
+  /// far the cursor is above or below it.
+  pub fn with_hover_tolerance(mut self, tolerance: f64) -> Self {
+    self.settings.hover_tolerance = Some(tolerance.max(0.0));
+    self
+  }
 
-    self.precision_y = y_iter
-      .clone()
-      .map(|y| get_precision(y))
-      .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
-      .unwrap_or(1);
+  /// Draws the axes, grid, text and value boxes in the colors of `theme`, whatever the chart
+  /// colors of the environment. By default they follow the keys of [`theme`](crate::charts::theme)
+  /// and, without them, the druid theme.
+  pub fn with_theme(mut self, theme: ChartTheme) -> Self {
+    self.settings.theme = Some(theme);
+    self
   }
 
-  fn get_axis(&self, min_value: f64, max_value: f64, max_labels: f64) -> (Vec<f64>, usize) {
-    let labels = wilkinson::generate_labels(
-      min_value,
-      max_value,
-      max_labels,
-      wilkinson::LabelRange::Included,
-    );
+  /// Reads the X values as seconds since the Unix epoch and labels the X axis with UTC dates and
+  /// times. Ticks fall on calendar boundaries, and gridlines on boundaries coarser than the
+  /// step between ticks, such as month starts among daily ticks, are drawn darker and wider.
+  pub fn with_time_x_axis(mut self, time: bool) -> Self {
+    self.settings.axis.time_x = time;
+    self
+  }
 
-    let precision = labels
-      .iter()
-      .map(|v| get_precision(*v))
-      .max_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
-      .unwrap_or(1);
+  /// Spaces the Y values by their logarithm, so each power of ten takes the same height. The axes
+  /// are labeled at 1, 2 and 5 times each power of ten, with the in-between labels drawn with a
+  /// shorter tick and a lighter gridline, or only at powers of ten when those don't fit.
+  ///
+  /// Only positive values can be shown: when the data reaches zero or below, the axis spans the
+  /// three decades below the largest value, and lower points fall off the plot.
+  pub fn with_log_y(mut self, log: bool) -> Self {
+    self.settings.axis.log_y = log;
+    self
+  }
 
-    (labels, precision)
+  /// Shows the numeric X labels and the X crosshair readout with `decimals` decimal places,
+  /// rather than as many as the labels need. `None` goes back to the automatic precision.
+  pub fn with_x_decimals(mut self, decimals: Option<usize>) -> Self {
+    self.settings.axis.x_decimals = decimals;
+    self
   }
 
-  fn paint_labels<X, Y>(&mut self, ctx: &mut PaintCtx, data: &LineChartData<X, Y>, env: &Env)
-  where
-    X: Data,
-    Y: Data,
-  {
-    let size = ctx.size();
+  /// Shows the Y labels, the Y crosshair readouts and the values of the tooltips and data labels
+  /// with `decimals` decimal places, rather than the precision derived from the data. Lines with
+  /// their own formatter keep it, and log axes format each label on their own. `None` goes back
+  /// to the automatic precision.
+  pub fn with_y_decimals(mut self, decimals: Option<usize>) -> Self {
+    self.settings.axis.y_decimals = decimals;
+    self
+  }
 
-    // Paint header
-    if let Some(ref title) = data.title {
-      self.settings.header_height = 40.0;
+  /// Rotates the X labels counter-clockwise by `degrees`, from 0 to 90, so more of them fit
+  /// without overlapping. Each label ends at its tick, and the padding on the labeled sides grows
+  /// to fit the rotated text.
+  pub fn with_x_label_rotation(mut self, degrees: f64) -> Self {
+    self.settings.axis.x_label_rotation = degrees.clamp(0.0, 90.0);
+    self
+  }
 
-      let header_label_font = ctx
-        .text()
-        .new_font_by_name(&env.get(theme::FONT_NAME), 25.0)
-        .build()
-        .unwrap();
+  /// Shows the values under the cursor in boxes at the ends of the crosshair, over the axis
+  /// labels. On by default.
+  pub fn with_crosshair_readouts(mut self, show: bool) -> Self {
+    self.settings.axis.cursor_readouts = show;
+    self
+  }
 
-      let header_layout = ctx
-        .text()
-        .new_text_layout(&header_label_font, title, std::f64::INFINITY)
-        .build()
-        .unwrap();
+  pub fn with_tooltip_style(mut self, style: TooltipStyle) -> Self {
+    self.settings.tooltip_style = style;
+    self
+  }
 
-      let pos_x = (size.width - header_layout.width()) / 2.0;
-      let pos_y = if let Some(metrics) = header_layout.line_metric(0) {
-        (self.settings.header_height + metrics.baseline) / 2.0
-      } else {
-        (self.settings.header_height + 25.0) / 2.0
-      };
+  /// Prints the Y value of every point of the visible lines next to it, above the point unless
+  /// the line comes from above. Labels that would cross the edge of the plot are left out.
+  pub fn with_data_labels(mut self, show: bool) -> Self {
+    self.settings.data_labels = show;
+    self
+  }
 
-      ctx.draw_text(
-        &header_layout,
-        (pos_x, pos_y),
-        &env.get(theme::FOREGROUND_DARK),
-      );
-    } else {
-      self.settings.header_height = 0.0;
-    }
+  pub fn with_stack_mode(mut self, mode: StackMode) -> Self {
+    self.settings.stack_mode = mode;
+    self.settings.axis.percent_y = mode == StackMode::PercentStacked;
+    self
+  }
 
-    let label_font = ctx
-      .text()
-      .new_font_by_name(&env.get(theme::FONT_NAME), self.settings.font_size)
-      .build()
-      .unwrap();
+  /// Emphasizes the lines through X = 0 and Y = 0, drawn when the axes cross zero, over the
+  /// gridlines. On by default.
+  pub fn with_zero_line(mut self, show: bool) -> Self {
+    self.settings.axis.zero_line = show;
+    self
+  }
 
-    let min_label_spacing_h = self.settings.font_size / 0.3;
-    let min_label_spacing_v = self.settings.font_size / 0.4;
+  /// Draws the axes, their labels and gridlines, the title and the crosshair around the plot. On
+  /// by default; without them, the lines fill the widget edge to edge like a larger sparkline.
+  /// The point tooltips are left to `with_point_tooltip`, and the legend and footer still take
+  /// their room below the plot when set.
+  pub fn with_chrome(mut self, chrome: bool) -> Self {
+    self.settings.axis.chrome = chrome;
+    self
+  }
 
-    let padding_h = self.settings.padding_left + self.settings.padding_right;
-    let padding_v = self.settings.padding_top + self.settings.padding_bottom;
+  /// Shows a legend below the plot, with an entry per line. Clicking an entry hides or shows its
+  /// line.
+  pub fn with_legend(mut self, show: bool) -> Self {
+    self.settings.show_legend = show;
+    self
+  }
 
-    let bounds_h = size.width - padding_h;
-    let bounds_v =
-      size.height - padding_v - self.settings.header_height - self.settings.footer_height;
+  pub fn with_grid(mut self, grid: GridConfig) -> Self {
+    self.settings.axis.grid = grid;
+    self
+  }
 
-    let max_labels_x = (bounds_h / min_label_spacing_h).floor().max(1.0);
-    let max_labels_y = (bounds_v / min_label_spacing_v).floor().max(1.0);
+  /// Draws `count` fainter gridlines between each pair of major ticks, on the axes whose
+  /// gridlines are shown.
+  pub fn with_minor_gridlines(mut self, count: usize) -> Self {
+    self.settings.axis.minor_gridlines = count;
+    self
+  }
 
-    let (x_axis, x_axis_precision) = self.get_axis(self.min_x, self.max_x, max_labels_x);
-    let (y_axis, y_axis_precision) = self.get_axis(self.min_y, self.max_y, max_labels_y);
+  /// Animates changes of data over `duration`: the axes and the points of every line move from
+  /// their previous positions, and lines whose number of points changed fade in at their new
+  /// ones. `Duration::ZERO`, the default, updates the chart at once.
+  pub fn with_animation(mut self, duration: Duration) -> Self {
+    self.settings.animation = duration;
+    self
+  }
 
-    let origin_left = self.settings.padding_left;
-    let origin_right = size.width - self.settings.padding_right;
-    let origin_top = self.settings.padding_top + self.settings.header_height;
-    let origin_bottom = size.height - self.settings.footer_height - self.settings.padding_bottom;
+  /// Repaints for cursor moves at most once per `interval`, such as 16 ms for a 60 Hz display,
+  /// instead of on every move. The cursor is still tracked and `on_hover` still called on every
+  /// move; moves within the interval are painted together on a later animation frame.
+  pub fn with_repaint_throttle(mut self, interval: Duration) -> Self {
+    self.throttle = Some(Throttle {
+      interval,
+      last_paint: None,
+      pending: false,
+    });
+    self
+  }
 
-    self.min_x = self.min_x;
-    self.min_y = self.min_y.min(y_axis[0]);
+  /// Fills the plot area with `color`, underneath the gridlines and the data.
+  pub fn with_plot_background(mut self, color: Color) -> Self {
+    self.settings.axis.plot_background = Some(color);
+    self
+  }
 
-    self.max_x = self.max_x;
-    self.max_y = self.max_y.max(y_axis[y_axis.len() - 1]);
+  /// Flips the X axis so its values decrease from left to right.
+  pub fn with_x_reversed(mut self, reversed: bool) -> Self {
+    self.settings.axis.reversed_x = reversed;
+    self
+  }
 
-    self.proportion_x = bounds_h / (self.max_x - self.min_x).abs();
-    self.proportion_y = bounds_v / (self.max_y - self.min_y).abs();
+  /// Leaves `fraction` of the span of the data empty below and above it on each Y axis, 0 to
+  /// reach exactly from the lowest to the highest value. Defaults to 5%.
+  pub fn with_y_padding(mut self, fraction: f64) -> Self {
+    self.settings.y_padding = fraction.max(0.0);
+    self
+  }
 
-    // Draw chart rectangle
-    let rect = Rect::from_points(
-      Point::new(origin_left, origin_top),
-      Point::new(origin_right, origin_bottom),
-    );
+  /// Flips the Y axis so its values decrease from bottom to top. This is the only way to reverse
+  /// it: bounds given high to low are put back in order first.
+  pub fn with_y_reversed(mut self, reversed: bool) -> Self {
+    self.settings.axis.reversed_y = reversed;
+    self
+  }
 
-    ctx.stroke(rect, &env.get(theme::FOREGROUND_DARK), 1.0);
+  /// Chooses on which sides of the plot the tick labels are drawn. The padding of a side without
+  /// labels shrinks to a small margin.
+  pub fn with_axis_sides(mut self, sides: AxisSides) -> Self {
+    self.settings.axis.axis_sides = sides;
+    self
+  }
 
-    for value_x in x_axis
-      .iter()
-      .skip_while(|v| **v < self.min_x.as_())
-      .take_while(|v| **v <= self.max_x.as_())
-    {
-      let label = format!("{:.prec$}", value_x, prec = x_axis_precision);
+  /// Calls `f` with the line index, the point index and the point itself when a click lands
+  /// on a data point of a visible line. Clicks away from every point are ignored.
+  ///
+  /// `X` and `Y` must match the `LineChartData` the chart displays, otherwise the callback is
+  /// never called. To change application state from the callback, submit a command for the
+  /// app's delegate or an ancestor widget to handle:
+  ///
+  /// ```no_run
+  /// use birog::charts::line::LineChart;
+  /// use druid::Selector;
+  ///
+  /// const SHOW_POINT: Selector<(usize, usize)> = Selector::new("app.show-point");
+  ///
+  /// let chart = LineChart::new().on_point_click(|ctx, line, point, _: &(i32, f64)| {
+  ///   ctx.submit_command(SHOW_POINT.with((line, point)), None);
+  /// });
+  /// ```
+  ///
+  /// A chart with `on_point_click` or `on_hover` takes focus when clicked, then the left and right
+  /// arrow keys step through the points of a line, the up and down arrows move to the nearest
+  /// point of the other lines, and Enter clicks the point.
+  pub fn on_point_click<X: 'static, Y: 'static>(
+    mut self,
+    f: impl Fn(&mut EventCtx, usize, usize, &(X, Y)) + 'static,
+  ) -> Self {
+    let callback: PointCallback<X, Y> = Box::new(f);
+    self.on_point_click = Some(Box::new(callback));
+    self
+  }
 
-      let layout = ctx
-        .text()
-        .new_text_layout(&label_font, &label, std::f64::INFINITY)
-        .build()
-        .unwrap();
+  /// Calls `f` with the line index and the point of that line nearest to the cursor, the one the
+  /// tooltip shows, whenever it changes. The point is `None` while the cursor is outside the plot
+  /// area or the line is hidden. While the chart has focus, the points stepped to with the arrow
+  /// keys are reported the same way.
+  ///
+  /// Like `on_point_click`, `X` and `Y` must match the chart's `LineChartData`. To show the
+  /// value in another widget, submit a command and store its payload in the app's `Data` from
+  /// an `AppDelegate`, which the other widget can then read through a lens:
+  ///
+  /// ```no_run
+  /// use birog::charts::line::LineChart;
+  /// use druid::{AppDelegate, Command, Data, DelegateCtx, Env, Selector, Target};
+  ///
+  /// const HOVER: Selector<Option<(i32, f64)>> = Selector::new("app.hover");
+  ///
+  /// #[derive(Clone, Data)]
+  /// struct AppData {
+  ///   readout: String,
+  /// }
+  ///
+  /// struct Delegate;
+  ///
+  /// impl AppDelegate<AppData> for Delegate {
+  ///   fn command(
+  ///     &mut self,
+  ///     _ctx: &mut DelegateCtx,
+  ///     _target: Target,
+  ///     cmd: &Command,
+  ///     data: &mut AppData,
+  ///     _env: &Env,
+  ///   ) -> bool {
+  ///     match cmd.get(HOVER) {
+  ///       Some(point) => {
+  ///         data.readout = point.map_or_else(String::new, |(x, y)| format!("{}: {}", x, y));
+  ///         false
+  ///       }
+  ///       None => true,
+  ///     }
+  ///   }
+  /// }
+  ///
+  /// let chart = LineChart::new().on_hover(|ctx, _line, point: Option<&(i32, f64)>| {
+  ///   ctx.submit_command(HOVER.with(point.copied()), None);
+  /// });
+  /// ```
+  pub fn on_hover<X: 'static, Y: 'static>(
+    mut self,
+    f: impl Fn(&mut EventCtx, usize, Option<&(X, Y)>) + 'static,
+  ) -> Self {
+    let callback: HoverCallback<X, Y> = Box::new(f);
+    self.on_hover = Some(Box::new(callback));
+    self
+  }
 
-      let position_x = origin_left + (value_x - self.min_x) * self.proportion_x;
+  /// Lets a drag with Shift held select a rectangle of the plot, and calls `f` with the X and Y
+  /// data ranges it covers, each as `(min, max)` on the primary axes, when the mouse is released.
+  /// Plain drags are left alone, and the selection stays drawn until the next click.
+  pub fn on_brush(mut self, f: impl Fn(&mut EventCtx, (f64, f64), (f64, f64)) + 'static) -> Self {
+    self.on_brush = Some(Box::new(f));
+    self
+  }
 
-      ctx.draw_text(
-        &layout,
-        (
-          position_x - layout.width() / 2.0,
-          origin_top - self.settings.tick_length - 2.0,
-        ),
-        &env.get(theme::FOREGROUND_DARK),
-      );
+  /// Calls `f` on a double-click in the plot area, in place of resetting the view. By default a
+  /// double-click clears the brush selection and fits the axes to the data again. Lines hidden
+  /// from the legend stay hidden.
+  ///
+  /// The second click of a double-click isn't reported to `on_point_click`.
+  pub fn with_on_double_click(mut self, f: impl Fn() + 'static) -> Self {
+    self.on_double_click = Some(Box::new(f));
+    self
+  }
 
-      ctx.draw_text(
-        &layout,
-        (
-          position_x - layout.width() / 2.0,
-          origin_bottom + self.settings.tick_length + self.settings.font_size,
-        ),
-        &env.get(theme::FOREGROUND_DARK),
-      );
+  /// Runs the `on_double_click` callback, or resets the view when there is none.
+  fn double_click<X, Y>(&mut self, data: &LineChartData<X, Y>)
+  where
+    X: Num + Data + AsPrimitive<f64>,
+    Y: Num + Data + AsPrimitive<f64>,
+  {
+    match &self.on_double_click {
+      Some(callback) => callback(),
+      None => {
+        self.brush = None;
+        self.update_reference_data(data);
+      }
+    }
+  }
 
-      // Ticks
-      let mut tick_line = BezPath::new();
-      tick_line.move_to((position_x, origin_top));
-      tick_line.line_to((position_x, origin_top - self.settings.tick_length));
+  /// Calls the `on_hover` callback for every line whose point nearest to the cursor changed
+  /// since the last call.
+  fn report_hover<X, Y>(&mut self, ctx: &mut EventCtx, data: &LineChartData<X, Y>)
+  where
+    X: Data + Num + AsPrimitive<f64>,
+    Y: Data + Num + AsPrimitive<f64>,
+  {
+    if self.on_hover.is_none() {
+      return;
+    }
 
-      tick_line.move_to((position_x, origin_bottom));
-      tick_line.line_to((position_x, origin_bottom + self.settings.tick_length));
+    let geometry = self.geometry(
+      ctx.size(),
+      &self.bounds,
+      self.secondary_bounds.as_ref(),
+      &data.categories,
+      data.title.is_some(),
+    );
+    let plotted = self.plotted_values(&data.lines);
+    let hovered = self.hovered_points(&data.lines, &plotted, &geometry);
 
-      ctx.stroke(tick_line, &env.get(theme::FOREGROUND_DARK), 1.0);
+    self.report_points(ctx, data, hovered);
+  }
 
-      // Grid line
-      let mut grid_line = BezPath::new();
-      grid_line.move_to((position_x, origin_top));
-      grid_line.line_to((position_x, origin_bottom));
+  /// Calls the `on_hover` callback for every line whose point in `points` differs from the one
+  /// last reported.
+  fn report_points<X, Y>(
+    &mut self,
+    ctx: &mut EventCtx,
+    data: &LineChartData<X, Y>,
+    points: Vec<Option<usize>>,
+  ) where
+    X: Data,
+    Y: Data,
+  {
+    let callback = match self
+      .on_hover
+      .as_ref()
+      .and_then(|callback| callback.downcast_ref::<HoverCallback<X, Y>>())
+    {
+      Some(callback) => callback,
+      None => return,
+    };
 
-      ctx.stroke(
-        grid_line,
-        &env.get(theme::FOREGROUND_DARK).with_alpha(0.1),
-        1.0,
-      );
+    for (index, point_index) in points.iter().enumerate() {
+      if self.hovered_points.get(index) != Some(point_index) {
+        callback(
+          ctx,
+          index,
+          point_index.map(|point_index| &data.lines[index].points[point_index]),
+        );
+      }
     }
 
-    for value_y in y_axis
-      .iter()
-      .skip_while(|v| **v < self.min_y.as_())
-      .take_while(|v| **v <= self.max_y.as_())
-    {
-      let label = format!("{:.prec$}", value_y, prec = y_axis_precision);
-
-      let layout = ctx
-        .text()
-        .new_text_layout(&label_font, &label, std::f64::INFINITY)
-        .build()
-        .unwrap();
+    self.hovered_points = points;
+  }
 
-      let position_y = origin_bottom - (value_y - self.min_y) * self.proportion_y;
-      let text_height_adjustment = if let Some(metric) = layout.line_metric(0) {
-        metric.cumulative_height - metric.baseline.floor()
-      } else {
-        self.settings.font_size / 2.2
-      };
+  /// Whether the points react to clicks or hovering, which also makes them reachable with the
+  /// keyboard.
+  fn is_interactive(&self) -> bool {
+    self.on_point_click.is_some() || self.on_hover.is_some()
+  }
 
-      ctx.draw_text(
-        &layout,
-        (
-          origin_left - layout.width() - self.settings.tick_length - 2.0,
-          position_y + text_height_adjustment,
-        ),
-        &env.get(theme::FOREGROUND_DARK),
-      );
+  /// Moves the keyboard focus to another point, reporting it to `on_hover`.
+  fn focus_point<X, Y>(
+    &mut self,
+    ctx: &mut EventCtx,
+    data: &LineChartData<X, Y>,
+    focused: Option<(usize, usize)>,
+  ) where
+    X: Data,
+    Y: Data,
+  {
+    if focused == self.focused_point {
+      return;
+    }
+    self.focused_point = focused;
 
-      ctx.draw_text(
-        &layout,
-        (
-          origin_right + self.settings.tick_length + 2.0,
-          position_y + text_height_adjustment,
-        ),
-        &env.get(theme::FOREGROUND_DARK),
-      );
+    let points = (0..data.lines.len())
+      .map(|index| {
+        focused
+          .filter(|(line, _)| *line == index)
+          .map(|(_, point)| point)
+      })
+      .collect();
+    self.report_points(ctx, data, points);
+    ctx.request_paint();
+  }
 
-      // Ticks
-      let mut tick_line = BezPath::new();
-      tick_line.move_to((origin_left, position_y));
-      tick_line.line_to((origin_left - self.settings.tick_length, position_y));
+  /// Index of the point of each line nearest to the cursor, the one its tooltip shows. `None`
+  /// for hidden lines, for lines whose point is beyond the hover tolerance, and for every line
+  /// while the cursor is outside the plot area.
+  fn hovered_points<X, Y>(
+    &self,
+    lines: &[Line<X, Y>],
+    plotted: &[Vec<(f64, f64)>],
+    geometry: &ChartGeometry,
+  ) -> Vec<Option<usize>>
+  where
+    X: Num + AsPrimitive<f64>,
+  {
+    let cursor_x = if geometry.contains(self.cursor_pos) {
+      let cursor_x = geometry.to_value_x(self.cursor_pos.x);
 
-      tick_line.move_to((origin_right, position_y));
-      tick_line.line_to((origin_right + self.settings.tick_length, position_y));
+      // In unified mode every line is read at the same X
+      match self.settings.tooltip_mode {
+        TooltipMode::PerSeries => Some(cursor_x),
+        TooltipMode::Unified => closest_x(plotted, cursor_x),
+      }
+    } else {
+      None
+    };
 
-      ctx.stroke(tick_line, &env.get(theme::FOREGROUND_DARK), 1.0);
+    // Lines may be sampled at different X, so each is searched on its own
+    (0..lines.len())
+      .map(|index| {
+        if self.hidden_lines.contains(&index) {
+          return None;
+        }
 
-      // Grid line
-      let mut grid_line = BezPath::new();
-      grid_line.move_to((origin_left, position_y));
-      grid_line.line_to((origin_right, position_y));
+        let point_index = closest_plotted(&plotted[index], cursor_x?, geometry)?;
+        match self.settings.hover_tolerance {
+          Some(tolerance) => {
+            let (x, y) = plotted[index][point_index];
+            let point = geometry
+              .y_axis_for(lines[index].secondary)
+              .data_to_pixel(x, y);
+            Some(point_index).filter(|_| point.distance(self.cursor_pos) <= tolerance)
+          }
+          None => Some(point_index),
+        }
+      })
+      .collect()
+  }
 
-      ctx.stroke(
-        grid_line,
-        &env.get(theme::FOREGROUND_DARK).with_alpha(0.1),
-        1.0,
-      );
-    }
+  fn update_reference_data<X, Y>(&mut self, data: &LineChartData<X, Y>)
+  where
+    X: Num + Data + AsPrimitive<f64>,
+    Y: Num + Data + AsPrimitive<f64>,
+  {
+    // Overlapping lines are plotted at their own values, so only the changed lines need measuring
+    let (bounds, secondary_bounds) = if self.settings.stack_mode == StackMode::Overlapping {
+      self.measure_lines(&data.lines);
+      self.bounds_from_extents(&data.lines, &data.bands)
+    } else {
+      let plotted = self.plotted_values(&data.lines);
+      self.data_bounds(&data.lines, &data.bands, &plotted)
+    };
+    self.bounds = bounds;
+    self.secondary_bounds = secondary_bounds;
   }
 
-  fn paint_lines<X, Y>(&self, ctx: &mut PaintCtx, lines: &[Line<X, Y>], env: &Env)
+  /// Measures the extremes of the lines whose revision changed since they were last measured.
+  fn measure_lines<X, Y>(&mut self, lines: &[Line<X, Y>])
   where
     X: Num + AsPrimitive<f64>,
-    Y: Num + AsPrimitive<f64> + Display,
+    Y: Num + AsPrimitive<f64>,
   {
-    let size = ctx.size();
-
-    let origin_left = self.settings.padding_left;
-    let origin_right = size.width - self.settings.padding_right;
-    let origin_top = self.settings.padding_top + self.settings.header_height;
-    let origin_bottom = size.height - self.settings.footer_height - self.settings.padding_bottom;
+    self.line_extents.truncate(lines.len());
 
-    let label_font = ctx
-      .text()
-      .new_font_by_name(&env.get(theme::FONT_NAME), self.settings.font_size)
-      .build()
-      .unwrap();
-
-    for line in lines.iter() {
-      let mut line_path = BezPath::new();
-      let mut line_polygon = BezPath::new();
-
-      // Move first point into position
-      if let Some((first_x, first_y)) = line.points.first() {
-        let pos_x = origin_left + (first_x.as_() - self.min_x) * self.proportion_x;
-        let pos_y = origin_bottom - (first_y.as_() - self.min_y) * self.proportion_y;
-
-        line_path.move_to((pos_x, pos_y));
-        line_polygon.move_to((pos_x, origin_bottom));
-        line_polygon.line_to((pos_x, pos_y));
+    for (index, line) in lines.iter().enumerate() {
+      if let Some(extent) = self.line_extents.get(index) {
+        if extent.revision == line.revision {
+          continue;
+        }
       }
 
-      // Draw the path along the chart area
-      for (x, y) in line.points.iter().skip(1) {
-        let pos_x = origin_left + (x.as_() - self.min_x) * self.proportion_x;
-        let pos_y = origin_bottom - (y.as_() - self.min_y) * self.proportion_y;
+      let extent = LineExtent {
+        revision: line.revision,
+        bounds: if line.points.is_empty() {
+          None
+        } else {
+          Some(DataBounds::extent(
+            line.points.iter().map(|(x, y)| (x.as_(), y.as_())),
+          ))
+        },
+      };
 
-        line_path.line_to((pos_x, pos_y));
-        line_polygon.line_to((pos_x, pos_y));
+      if index < self.line_extents.len() {
+        self.line_extents[index] = extent;
+      } else {
+        self.line_extents.push(extent);
       }
+    }
+  }
 
-      ctx.stroke(
-        line_path.clone(),
-        &line.color,
-        self.settings.path_stroke_width,
-      );
+  /// Bounds of the primary and secondary Y axes of overlapping lines, padded, from the extents
+  /// measured by `measure_lines`. Same as `data_bounds`, without going through every point.
+  fn bounds_from_extents<X, Y>(
+    &self,
+    lines: &[Line<X, Y>],
+    bands: &[Band<X, Y>],
+  ) -> (DataBounds, Option<DataBounds>)
+  where
+    X: AsPrimitive<f64>,
+    Y: AsPrimitive<f64>,
+  {
+    let axis = |secondary: bool| {
+      let measured = lines
+        .iter()
+        .zip(&self.line_extents)
+        .enumerate()
+        .filter(move |(_, (line, _))| line.secondary == secondary)
+        .filter_map(|(index, (_, extent))| Some((index, extent.bounds?)));
 
-      if let Some((last_x, _)) = line.points.iter().last() {
-        let pos_x = origin_left + (last_x.as_() - self.min_x) * self.proportion_x;
+      // Hidden lines leave the range alone, but still count for the precision like in `from_lines`
+      let precision_y = measured
+        .clone()
+        .map(|(_, bounds)| bounds.precision_y)
+        .max()
+        .unwrap_or(1);
+      let range = measured
+        .filter(|(index, _)| !self.hidden_lines.contains(index))
+        .map(|(_, bounds)| bounds)
+        .reduce(DataBounds::union);
+      (range, precision_y)
+    };
 
-        line_polygon.line_to((pos_x, origin_bottom));
-        ctx.fill(
-          line_polygon.clone(),
-          &LinearGradient::new(
-            UnitPoint::TOP,
-            UnitPoint::BOTTOM,
-            (
-              line.color.clone().with_alpha(0.5),
-              line.color.clone().with_alpha(0.0),
-            ),
-          ),
-        );
-      }
+    let (range, precision_y) = axis(false);
+    let range = union_bands(range, bands);
+    let bounds = DataBounds {
+      precision_y,
+      ..range.unwrap_or_else(|| DataBounds::extent(std::iter::empty()))
+    };
 
-      // Highlight the closest point to the cursor position
-      if self.cursor_pos.x > origin_left
-        && self.cursor_pos.x < origin_right
-        && self.cursor_pos.y > origin_top
-        && self.cursor_pos.y < origin_bottom
-      {
-        let closest_point = line.points.iter().min_by(|(a, _), (b, _)| {
-          ((a.as_() - self.min_x) - (self.cursor_pos.x - origin_left) / self.proportion_x)
-            .abs()
-            .partial_cmp(
-              &((b.as_() - self.min_x) - (self.cursor_pos.x - origin_left) / self.proportion_x)
-                .abs(),
-            )
-            .unwrap_or(Ordering::Equal)
-        });
+    let (range, precision_y) = axis(true);
+    let secondary = range.map(|range| {
+      self.padded(DataBounds {
+        precision_y,
+        ..range
+      })
+    });
 
-        if let Some((x, y)) = closest_point {
-          let pos_x = origin_left + (x.as_() - self.min_x) * self.proportion_x;
-          let pos_y = origin_bottom - (y.as_() - self.min_y) * self.proportion_y;
-
-          // Add circle emphasizing the point
-          let path = Circle::new((pos_x, pos_y), 4.0);
-          ctx.fill(path.clone(), &line.color);
-          ctx.stroke(
-            path,
-            &env.get(theme::BACKGROUND_DARK),
-            self.settings.path_stroke_width,
-          );
+    (self.padded(bounds), secondary)
+  }
 
-          let layout = ctx
-            .text()
-            .new_text_layout(
-              &label_font,
-              &format!("{:.prec$}", y, prec = self.precision_y),
-              std::f64::INFINITY,
-            )
-            .build()
-            .unwrap();
-
-          let text_height = if let Some(metric) = layout.line_metric(0) {
-            self.settings.font_size - (metric.cumulative_height - metric.baseline.floor())
-          } else {
-            self.settings.font_size
-          };
+  /// Leaves the Y padding empty below and above the bounds, as a share of the span of the
+  /// logarithms on a log axis.
+  fn padded(&self, bounds: DataBounds) -> DataBounds {
+    if self.settings.axis.log_y {
+      bounds.padded_log_y(self.settings.y_padding)
+    } else {
+      bounds.padded_y(self.settings.y_padding)
+    }
+  }
 
-          // Draw box with the point Y value
-          if pos_x + layout.width() < origin_right - 15.0 {
-            let rect = Rect::from_points(
-              Point::new(pos_x + 8.0, pos_y - 5.0 - text_height / 2.0),
-              Point::new(
-                pos_x + 18.0 + layout.width(),
-                pos_y + 5.0 + text_height / 2.0,
-              ),
-            );
+  /// Resolves where `data` is plotted in a chart of the given size: the plot area, the axis
+  /// scales and the conversions between data values and pixels. Painting, hit testing and SVG
+  /// export all lay the chart out this way.
+  pub fn compute_geometry<X, Y>(&self, data: &LineChartData<X, Y>, size: Size) -> ChartGeometry
+  where
+    X: Data + AsPrimitive<f64> + Num,
+    Y: Data + AsPrimitive<f64> + Num,
+  {
+    let plotted = self.plotted_values(&data.lines);
+    let (bounds, secondary) = self.data_bounds(&data.lines, &data.bands, &plotted);
 
-            ctx.fill(rect, &env.get(theme::FOREGROUND_DARK));
+    self.geometry(
+      size,
+      &bounds,
+      secondary.as_ref(),
+      &data.categories,
+      data.title.is_some(),
+    )
+  }
 
-            ctx.draw_text(
-              &layout,
-              (pos_x + 13.0, pos_y + (self.settings.font_size * 0.334)),
-              &env.get(theme::BACKGROUND_DARK),
-            );
-          } else {
-            let rect = Rect::from_points(
-              Point::new(pos_x - 8.0, pos_y - 5.0 - text_height / 2.0),
-              Point::new(
-                pos_x - 18.0 - layout.width(),
-                pos_y + 5.0 + text_height / 2.0,
-              ),
-            );
+  /// The data X and Y values at a position in the widget, as of the last paint, or `None` before
+  /// the chart is painted. Values are those of the primary Y axis.
+  pub fn pixel_to_data(&self, point: Point) -> Option<(f64, f64)> {
+    self
+      .painted
+      .as_ref()
+      .map(|geometry| geometry.pixel_to_data(point))
+  }
 
-            ctx.fill(rect, &env.get(theme::FOREGROUND_DARK));
+  /// The position in the widget data values are drawn at, against the primary Y axis, as of the
+  /// last paint, or `None` before the chart is painted.
+  pub fn data_to_pixel(&self, x: f64, y: f64) -> Option<Point> {
+    self
+      .painted
+      .as_ref()
+      .map(|geometry| geometry.data_to_pixel(x, y))
+  }
 
-            ctx.draw_text(
-              &layout,
-              (
-                pos_x - 13.0 - layout.width(),
-                pos_y + (self.settings.font_size * 0.334),
-              ),
-              &env.get(theme::BACKGROUND_DARK),
-            );
-          }
-        }
-      }
-    }
+  /// The X and Y axis labels drawn for `data` in a chart of the given size, with their
+  /// positions, as painting would place them.
+  pub fn axis_labels<X, Y>(
+    &self,
+    data: &LineChartData<X, Y>,
+    size: Size,
+  ) -> (Vec<AxisLabel>, Vec<AxisLabel>)
+  where
+    X: Data + AsPrimitive<f64> + Num,
+    Y: Data + AsPrimitive<f64> + Num,
+  {
+    let geometry = self.compute_geometry(data, size);
+    (geometry.x_labels(), geometry.y_labels())
   }
 
-  fn paint_cursor_reference(&self, ctx: &mut PaintCtx, env: &Env) {
-    let size = ctx.size();
+  /// Bounds of the primary and secondary Y axes, padded. Percentages always span 0 to 100.
+  fn data_bounds<X, Y>(
+    &self,
+    lines: &[Line<X, Y>],
+    bands: &[Band<X, Y>],
+    plotted: &[Vec<(f64, f64)>],
+  ) -> (DataBounds, Option<DataBounds>)
+  where
+    X: Num + AsPrimitive<f64>,
+    Y: Num + AsPrimitive<f64>,
+  {
+    let has_points = plotted
+      .iter()
+      .zip(lines)
+      .any(|(points, line)| !line.secondary && !points.is_empty());
+    let lines_bounds = DataBounds::from_lines(lines, plotted, false);
+    let bounds = self.padded(
+      union_bands(Some(lines_bounds).filter(|_| has_points), bands).unwrap_or(lines_bounds),
+    );
+    let secondary =
+      DataBounds::secondary_from_lines(lines, plotted).map(|bounds| self.padded(bounds));
 
-    let origin_left = self.settings.padding_left;
-    let origin_right = size.width - self.settings.padding_right;
-    let origin_top = self.settings.padding_top + self.settings.header_height;
-    let origin_bottom = size.height - self.settings.footer_height - self.settings.padding_bottom;
+    if self.settings.stack_mode != StackMode::PercentStacked {
+      return (bounds, secondary);
+    }
 
-    if self.cursor_pos.x > origin_left
-      && self.cursor_pos.x < origin_right
-      && self.cursor_pos.y > origin_top
-      && self.cursor_pos.y < origin_bottom
-    {
-      // Y
-      let mut line_path = BezPath::new();
+    let percent = |bounds: DataBounds| DataBounds {
+      min_y: 0.0,
+      max_y: 100.0,
+      ..bounds
+    };
+    (percent(bounds), secondary.map(percent))
+  }
 
-      let mut x = origin_left;
-      while x <= origin_right {
-        line_path.move_to((x.min(origin_right), self.cursor_pos.y));
-        line_path.line_to(((x + 5.0).min(origin_right), self.cursor_pos.y));
+  /// Resolves the plot area and axis scales for the given size, leaving room for the legend.
+  fn geometry(
+    &self,
+    size: Size,
+    bounds: &DataBounds,
+    secondary: Option<&DataBounds>,
+    categories: &[String],
+    has_title: bool,
+  ) -> ChartGeometry {
+    let size = Size::new(size.width, size.height - self.legend_height());
 
-        x += 10.0;
-      }
+    self
+      .settings
+      .axis
+      .resolve_geometry(size, bounds, secondary, categories, has_title)
+  }
 
-      ctx.stroke(
-        line_path,
-        &env.get(theme::FOREGROUND_DARK).with_alpha(0.3),
-        1.0,
-      );
+  fn footer_height(&self) -> f64 {
+    if self.settings.footer.is_some() {
+      self.settings.axis.font_size * 2.0
+    } else {
+      0.0
+    }
+  }
 
-      // Draw reference value at the end
-      let value = (origin_bottom - self.cursor_pos.y) / self.proportion_y + self.min_y;
+  /// Vertical center of the footer caption, just above the legend.
+  fn footer_center_y(&self, geometry: &ChartGeometry) -> f64 {
+    geometry.area.y1 - self.footer_height() / 2.0
+  }
 
-      let label_font = ctx
-        .text()
-        .new_font_by_name(&env.get(theme::FONT_NAME), self.settings.font_size)
-        .build()
-        .unwrap();
+  /// Vertical center of the legend, at the bottom of the area inside the margin.
+  fn legend_center_y(&self, geometry: &ChartGeometry) -> f64 {
+    geometry.area.y1 + self.legend_height() / 2.0
+  }
 
-      let layout = ctx
-        .text()
-        .new_text_layout(
-          &label_font,
-          &format!("{:.prec$}", value, prec = self.precision_y as usize),
-          std::f64::INFINITY,
-        )
-        .build()
-        .unwrap();
+  /// Shades the selection rectangle, clipped to the plot area.
+  fn paint_brush(&self, ctx: &mut PaintCtx, geometry: &ChartGeometry, env: &Env) {
+    let brush = match self.brush {
+      Some(brush) => brush,
+      None => return,
+    };
 
-      let text_height = if let Some(metric) = layout.line_metric(0) {
-        self.settings.font_size - (metric.cumulative_height - metric.baseline.floor())
-      } else {
-        self.settings.font_size
-      };
+    let rect = Rect::from_points(brush.start, brush.end).intersect(geometry.plot);
+    let color = chart_theme::axis_color(env);
+    ctx.fill(rect, &color.clone().with_alpha(BRUSH_FILL_ALPHA));
+    ctx.stroke(rect, &color.with_alpha(BRUSH_STROKE_ALPHA), 1.0);
+  }
 
-      let rect = Rect::from_points(
-        Point::new(origin_right, self.cursor_pos.y - (10.0 + text_height) / 2.0),
-        // Discover max width possible?
-        Point::new(
-          origin_right + layout.width() + 10.0,
-          self.cursor_pos.y + (10.0 + text_height) / 2.0,
-        ),
-      );
+  fn paint_footer(&self, ctx: &mut PaintCtx, geometry: &ChartGeometry, env: &Env) {
+    let caption = match &self.settings.footer {
+      Some(caption) => caption,
+      None => return,
+    };
 
-      ctx.fill(rect, &env.get(theme::FOREGROUND_DARK));
+    let font_size = self.settings.axis.font_size;
+    let font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+    let layout = ctx
+      .text()
+      .new_text_layout(&font, caption, f64::INFINITY)
+      .build()
+      .unwrap();
 
-      ctx.draw_text(
-        &layout,
-        (
-          origin_right + 5.0,
-          self.cursor_pos.y + (self.settings.font_size * 0.334),
-        ),
-        &env.get(theme::BACKGROUND_DARK),
-      );
+    ctx.draw_text(
+      &layout,
+      (
+        geometry.area.center().x - layout.width() / 2.0,
+        self.footer_center_y(geometry) + font_size * 0.334,
+      ),
+      &chart_theme::axis_color(env),
+    );
+  }
+
+  fn legend_height(&self) -> f64 {
+    if self.settings.show_legend {
+      self.settings.axis.font_size * 2.5
+    } else {
+      0.0
+    }
+  }
 
-      // X
+  /// The points of each visible line as they are plotted: the data itself, or offset by the sum
+  /// of the previous visible lines at the same point index when stacked. Hidden lines have no
+  /// points.
+  fn plotted_values<X, Y>(&self, lines: &[Line<X, Y>]) -> Vec<Vec<(f64, f64)>>
+  where
+    X: Num + AsPrimitive<f64>,
+    Y: Num + AsPrimitive<f64>,
+  {
+    plotted_values(lines, self.settings.stack_mode, &self.hidden_lines)
+  }
 
-      let mut line_path = BezPath::new();
+  /// Draws a swatch and the name of every line below the plot, dimming hidden ones, and records
+  /// where each entry was drawn for hit testing.
+  fn paint_legend<X, Y>(
+    &mut self,
+    ctx: &mut PaintCtx,
+    lines: &[Line<X, Y>],
+    geometry: &ChartGeometry,
+    env: &Env,
+  ) {
+    self.legend_entries.clear();
 
-      let mut y = origin_top;
-      while y <= origin_bottom {
-        line_path.move_to((self.cursor_pos.x, y.min(origin_bottom)));
-        line_path.line_to((self.cursor_pos.x, (y + 5.0).min(origin_bottom)));
+    if !self.settings.show_legend {
+      return;
+    }
 
-        y += 10.0;
-      }
+    let font_size = self.settings.axis.font_size;
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
 
-      ctx.stroke(
-        line_path,
-        &env.get(theme::FOREGROUND_DARK).with_alpha(0.3),
-        1.0,
-      );
+    let center_y = self.legend_center_y(geometry);
+    let swatch_size = font_size * 0.8;
+    let mut left = geometry.area.x0 + self.settings.axis.padding_left;
 
-      // Draw reference value at the end
-      let value = (self.cursor_pos.x - origin_left) / self.proportion_x + self.min_x;
+    for (index, line) in lines.iter().enumerate() {
+      let alpha = if self.hidden_lines.contains(&index) {
+        0.3
+      } else {
+        1.0
+      };
 
       let layout = ctx
         .text()
-        .new_text_layout(
-          &label_font,
-          &format!("{:.prec$}", value, prec = self.precision_x as usize),
-          std::f64::INFINITY,
-        )
+        .new_text_layout(&label_font, &legend_name(line, index), f64::INFINITY)
         .build()
         .unwrap();
 
-      let rect = Rect::from_points(
-        Point::new(
-          self.cursor_pos.x - layout.width() / 2.0 - 5.0,
-          origin_bottom,
-        ),
-        Point::new(
-          self.cursor_pos.x + layout.width() / 2.0 + 5.0,
-          origin_bottom + text_height + 10.0,
-        ),
+      let swatch = Rect::from_origin_size(
+        (left, center_y - swatch_size / 2.0),
+        (swatch_size, swatch_size),
       );
-
-      ctx.fill(rect, &env.get(theme::FOREGROUND_DARK));
+      ctx.fill(swatch, &line.color.clone().with_alpha(alpha));
 
       ctx.draw_text(
         &layout,
-        (
-          self.cursor_pos.x - layout.width() / 2.0,
-          origin_bottom + text_height + 5.0,
-        ),
-        &env.get(theme::BACKGROUND_DARK),
+        (left + swatch_size + 5.0, center_y + font_size * 0.334),
+        &chart_theme::axis_color(env).with_alpha(alpha),
       );
+
+      let width = swatch_size + 5.0 + layout.width();
+      self.legend_entries.push(Rect::from_origin_size(
+        (left, center_y - font_size),
+        (width, font_size * 2.0),
+      ));
+
+      left += width + 15.0;
     }
   }
-}
 
-impl<X, Y> Line<X, Y> {
-  pub fn new(points: Vec<(X, Y)>, color: Color) -> Self {
-    Self { points, color }
-  }
-}
+  /// Fills each band between its series, under the lines.
+  fn paint_bands<X, Y>(&self, ctx: &mut PaintCtx, bands: &[Band<X, Y>], geometry: &ChartGeometry)
+  where
+    X: AsPrimitive<f64>,
+    Y: AsPrimitive<f64>,
+  {
+    for band in bands {
+      let (lower, upper, region) = band.paths(geometry);
 
-impl<X, Y> LineChartData<X, Y>
-where
-  X: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
-  Y: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
-{
-  pub fn new() -> Self {
-    Self {
-      title: None,
-      lines: Vec::new(),
+      if let Some(region) = region {
+        ctx.fill(region, &band.color.clone().with_alpha(BAND_ALPHA));
+      }
+
+      if band.outline {
+        ctx.stroke(lower, &band.color, 1.0);
+        ctx.stroke(upper, &band.color, 1.0);
+      }
     }
   }
 
-  pub fn with_title(mut self, title: impl Into<String>) -> Self {
-    self.title = Some(title.into());
-    self
-  }
+  fn paint_lines<X, Y>(
+    &self,
+    ctx: &mut PaintCtx,
+    lines: &[Line<X, Y>],
+    plotted: &[Vec<(f64, f64)>],
+    geometry: &ChartGeometry,
+    env: &Env,
+  ) where
+    X: Num + AsPrimitive<f64>,
+    Y: Num + AsPrimitive<f64> + Display,
+  {
+    let hovered = self.hovered_points(lines, plotted, geometry);
 
-  pub fn with_line(mut self, line: Line<X, Y>) -> Self {
-    self.lines.push(line);
-    self
-  }
-}
+    for (index, line) in lines.iter().enumerate() {
+      if self.hidden_lines.contains(&index) {
+        continue;
+      }
 
-impl<X, Y> Data for LineChartData<X, Y>
-where
-  X: AsPrimitive<f64> + Data + PartialEq,
-  Y: AsPrimitive<f64> + Data + PartialEq,
-{
-  fn same(&self, other: &Self) -> bool {
-    let basic_test = self.title == other.title && self.lines.len() == other.lines.len();
+      let geometry = geometry.y_axis_for(line.secondary);
+      let (line_path, line_polygon) =
+        line_paths(&plotted[index], self.below(lines, plotted, index), geometry);
 
-    basic_test
-      && self
-        .lines
-        .iter()
-        .zip(other.lines.iter())
-        .all(|(line_a, line_b)| {
-          line_a
-            .points
-            .iter()
-            .zip(line_b.points.iter())
-            .all(|(point_a, point_b)| point_a == point_b)
-        })
-  }
-}
+      let opacity = self.line_opacity(index, &plotted[index]);
 
-impl<X, Y> Widget<LineChartData<X, Y>> for LineChart
-where
-  X: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
-  Y: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
-{
-  fn event(
-    &mut self,
-    ctx: &mut EventCtx,
-    event: &Event,
-    _data: &mut LineChartData<X, Y>,
-    _env: &Env,
-  ) {
-    match event {
-      Event::MouseMove(e) => {
-        self.cursor_pos = e.pos;
-        ctx.request_paint();
+      if line.stroke {
+        ctx.stroke(
+          line_path.clone(),
+          &scale_alpha(&line.color, opacity),
+          self.stroke_width(line),
+        );
+      }
+
+      if let Some(point) = lone_point(&plotted[index], geometry) {
+        ctx.fill(
+          Circle::new(point, self.lone_point_radius(line)),
+          &scale_alpha(&line.color, opacity),
+        );
+      }
+
+      if let Some(line_polygon) = line_polygon {
+        match &line.area_fill {
+          AreaFill::Solid(alpha) => {
+            ctx.fill(
+              line_polygon.clone(),
+              &line.color.clone().with_alpha(alpha * opacity),
+            );
+          }
+          AreaFill::Gradient { direction, stops } => {
+            let (start, end, stops) = self.area_gradient(line, *direction, stops, opacity);
+            let stops: Vec<GradientStop> = stops
+              .into_iter()
+              .map(|(offset, color)| GradientStop {
+                pos: offset as f32,
+                color,
+              })
+              .collect();
+            ctx.fill(
+              line_polygon.clone(),
+              &LinearGradient::new(start, end, stops),
+            );
+          }
+        }
+
+        if line.area_outline {
+          ctx.stroke(line_polygon, &scale_alpha(&line.color, opacity), 1.0);
+        }
       }
-      _ => {}
     }
-  }
 
-  fn lifecycle(
-    &mut self,
-    _ctx: &mut LifeCycleCtx,
-    event: &LifeCycle,
-    data: &LineChartData<X, Y>,
-    _env: &Env,
-  ) {
-    match event {
-      LifeCycle::WidgetAdded => self.update_reference_data(data),
-      _ => (),
+    if self.settings.data_labels {
+      self.paint_data_labels(ctx, lines, plotted, geometry, env);
     }
-  }
 
-  fn update(
-    &mut self,
-    _ctx: &mut UpdateCtx,
-    _old_data: &LineChartData<X, Y>,
-    _data: &LineChartData<X, Y>,
-    _env: &Env,
-  ) {
-  }
+    if !self.settings.point_tooltip {
+      return;
+    }
 
-  fn layout(
-    &mut self,
-    _layout_ctx: &mut LayoutCtx,
-    bc: &BoxConstraints,
-    _data: &LineChartData<X, Y>,
-    _env: &Env,
-  ) -> Size {
-    bc.max()
+    for (index, line) in lines.iter().enumerate() {
+      if self.hidden_lines.contains(&index) {
+        continue;
+      }
+      let geometry = geometry.y_axis_for(line.secondary);
+
+      // Highlight the closest point to the cursor position
+      if self.settings.tooltip_mode == TooltipMode::PerSeries {
+        if let Some(point_index) = hovered[index] {
+          let (_, y) = &line.points[point_index];
+          let position = match highlight_position(plotted[index][point_index], geometry) {
+            Some(position) => position,
+            None => continue,
+          };
+
+          self.paint_highlight(ctx, position, line, env);
+
+          self.paint_value_box(
+            ctx,
+            position,
+            &self.format_value(line, y),
+            geometry.plot.x1,
+            env,
+          );
+        }
+      }
+    }
+
+    if self.settings.tooltip_mode == TooltipMode::Unified {
+      self.paint_unified_tooltip(ctx, lines, plotted, &hovered, geometry, env);
+    }
   }
 
-  fn paint(&mut self, ctx: &mut PaintCtx, data: &LineChartData<X, Y>, env: &Env) {
-    self.paint_labels(ctx, data, env);
-    self.paint_cursor_reference(ctx, env);
-    self.paint_lines(ctx, &data.lines, env);
+  /// Emphasizes a point whose value is shown, ringing it when the points react to the pointer.
+  fn paint_highlight<X, Y>(&self, ctx: &mut PaintCtx, point: Point, line: &Line<X, Y>, env: &Env) {
+    let radius = self.settings.hover_radius;
+    let path = Circle::new(point, radius);
+    ctx.fill(path, &line.color);
+    ctx.stroke(
+      path,
+      &chart_theme::outline_color(env),
+      self.stroke_width(line),
+    );
+
+    if self.is_interactive() {
+      ctx.stroke(
+        Circle::new(point, radius + HOVER_RING_GAP),
+        &line.color.clone().with_alpha(HOVER_RING_ALPHA),
+        2.0,
+      );
+    }
   }
-}
 
-fn get_precision<N>(i: N) -> usize
-where
-  N: Num + AsPrimitive<f64>,
-{
-  let i = i.as_();
-  let mut e = 1.0f64;
-  while (i * e).round() / e != i {
-    e *= 10.;
+  /// Rings the point stepped to with the arrow keys in the focus color, and shows its value.
+  fn paint_focused_point<X, Y>(
+    &self,
+    ctx: &mut PaintCtx,
+    lines: &[Line<X, Y>],
+    plotted: &[Vec<(f64, f64)>],
+    geometry: &ChartGeometry,
+    env: &Env,
+  ) where
+    X: Num + AsPrimitive<f64>,
+    Y: Num + AsPrimitive<f64> + Display,
+  {
+    let (index, point_index) = match self.focused_point {
+      Some(focused) if !self.hidden_lines.contains(&focused.0) => focused,
+      _ => return,
+    };
+    // The data may have changed since the point was focused
+    let (line, (_, y), (plot_x, plot_y)) = match (
+      lines.get(index),
+      plotted
+        .get(index)
+        .and_then(|points| points.get(point_index)),
+    ) {
+      (Some(line), Some(plotted)) => match line.points.get(point_index) {
+        Some(point) => (line, point, *plotted),
+        None => return,
+      },
+      _ => return,
+    };
+
+    let geometry = geometry.y_axis_for(line.secondary);
+    let point = geometry.data_to_pixel(plot_x, plot_y);
+    if !in_plot(geometry.plot, point) {
+      return;
+    }
+
+    let radius = self.settings.hover_radius;
+    ctx.fill(Circle::new(point, radius), &line.color);
+    ctx.stroke(
+      Circle::new(point, radius + HOVER_RING_GAP),
+      &chart_theme::focus_color(env),
+      2.0,
+    );
+
+    self.paint_value_box(
+      ctx,
+      point,
+      &self.format_value(line, y),
+      geometry.plot.x1,
+      env,
+    );
   }
 
-  return (e.ln() / 10.0f64.ln()).round() as usize;
+  /// Draws the value of every point of the visible lines, in the line's color.
+  fn paint_data_labels<X, Y>(
+    &self,
+    ctx: &mut PaintCtx,
+    lines: &[Line<X, Y>],
+    plotted: &[Vec<(f64, f64)>],
+    geometry: &ChartGeometry,
+    env: &Env,
+  ) where
+    Y: Display,
+  {
+    let font_size = self.data_label_font_size();
+    let font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+
+    for (index, line) in lines.iter().enumerate() {
+      if self.hidden_lines.contains(&index) {
+        continue;
+      }
+
+      let geometry = geometry.y_axis_for(line.secondary);
+      let pixels = to_pixels(&plotted[index], geometry);
+
+      for (point_index, (_, y)) in line.points.iter().enumerate() {
+        let layout = ctx
+          .text()
+          .new_text_layout(&font, &self.format_value(line, y), f64::INFINITY)
+          .build()
+          .unwrap();
+
+        let size = Size::new(layout.width(), font_size);
+        if let Some(rect) = data_label_rect(&pixels, point_index, size, geometry.plot) {
+          ctx.draw_text(&layout, (rect.x0, rect.y1 - font_size * 0.2), &line.color);
+        }
+      }
+    }
+  }
+
+  fn data_label_font_size(&self) -> f64 {
+    self.settings.axis.font_size * 0.85
+  }
+
+  /// Draws `text` in a box beside `anchor`, on its right unless the box would cross `right_edge`.
+  fn paint_value_box(
+    &self,
+    ctx: &mut PaintCtx,
+    anchor: Point,
+    text: &str,
+    right_edge: f64,
+    env: &Env,
+  ) {
+    let style = &self.settings.tooltip_style;
+    let font_size = self.tooltip_font_size();
+
+    let font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+    let layout = ctx
+      .text()
+      .new_text_layout(&font, text, f64::INFINITY)
+      .build()
+      .unwrap();
+
+    let text_height = if let Some(metric) = layout.line_metric(0) {
+      font_size - (metric.cumulative_height - metric.baseline.floor())
+    } else {
+      font_size
+    };
+
+    let size = Size::new(
+      layout.width() + style.padding * 2.0,
+      text_height + style.padding * 2.0,
+    );
+    let left = if anchor.x + TOOLTIP_OFFSET + size.width < right_edge {
+      anchor.x + TOOLTIP_OFFSET
+    } else {
+      anchor.x - TOOLTIP_OFFSET - size.width
+    };
+
+    let rect = Rect::from_origin_size((left, anchor.y - size.height / 2.0), size);
+    ctx.fill(
+      rect.to_rounded_rect(style.corner_radius),
+      &self.tooltip_background(env),
+    );
+
+    ctx.draw_text(
+      &layout,
+      (left + style.padding, anchor.y + font_size * 0.334),
+      &self.tooltip_text_color(env),
+    );
+  }
+
+  fn tooltip_font_size(&self) -> f64 {
+    let style = &self.settings.tooltip_style;
+    style.font_size.unwrap_or(self.settings.axis.font_size)
+  }
+
+  fn tooltip_background(&self, env: &Env) -> Color {
+    let style = &self.settings.tooltip_style;
+    style
+      .background
+      .clone()
+      .unwrap_or_else(|| chart_theme::tooltip_bg(env))
+  }
+
+  fn tooltip_text_color(&self, env: &Env) -> Color {
+    let style = &self.settings.tooltip_style;
+    style
+      .text_color
+      .clone()
+      .unwrap_or_else(|| chart_theme::tooltip_fg(env))
+  }
+
+  /// Draws a single box listing the value of every line at the data X closest to the cursor, with
+  /// a color swatch per line, and marks the corresponding points.
+  fn paint_unified_tooltip<X, Y>(
+    &self,
+    ctx: &mut PaintCtx,
+    lines: &[Line<X, Y>],
+    plotted: &[Vec<(f64, f64)>],
+    hovered: &[Option<usize>],
+    geometry: &ChartGeometry,
+    env: &Env,
+  ) where
+    X: Num + AsPrimitive<f64>,
+    Y: Num + AsPrimitive<f64> + Display,
+  {
+    let font_size = self.tooltip_font_size();
+    let pos_x = self.crosshair_position(plotted, geometry).x;
+
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+
+    let mut rows = Vec::with_capacity(lines.len());
+    for (index, line) in lines.iter().enumerate() {
+      if let Some(point_index) = hovered[index] {
+        let (_, y) = &line.points[point_index];
+        let (plot_x, plot_y) = plotted[index][point_index];
+        let line_geometry = geometry.y_axis_for(line.secondary);
+        let point = line_geometry.data_to_pixel(plot_x, plot_y);
+
+        if in_plot(geometry.plot, point) {
+          self.paint_highlight(ctx, point, line, env);
+        }
+
+        let layout = ctx
+          .text()
+          .new_text_layout(&label_font, &self.format_value(line, y), f64::INFINITY)
+          .build()
+          .unwrap();
+
+        rows.push((line.color.clone(), layout));
+      }
+    }
+
+    if rows.is_empty() {
+      return;
+    }
+
+    let padding = self.settings.tooltip_style.padding;
+    let swatch_size = font_size * 0.6;
+    let row_height = font_size + 4.0;
+    let text_width = rows
+      .iter()
+      .map(|(_, layout)| layout.width())
+      .fold(0.0, f64::max);
+
+    let width = padding * 3.0 + swatch_size + text_width;
+    let height = padding * 2.0 + row_height * rows.len() as f64;
+
+    // Keep the box beside the crosshair, flipping to the left near the right edge
+    let left = if pos_x + TOOLTIP_OFFSET + width < geometry.plot.x1 {
+      pos_x + TOOLTIP_OFFSET
+    } else {
+      pos_x - TOOLTIP_OFFSET - width
+    };
+    let top = (self.cursor_pos.y - height / 2.0)
+      .min(geometry.plot.y1 - height)
+      .max(geometry.plot.y0);
+
+    let rect = Rect::from_origin_size((left, top), (width, height));
+    ctx.fill(
+      rect.to_rounded_rect(self.settings.tooltip_style.corner_radius),
+      &self.tooltip_background(env),
+    );
+
+    for (index, (color, layout)) in rows.iter().enumerate() {
+      let center_y = top + padding + row_height * (index as f64 + 0.5);
+
+      let swatch = Rect::from_center_size(
+        (left + padding + swatch_size / 2.0, center_y),
+        (swatch_size, swatch_size),
+      );
+      ctx.fill(swatch, color);
+
+      ctx.draw_text(
+        layout,
+        (
+          left + padding * 2.0 + swatch_size,
+          center_y + font_size * 0.334,
+        ),
+        &self.tooltip_text_color(env),
+      );
+    }
+  }
+
+  /// Position the crosshair is drawn at, locked to the closest data X when snapping or in
+  /// unified tooltip mode.
+  fn crosshair_position(&self, plotted: &[Vec<(f64, f64)>], geometry: &ChartGeometry) -> Point {
+    if self.settings.tooltip_mode == TooltipMode::PerSeries && !self.settings.crosshair_snap {
+      return self.cursor_pos;
+    }
+
+    closest_x(plotted, geometry.to_value_x(self.cursor_pos.x)).map_or(self.cursor_pos, |x| {
+      Point::new(geometry.to_pixel_x(x), self.cursor_pos.y)
+    })
+  }
+
+  /// Bounds and plotted values as currently drawn, interpolated from the previous data while a
+  /// transition runs.
+  fn displayed_state<X, Y>(&self, data: &LineChartData<X, Y>) -> DisplayedState
+  where
+    X: Num + Data + AsPrimitive<f64>,
+    Y: Num + Data + AsPrimitive<f64>,
+  {
+    let plotted = self.plotted_values(&data.lines);
+
+    match (&self.transition, self.transition_progress()) {
+      (Some(Transition { from, .. }), Some(progress)) => DisplayedState {
+        bounds: from.bounds.interpolate(&self.bounds, progress),
+        secondary_bounds: match (&from.secondary_bounds, &self.secondary_bounds) {
+          (Some(from), Some(to)) => Some(from.interpolate(to, progress)),
+          (_, to) => *to,
+        },
+        plotted: interpolate_plotted(&from.plotted, &plotted, progress),
+      },
+      _ => DisplayedState {
+        bounds: self.bounds,
+        secondary_bounds: self.secondary_bounds,
+        plotted,
+      },
+    }
+  }
+
+  fn stroke_width<X, Y>(&self, line: &Line<X, Y>) -> f64 {
+    line.width.unwrap_or(self.settings.path_stroke_width)
+  }
+
+  /// Radius of the dot standing in for a line with a single point, a little wider than the line.
+  fn lone_point_radius<X, Y>(&self, line: &Line<X, Y>) -> f64 {
+    self.stroke_width(line) + 1.5
+  }
+
+  /// Eased progress of the running transition, from 0 to 1. `None` when nothing is animating.
+  fn transition_progress(&self) -> Option<f64> {
+    let transition = self.transition.as_ref()?;
+    let progress = transition.elapsed.as_secs_f64() / self.settings.animation.as_secs_f64();
+
+    // Ease out, so the motion settles gently
+    Some(1.0 - (1.0 - progress.min(1.0)).powi(3))
+  }
+
+  /// Opacity of a line, below 1 while it fades in during a transition.
+  fn line_opacity(&self, index: usize, plotted: &[(f64, f64)]) -> f64 {
+    match (&self.transition, self.transition_progress()) {
+      (Some(transition), Some(progress))
+        if transition.from.plotted.get(index).map(Vec::len) != Some(plotted.len()) =>
+      {
+        progress
+      }
+      _ => 1.0,
+    }
+  }
+
+  /// Ends and color stops of a gradient filling the area under `line`, on its bounding box.
+  /// Reversed axes flip the ends, so gradients still start on the side of the line or of the
+  /// lowest X.
+  fn area_gradient<X, Y>(
+    &self,
+    line: &Line<X, Y>,
+    direction: FillDirection,
+    stops: &[(f64, f64)],
+    opacity: f64,
+  ) -> (UnitPoint, UnitPoint, Vec<(f64, Color)>) {
+    let axis = &self.settings.axis;
+    let (start, end) = match direction {
+      FillDirection::Vertical if axis.reversed_y => (UnitPoint::BOTTOM, UnitPoint::TOP),
+      FillDirection::Vertical => (UnitPoint::TOP, UnitPoint::BOTTOM),
+      FillDirection::Horizontal if axis.reversed_x => (UnitPoint::RIGHT, UnitPoint::LEFT),
+      FillDirection::Horizontal => (UnitPoint::LEFT, UnitPoint::RIGHT),
+    };
+    let stops = stops
+      .iter()
+      .map(|(offset, alpha)| (*offset, line.color.clone().with_alpha(alpha * opacity)))
+      .collect();
+
+    (start, end, stops)
+  }
+
+  /// Decimals the values of a line are shown with, as forced by `with_y_decimals` or else from
+  /// the data on its axis.
+  fn precision_y<X, Y>(&self, line: &Line<X, Y>) -> usize {
+    if let Some(decimals) = self.settings.axis.y_decimals {
+      return decimals;
+    }
+    match &self.secondary_bounds {
+      Some(bounds) if line.secondary => bounds.precision_y,
+      _ => self.bounds.precision_y,
+    }
+  }
+
+  /// Formats a Y value of `line` with its own formatter, or else with its precision.
+  fn format_value<X, Y: Display>(&self, line: &Line<X, Y>, y: &Y) -> String {
+    match &line.formatter {
+      Some(ValueFormatter(format)) => format(y),
+      None => format!("{:.prec$}", y, prec = self.precision_y(line)),
+    }
+  }
+
+  /// The boundary a line's fill goes down to, `None` for the bottom of the plot area. Lines only
+  /// stack on lines of the same Y axis.
+  fn below<'a, X, Y>(
+    &self,
+    lines: &[Line<X, Y>],
+    plotted: &'a [Vec<(f64, f64)>],
+    index: usize,
+  ) -> Option<&'a [(f64, f64)]> {
+    match self.settings.stack_mode {
+      StackMode::Stacked | StackMode::PercentStacked => lines[..index]
+        .iter()
+        .zip(plotted)
+        .rev()
+        .find(|(line, points)| line.secondary == lines[index].secondary && !points.is_empty())
+        .map(|(_, points)| points.as_slice()),
+      StackMode::Overlapping => None,
+    }
+  }
+
+  /// Renders the chart as a standalone SVG document of the given size, in the `with_theme` colors.
+  pub fn render_svg<X, Y>(&self, data: &LineChartData<X, Y>, size: Size) -> String
+  where
+    X: Data + AsPrimitive<f64> + Num,
+    Y: Data + AsPrimitive<f64> + Num + Display,
+  {
+    self.export(data, size).finish()
+  }
+
+  /// Renders the chart as a PNG image of the given size, laid out as `render_svg`.
+  #[cfg(feature = "png")]
+  pub fn render_png<X, Y>(&self, data: &LineChartData<X, Y>, size: Size) -> Vec<u8>
+  where
+    X: Data + AsPrimitive<f64> + Num,
+    Y: Data + AsPrimitive<f64> + Num + Display,
+  {
+    raster::rasterize(&self.export(data, size))
+  }
+
+  /// Draws the chart into a document shared by the SVG and PNG exports.
+  fn export<X, Y>(&self, data: &LineChartData<X, Y>, size: Size) -> SvgDocument
+  where
+    X: Data + AsPrimitive<f64> + Num,
+    Y: Data + AsPrimitive<f64> + Num + Display,
+  {
+    let plotted = self.plotted_values(&data.lines);
+    let geometry = self.compute_geometry(data, size);
+    let mut svg = SvgDocument::new(size);
+    let theme = self
+      .settings
+      .theme
+      .clone()
+      .unwrap_or_else(ChartTheme::druid_default);
+
+    if self.settings.axis.chrome {
+      self
+        .settings
+        .axis
+        .render_svg_axes(&mut svg, data.title.as_deref(), &geometry, &theme);
+    }
+
+    for band in data.bands.iter() {
+      let (lower, upper, region) = band.paths(&geometry);
+
+      if let Some(region) = region {
+        svg.fill_path(&region, &band.color.clone().with_alpha(BAND_ALPHA));
+      }
+
+      if band.outline {
+        svg.stroke_path(&lower, &band.color, 1.0);
+        svg.stroke_path(&upper, &band.color, 1.0);
+      }
+    }
+
+    for (index, line) in data.lines.iter().enumerate() {
+      if self.hidden_lines.contains(&index) {
+        continue;
+      }
+
+      let (line_path, line_polygon) = line_paths(
+        &plotted[index],
+        self.below(&data.lines, &plotted, index),
+        geometry.y_axis_for(line.secondary),
+      );
+
+      if line.stroke {
+        svg.stroke_path(&line_path, &line.color, self.stroke_width(line));
+      }
+
+      if let Some(point) = lone_point(&plotted[index], geometry.y_axis_for(line.secondary)) {
+        svg.fill_circle(point, self.lone_point_radius(line), &line.color);
+      }
+
+      if let Some(line_polygon) = line_polygon {
+        match &line.area_fill {
+          AreaFill::Solid(alpha) => {
+            svg.fill_path(&line_polygon, &line.color.clone().with_alpha(*alpha));
+          }
+          AreaFill::Gradient { direction, stops } => {
+            let (start, end, stops) = self.area_gradient(line, *direction, stops, 1.0);
+            svg.fill_path_gradient(&line_polygon, start, end, &stops);
+          }
+        }
+
+        if line.area_outline {
+          svg.stroke_path(&line_polygon, &line.color, 1.0);
+        }
+      }
+    }
+
+    if self.settings.data_labels {
+      self.render_svg_data_labels(&mut svg, &data.lines, &plotted, &geometry);
+    }
+
+    if let Some(caption) = &self.settings.footer {
+      let font_size = self.settings.axis.font_size;
+      svg.text(
+        caption,
+        Point::new(
+          geometry.area.center().x,
+          self.footer_center_y(&geometry) + font_size * 0.334,
+        ),
+        TextAnchor::Middle,
+        font_size,
+        &theme.axis,
+      );
+    }
+
+    if self.settings.show_legend {
+      self.render_svg_legend(&mut svg, &data.lines, &geometry, &theme.axis);
+    }
+
+    svg
+  }
+
+  /// Labels are placed and left out as on screen, from an estimate of their widths.
+  fn render_svg_data_labels<X, Y>(
+    &self,
+    svg: &mut SvgDocument,
+    lines: &[Line<X, Y>],
+    plotted: &[Vec<(f64, f64)>],
+    geometry: &ChartGeometry,
+  ) where
+    Y: Display,
+  {
+    let font_size = self.data_label_font_size();
+
+    for (index, line) in lines.iter().enumerate() {
+      if self.hidden_lines.contains(&index) {
+        continue;
+      }
+
+      let geometry = geometry.y_axis_for(line.secondary);
+      let pixels = to_pixels(&plotted[index], geometry);
+
+      for (point_index, (_, y)) in line.points.iter().enumerate() {
+        let text = self.format_value(line, y);
+        let size = Size::new(text.chars().count() as f64 * font_size * 0.6, font_size);
+
+        if let Some(rect) = data_label_rect(&pixels, point_index, size, geometry.plot) {
+          svg.text(
+            &text,
+            Point::new(rect.center().x, rect.y1 - font_size * 0.2),
+            TextAnchor::Middle,
+            font_size,
+            &line.color,
+          );
+        }
+      }
+    }
+  }
+
+  /// Text isn't measured in SVG, so entries are spaced by an estimate of the name widths.
+  fn render_svg_legend<X, Y>(
+    &self,
+    svg: &mut SvgDocument,
+    lines: &[Line<X, Y>],
+    geometry: &ChartGeometry,
+    color: &Color,
+  ) {
+    let font_size = self.settings.axis.font_size;
+    let center_y = self.legend_center_y(geometry);
+    let swatch_size = font_size * 0.8;
+    let mut left = geometry.area.x0 + self.settings.axis.padding_left;
+
+    for (index, line) in lines.iter().enumerate() {
+      let alpha = if self.hidden_lines.contains(&index) {
+        0.3
+      } else {
+        1.0
+      };
+      let name = legend_name(line, index);
+
+      svg.fill_rect(
+        Rect::from_origin_size(
+          (left, center_y - swatch_size / 2.0),
+          (swatch_size, swatch_size),
+        ),
+        &line.color.clone().with_alpha(alpha),
+      );
+      svg.text(
+        &name,
+        Point::new(left + swatch_size + 5.0, center_y + font_size * 0.334),
+        TextAnchor::Start,
+        font_size,
+        &color.clone().with_alpha(alpha),
+      );
+
+      left += swatch_size + 5.0 + name.chars().count() as f64 * font_size * 0.6 + 15.0;
+    }
+  }
+}
+
+impl Default for TooltipStyle {
+  fn default() -> Self {
+    Self {
+      background: None,
+      text_color: None,
+      corner_radius: 0.0,
+      padding: 5.0,
+      font_size: None,
+    }
+  }
+}
+
+impl Default for LineChart {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl DataBounds {
+  /// Extremes of the lines on the primary, or `secondary`, Y axis as `plotted`, so stacked lines
+  /// are measured at their cumulative values and hidden lines are left out. The precision still
+  /// comes from the data itself.
+  fn from_lines<X, Y>(lines: &[Line<X, Y>], plotted: &[Vec<(f64, f64)>], secondary: bool) -> Self
+  where
+    X: Num + AsPrimitive<f64>,
+    Y: Num + AsPrimitive<f64>,
+  {
+    let on_axis = |line: &&Line<X, Y>| line.secondary == secondary;
+
+    let data = DataBounds::extent(
+      lines
+        .iter()
+        .filter(on_axis)
+        .flat_map(|l| l.points.iter())
+        .map(|(x, y)| (x.as_(), y.as_())),
+    );
+
+    DataBounds {
+      precision_y: data.precision_y,
+      ..DataBounds::extent(
+        lines
+          .iter()
+          .zip(plotted)
+          .filter(|(line, _)| on_axis(line))
+          .flat_map(|(_, points)| points.iter().copied()),
+      )
+    }
+  }
+
+  /// Bounds of the secondary Y axis, `None` when none of the plotted lines are on it. The X range
+  /// is shared with the primary axis.
+  fn secondary_from_lines<X, Y>(lines: &[Line<X, Y>], plotted: &[Vec<(f64, f64)>]) -> Option<Self>
+  where
+    X: Num + AsPrimitive<f64>,
+    Y: Num + AsPrimitive<f64>,
+  {
+    let has_secondary = lines
+      .iter()
+      .zip(plotted)
+      .any(|(line, points)| line.secondary && !points.is_empty());
+
+    if has_secondary {
+      Some(DataBounds::from_lines(lines, plotted, true))
+    } else {
+      None
+    }
+  }
+
+  /// Bounds `progress` of the way from these to `to`, with the precision of `to`.
+  fn interpolate(&self, to: &DataBounds, progress: f64) -> Self {
+    let lerp = |from: f64, to: f64| from + (to - from) * progress;
+
+    DataBounds {
+      min_x: lerp(self.min_x, to.min_x),
+      max_x: lerp(self.max_x, to.max_x),
+      min_y: lerp(self.min_y, to.min_y),
+      max_y: lerp(self.max_y, to.max_y),
+      precision_y: to.precision_y,
+    }
+  }
+}
+
+impl<X, Y> Line<X, Y> {
+  /// A line without points draws nothing and leaves the axis bounds to the other lines. A line
+  /// with a single point is drawn as a dot, without an area below it.
+  ///
+  /// Points are joined in the order given, so they should be sorted by X for the line and the
+  /// area under it to come out right. `sorted_by_x` and `dedup_x` clean up a series that isn't.
+  pub fn new(points: Vec<(X, Y)>, color: Color) -> Self {
+    Self {
+      points,
+      color,
+      width: None,
+      name: None,
+      secondary: false,
+      area_outline: false,
+      area_fill: AreaFill::default(),
+      stroke: true,
+      formatter: None,
+      revision: next_revision(),
+    }
+  }
+
+  /// Collects the points of a line from any iterator of `(x, y)` pairs, as when deriving them
+  /// from another collection:
+  ///
+  /// ```
+  /// use birog::charts::line::{Line, LineChartData};
+  /// use druid::Color;
+  ///
+  /// let prices = [8.69, 8.76, 8.67, 8.61];
+  /// let line = Line::from_iter(
+  ///   prices.iter().enumerate().map(|(idx, price)| (-(idx as i32), *price)),
+  ///   Color::rgb8(0x73, 0xD0, 0xFF),
+  /// );
+  ///
+  /// let data = LineChartData::new().with_line(line.with_name("A"));
+  /// ```
+  pub fn from_iter(points: impl IntoIterator<Item = (X, Y)>, color: Color) -> Self {
+    Self::new(points.into_iter().collect(), color)
+  }
+
+  /// Plots `ys` in order, at X values counting up from zero:
+  ///
+  /// ```
+  /// use birog::charts::line::{Line, LineChartData};
+  /// use druid::Color;
+  ///
+  /// let prices = [8.69, 8.76, 8.67, 8.61];
+  /// let line: Line<i32, f64> = Line::from_ys(prices.iter().copied(), Color::rgb8(0x73, 0xD0, 0xFF));
+  ///
+  /// let data = LineChartData::new().with_line(line.with_name("A"));
+  /// ```
+  pub fn from_ys(ys: impl IntoIterator<Item = Y>, color: Color) -> Self
+  where
+    X: Num + Copy,
+  {
+    let mut x = X::zero();
+    let points = ys
+      .into_iter()
+      .map(|y| {
+        let point = (x, y);
+        x = x + X::one();
+        point
+      })
+      .collect();
+
+    Self::new(points, color)
+  }
+
+  /// Name shown for this line in the legend.
+  pub fn with_name(mut self, name: impl Into<String>) -> Self {
+    self.name = Some(name.into());
+    self
+  }
+
+  /// Overrides the chart's line stroke width for this line.
+  pub fn with_width(mut self, width: f64) -> Self {
+    self.width = Some(width);
+    self
+  }
+
+  /// Plots this line against a secondary Y axis with its own scale, labeled on the right of the
+  /// chart.
+  pub fn with_secondary_axis(mut self, secondary: bool) -> Self {
+    self.secondary = secondary;
+    self
+  }
+
+  /// Formats the values of this line in its tooltip, in the unified tooltip and in its data
+  /// labels, as when lines have different units. Lines without a formatter show their values
+  /// with the decimals of the axis data.
+  pub fn with_formatter(mut self, format: impl Fn(&Y) -> String + 'static) -> Self {
+    self.formatter = Some(ValueFormatter(Arc::new(format)));
+    self
+  }
+
+  /// Also strokes the whole outline of the filled area, baseline and sides included, in the line
+  /// color. Tells overlapping translucent areas apart.
+  pub fn with_area_outline(mut self, outline: bool) -> Self {
+    self.area_outline = outline;
+    self
+  }
+
+  /// Fills the area under the line as `fill` describes, instead of the default fade from half
+  /// opacity at the top to transparent at the bottom.
+  pub fn with_area_fill(mut self, fill: AreaFill) -> Self {
+    self.area_fill = fill;
+    self
+  }
+
+  /// Leaves out the line itself when `false`, so only the area under it is drawn. Its points are
+  /// still highlighted on the edge of the area, and a line of a single point still shows its
+  /// dot.
+  pub fn with_stroke(mut self, stroke: bool) -> Self {
+    self.stroke = stroke;
+    self
+  }
+
+  /// A copy of this line with its points sorted by increasing X. Points with the same X keep
+  /// their order.
+  pub fn sorted_by_x(&self) -> Self
+  where
+    X: AsPrimitive<f64> + Clone,
+    Y: Clone,
+  {
+    let mut line = self.clone();
+    line
+      .points
+      .sort_by(|(a, _), (b, _)| a.as_().partial_cmp(&b.as_()).unwrap_or(Ordering::Equal));
+    line.revision = next_revision();
+    line
+  }
+
+  /// A copy of this line keeping only the first of consecutive points with the same X. Sort the
+  /// line first with `sorted_by_x` to drop every duplicate X.
+  pub fn dedup_x(&self) -> Self
+  where
+    X: AsPrimitive<f64> + Clone,
+    Y: Clone,
+  {
+    let mut line = self.clone();
+    line
+      .points
+      .dedup_by(|(x, _), (previous, _)| x.as_() == previous.as_());
+    line.revision = next_revision();
+    line
+  }
+
+  /// Derives a line of the moving average of this one over `window` points, at the same X
+  /// values, to add to the chart with its own `color`. It uses the same Y axis as this line.
+  pub fn moving_average(
+    &self,
+    window: usize,
+    kind: AverageKind,
+    leading: LeadingPoints,
+    color: Color,
+  ) -> Line<X, f64>
+  where
+    X: Clone,
+    Y: AsPrimitive<f64>,
+  {
+    let values: Vec<f64> = self.points.iter().map(|(_, y)| y.as_()).collect();
+    let points = self
+      .points
+      .iter()
+      .zip(moving_averages(&values, window, kind, leading))
+      .filter_map(|((x, _), average)| Some((x.clone(), average?)))
+      .collect();
+
+    Line::new(points, color).with_secondary_axis(self.secondary)
+  }
+}
+
+impl Line<f64, f64> {
+  /// Pairs `xs` with `ys` in order, as when they come from separate columns. The extra values of
+  /// the longer slice are left out.
+  pub fn from_xy(xs: &[f64], ys: &[f64], color: Color) -> Self {
+    Self::new(xs.iter().copied().zip(ys.iter().copied()).collect(), color)
+  }
+}
+
+impl<X, Y> LineChartData<X, Y>
+where
+  X: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+  Y: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+{
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with_title(mut self, title: impl Into<String>) -> Self {
+    self.title = Some(title.into());
+    self
+  }
+
+  pub fn with_line(mut self, line: Line<X, Y>) -> Self {
+    self.lines.push(line);
+    self
+  }
+
+  /// Labels the X axis with names instead of numbers, such as months: the first category at
+  /// X = 0 and each next one at the next whole X, where `Line::from_ys` plots its values. The
+  /// categories are evenly spaced, with every second, third and so on labeled when they don't
+  /// all fit.
+  pub fn with_categories(
+    mut self,
+    categories: impl IntoIterator<Item = impl Into<String>>,
+  ) -> Self {
+    self.categories = categories.into_iter().map(Into::into).collect();
+    self
+  }
+
+  /// Adds a band, shaded under the lines between its lower and upper series.
+  pub fn with_band(mut self, band: Band<X, Y>) -> Self {
+    self.bands.push(band);
+    self
+  }
+
+  /// Adds a line colored with the next color of the palette, cycling back to the first once
+  /// every color is used.
+  pub fn with_line_auto(mut self, points: Vec<(X, Y)>) -> Self {
+    let color = palette::cycle(&self.palette, self.lines.len());
+    self.lines.push(Line::new(points, color));
+    self
+  }
+
+  /// Appends a point to the line at `line_index`, as data streams in. Only this line is measured
+  /// again when the chart updates its bounds, rather than every point of every line.
+  ///
+  /// # Panics
+  ///
+  /// Panics if there is no line at `line_index`.
+  pub fn push_point(&mut self, line_index: usize, point: (X, Y)) {
+    let line = &mut self.lines[line_index];
+    line.points.push(point);
+    line.revision = next_revision();
+    self.generation = line.revision;
+  }
+
+  /// Replaces the points of the line at `line_index`, keeping its color and other settings. Like
+  /// `push_point`, only this line is measured again when the chart updates.
+  ///
+  /// # Panics
+  ///
+  /// Panics if there is no line at `line_index`.
+  pub fn set_line_points(&mut self, line_index: usize, points: Vec<(X, Y)>) {
+    let line = &mut self.lines[line_index];
+    line.points = points;
+    line.revision = next_revision();
+    self.generation = line.revision;
+  }
+
+  /// Colors picked in turn by `with_line_auto`. Lines are colored from the colorblind-friendly
+  /// `palette::OKABE_ITO` by default.
+  pub fn with_palette(mut self, palette: Vec<Color>) -> Self {
+    self.palette = palette;
+    self
+  }
+
+  /// Builds one line per column in `y_cols`, all sharing the X values of `x_col`. Columns are
+  /// zero-based, and `colors` are assigned to the lines in order, cycling when there are fewer
+  /// colors than lines. See [`charts::csv`] for the supported format.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `colors` is empty.
+  ///
+  /// [`charts::csv`]: ../csv/index.html
+  pub fn from_csv_reader<R: Read>(
+    reader: R,
+    x_col: usize,
+    y_cols: &[usize],
+    colors: &[Color],
+    has_header: bool,
+  ) -> Result<Self, CsvError>
+  where
+    X: FromStr,
+    Y: FromStr,
+  {
+    assert!(!colors.is_empty(), "at least one line color is required");
+
+    let (xs, columns) = csv::read_columns::<_, X, Y>(reader, x_col, y_cols, has_header)?;
+
+    let lines = columns
+      .into_iter()
+      .zip(colors.iter().cycle())
+      .map(|(ys, color)| Line::new(xs.iter().cloned().zip(ys).collect(), color.clone()))
+      .collect();
+
+    Ok(Self {
+      lines,
+      ..Self::default()
+    })
+  }
+}
+
+// Only requires what the struct itself does, unlike `new` and the builders.
+impl<X, Y> Band<X, Y> {
+  /// A band between `lower` and `upper`, which must share the same X values, filled with a
+  /// translucent `color`.
+  pub fn new(lower: Vec<(X, Y)>, upper: Vec<(X, Y)>, color: Color) -> Self {
+    Self {
+      lower,
+      upper,
+      color,
+      outline: false,
+    }
+  }
+
+  /// Strokes the lower and upper series in the color of the band.
+  pub fn with_outline(mut self, outline: bool) -> Self {
+    self.outline = outline;
+    self
+  }
+}
+
+impl<X, Y> Band<X, Y>
+where
+  X: AsPrimitive<f64>,
+  Y: AsPrimitive<f64>,
+{
+  fn plotted(points: &[(X, Y)]) -> Vec<(f64, f64)> {
+    points.iter().map(|(x, y)| (x.as_(), y.as_())).collect()
+  }
+
+  /// Extremes of both series, `None` without points.
+  fn extent(&self) -> Option<DataBounds> {
+    if self.lower.is_empty() && self.upper.is_empty() {
+      return None;
+    }
+
+    let points = self.lower.iter().chain(self.upper.iter());
+    Some(DataBounds::extent(points.map(|(x, y)| (x.as_(), y.as_()))))
+  }
+
+  /// The lower and upper series as paths, and the region between them.
+  fn paths(&self, geometry: &ChartGeometry) -> (BezPath, BezPath, Option<BezPath>) {
+    let lower = Self::plotted(&self.lower);
+    let (upper_path, region) = line_paths(&Self::plotted(&self.upper), Some(&lower), geometry);
+    let (lower_path, _) = line_paths(&lower, None, geometry);
+
+    (lower_path, upper_path, region)
+  }
+}
+
+impl<X: PartialEq, Y: PartialEq> PartialEq for Band<X, Y> {
+  fn eq(&self, other: &Self) -> bool {
+    self.lower == other.lower
+      && self.upper == other.upper
+      && self.outline == other.outline
+      && self.color.as_rgba_u32() == other.color.as_rgba_u32()
+  }
+}
+
+impl<X: Data, Y: Data> Default for LineChartData<X, Y> {
+  fn default() -> Self {
+    Self {
+      title: None,
+      lines: Vec::new(),
+      bands: Vec::new(),
+      categories: Vec::new(),
+      palette: Vec::new(),
+      generation: 0,
+    }
+  }
+}
+
+impl<X, Y> Data for LineChartData<X, Y>
+where
+  X: AsPrimitive<f64> + Data + PartialEq,
+  Y: AsPrimitive<f64> + Data + PartialEq,
+{
+  fn same(&self, other: &Self) -> bool {
+    if self.title != other.title
+      || self.lines.len() != other.lines.len()
+      || self.bands != other.bands
+      || self.categories != other.categories
+    {
+      return false;
+    }
+
+    // Generations and revisions are unique, so sharing one means sharing the same points
+    if self.generation != 0 && self.generation == other.generation {
+      return true;
+    }
+
+    self
+      .lines
+      .iter()
+      .zip(other.lines.iter())
+      .all(|(line_a, line_b)| {
+        line_a.revision == line_b.revision
+          || line_a.points.len() == line_b.points.len()
+            && line_a
+              .points
+              .iter()
+              .zip(line_b.points.iter())
+              .all(|(point_a, point_b)| point_a == point_b)
+      })
+  }
+}
+
+impl<X, Y> Widget<LineChartData<X, Y>> for LineChart
+where
+  X: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+  Y: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+{
+  fn event(
+    &mut self,
+    ctx: &mut EventCtx,
+    event: &Event,
+    data: &mut LineChartData<X, Y>,
+    _env: &Env,
+  ) {
+    match event {
+      Event::MouseMove(e) => {
+        self.cursor_pos = e.pos;
+        self.report_hover(ctx, data);
+
+        if let Some(brush) = self.brush.as_mut().filter(|brush| !brush.done) {
+          brush.end = e.pos;
+        }
+
+        match &mut self.throttle {
+          // A frame is already on its way for an earlier move
+          Some(throttle) if throttle.pending => (),
+          Some(throttle) => {
+            if throttle.try_paint(Instant::now()) {
+              ctx.request_paint();
+            } else {
+              ctx.request_anim_frame();
+            }
+          }
+          None => ctx.request_paint(),
+        }
+      }
+      Event::MouseDown(e) => {
+        // Any click clears the previous selection
+        if self.brush.take().is_some() {
+          ctx.request_paint();
+        }
+
+        if self.is_interactive() {
+          ctx.request_focus();
+        }
+
+        // The first click of the pair was already handled as a single click
+        if e.count == 2 {
+          let geometry = self.geometry(
+            ctx.size(),
+            &self.bounds,
+            self.secondary_bounds.as_ref(),
+            &data.categories,
+            data.title.is_some(),
+          );
+
+          if geometry.contains(e.pos) {
+            self.double_click(data);
+            ctx.request_paint();
+            return;
+          }
+        }
+
+        if self.on_brush.is_some() && e.mods.shift {
+          let geometry = self.geometry(
+            ctx.size(),
+            &self.bounds,
+            self.secondary_bounds.as_ref(),
+            &data.categories,
+            data.title.is_some(),
+          );
+
+          if geometry.contains(e.pos) {
+            self.brush = Some(Brush {
+              start: e.pos,
+              end: e.pos,
+              done: false,
+            });
+            ctx.set_active(true);
+            return;
+          }
+        }
+
+        let clicked = self
+          .legend_entries
+          .iter()
+          .position(|entry| entry.contains(e.pos));
+
+        if let Some(index) = clicked {
+          if !self.hidden_lines.remove(&index) {
+            self.hidden_lines.insert(index);
+          }
+
+          self.update_reference_data(data);
+          ctx.request_paint();
+          return;
+        }
+
+        let callback = self
+          .on_point_click
+          .as_ref()
+          .and_then(|callback| callback.downcast_ref::<PointCallback<X, Y>>());
+
+        if let Some(callback) = callback {
+          let geometry = self.geometry(
+            ctx.size(),
+            &self.bounds,
+            self.secondary_bounds.as_ref(),
+            &data.categories,
+            data.title.is_some(),
+          );
+          let plotted = self.plotted_values(&data.lines);
+
+          if let Some((line, point)) = clicked_point(&data.lines, &plotted, &geometry, e.pos) {
+            // The arrow keys carry on from the clicked point
+            self.focused_point = Some((line, point));
+            callback(ctx, line, point, &data.lines[line].points[point]);
+          }
+        }
+      }
+      Event::KeyDown(key) if ctx.is_focused() => {
+        let (point_step, line_step) = match key.key_code {
+          KeyCode::ArrowLeft => (-1, 0),
+          KeyCode::ArrowRight => (1, 0),
+          KeyCode::ArrowUp => (0, -1),
+          KeyCode::ArrowDown => (0, 1),
+          KeyCode::Return | KeyCode::NumpadEnter => {
+            let callback = self
+              .on_point_click
+              .as_ref()
+              .and_then(|callback| callback.downcast_ref::<PointCallback<X, Y>>());
+            let focused = self.focused_point.and_then(|(line, point)| {
+              Some((line, point, data.lines.get(line)?.points.get(point)?))
+            });
+
+            if let (Some(callback), Some((line, point, value))) = (callback, focused) {
+              callback(ctx, line, point, value);
+            }
+            ctx.set_handled();
+            return;
+          }
+          _ => return,
+        };
+
+        let plotted = self.plotted_values(&data.lines);
+        let focused = step_focus(
+          &plotted,
+          &self.hidden_lines,
+          self.focused_point,
+          point_step,
+          line_step,
+        );
+        self.focus_point(ctx, data, focused);
+        ctx.set_handled();
+      }
+      Event::MouseUp(e) if ctx.is_active() => {
+        ctx.set_active(false);
+
+        let mut brush = match self.brush.take() {
+          Some(brush) => brush,
+          None => return,
+        };
+        brush.end = e.pos;
+
+        // A Shift click without a drag selects nothing
+        if brush.start == brush.end {
+          ctx.request_paint();
+          return;
+        }
+
+        let geometry = self.geometry(
+          ctx.size(),
+          &self.bounds,
+          self.secondary_bounds.as_ref(),
+          &data.categories,
+          data.title.is_some(),
+        );
+        let (x_range, y_range) = brush_ranges(&geometry, brush.start, brush.end);
+
+        brush.done = true;
+        self.brush = Some(brush);
+        ctx.request_paint();
+
+        if let Some(callback) = &self.on_brush {
+          callback(ctx, x_range, y_range);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn lifecycle(
+    &mut self,
+    ctx: &mut LifeCycleCtx,
+    event: &LifeCycle,
+    data: &LineChartData<X, Y>,
+    _env: &Env,
+  ) {
+    match event {
+      LifeCycle::WidgetAdded => {
+        if self.is_interactive() {
+          ctx.register_for_focus();
+        }
+        self.update_reference_data(data);
+      }
+      LifeCycle::FocusChanged(_) => ctx.request_paint(),
+      LifeCycle::AnimFrame(interval) => {
+        if let Some(transition) = &mut self.transition {
+          transition.elapsed += Duration::from_nanos(*interval);
+
+          if transition.elapsed >= self.settings.animation {
+            self.transition = None;
+          } else {
+            ctx.request_anim_frame();
+          }
+          ctx.request_paint();
+        }
+
+        if let Some(throttle) = self.throttle.as_mut().filter(|throttle| throttle.pending) {
+          if throttle.try_paint(Instant::now()) {
+            ctx.request_paint();
+          } else {
+            ctx.request_anim_frame();
+          }
+        }
+      }
+      _ => (),
+    }
+  }
+
+  fn update(
+    &mut self,
+    ctx: &mut UpdateCtx,
+    old_data: &LineChartData<X, Y>,
+    data: &LineChartData<X, Y>,
+    _env: &Env,
+  ) {
+    if old_data.same(data) {
+      return;
+    }
+
+    if self.settings.animation > Duration::ZERO {
+      // Start from wherever the lines are drawn now, even halfway through another transition
+      self.transition = Some(Transition {
+        from: self.displayed_state(old_data),
+        elapsed: Duration::ZERO,
+      });
+      ctx.request_anim_frame();
+    }
+
+    self.update_reference_data(data);
+    ctx.request_paint();
+  }
+
+  fn layout(
+    &mut self,
+    _layout_ctx: &mut LayoutCtx,
+    bc: &BoxConstraints,
+    _data: &LineChartData<X, Y>,
+    _env: &Env,
+  ) -> Size {
+    chart_size(bc)
+  }
+
+  fn paint(&mut self, ctx: &mut PaintCtx, data: &LineChartData<X, Y>, env: &Env) {
+    let themed = self.settings.theme.as_ref().map(|theme| theme.scoped(env));
+    let env = themed.as_ref().unwrap_or(env);
+    let DisplayedState {
+      bounds,
+      secondary_bounds,
+      plotted,
+    } = self.displayed_state(data);
+    let geometry = self.geometry(
+      ctx.size(),
+      &bounds,
+      secondary_bounds.as_ref(),
+      &data.categories,
+      data.title.is_some(),
+    );
+
+    let cursor = self.crosshair_position(&plotted, &geometry);
+
+    if self.settings.axis.chrome {
+      self
+        .settings
+        .axis
+        .paint_axes(ctx, data.title.as_deref(), &geometry, env);
+      self
+        .settings
+        .axis
+        .paint_cursor_reference(ctx, &geometry, self.cursor_pos, cursor, env);
+    }
+    self.paint_bands(ctx, &data.bands, &geometry);
+    self.paint_lines(ctx, &data.lines, &plotted, &geometry, env);
+    if ctx.is_focused() {
+      self.paint_focused_point(ctx, &data.lines, &plotted, &geometry, env);
+    }
+    self.paint_brush(ctx, &geometry, env);
+    self.paint_footer(ctx, &geometry, env);
+    self.paint_legend(ctx, &data.lines, &geometry, env);
+    self.painted = Some(geometry);
+  }
+}
+
+fn plotted_values<X, Y>(
+  lines: &[Line<X, Y>],
+  stack_mode: StackMode,
+  hidden: &HashSet<usize>,
+) -> Vec<Vec<(f64, f64)>>
+where
+  X: Num + AsPrimitive<f64>,
+  Y: Num + AsPrimitive<f64>,
+{
+  // Lines on each Y axis stack separately
+  let mut offsets: [Vec<f64>; 2] = [Vec::new(), Vec::new()];
+
+  // Totals of each point index, by axis, that percentages are taken of
+  let mut totals: [Vec<f64>; 2] = [Vec::new(), Vec::new()];
+  if stack_mode == StackMode::PercentStacked {
+    for (_, line) in lines
+      .iter()
+      .enumerate()
+      .filter(|(index, _)| !hidden.contains(index))
+    {
+      let totals = &mut totals[line.secondary as usize];
+      for (index, (_, y)) in line.points.iter().enumerate() {
+        if totals.len() <= index {
+          totals.push(0.0);
+        }
+        totals[index] += y.as_();
+      }
+    }
+  }
+
+  lines
+    .iter()
+    .enumerate()
+    .map(|(index, line)| {
+      if hidden.contains(&index) {
+        return Vec::new();
+      }
+
+      let offsets = &mut offsets[line.secondary as usize];
+      let totals = &totals[line.secondary as usize];
+      line
+        .points
+        .iter()
+        .enumerate()
+        .map(|(index, (x, y))| match stack_mode {
+          StackMode::Overlapping => (x.as_(), y.as_()),
+          StackMode::Stacked => {
+            if offsets.len() <= index {
+              offsets.push(0.0);
+            }
+            offsets[index] += y.as_();
+            (x.as_(), offsets[index])
+          }
+          StackMode::PercentStacked => {
+            if offsets.len() <= index {
+              offsets.push(0.0);
+            }
+            // A zero total would divide into NaN, all its lines are drawn at zero instead
+            let total = totals[index];
+            if total != 0.0 {
+              offsets[index] += y.as_() / total * 100.0;
+            }
+            (x.as_(), offsets[index])
+          }
+        })
+        .collect()
+    })
+    .collect()
+}
+
+/// Moves every point `progress` of the way from its position in `from`. Lines whose number of
+/// points changed can't be matched point to point, so they are left at their new positions.
+fn interpolate_plotted(
+  from: &[Vec<(f64, f64)>],
+  to: &[Vec<(f64, f64)>],
+  progress: f64,
+) -> Vec<Vec<(f64, f64)>> {
+  to.iter()
+    .enumerate()
+    .map(|(index, points)| match from.get(index) {
+      Some(previous) if previous.len() == points.len() => previous
+        .iter()
+        .zip(points)
+        .map(|((from_x, from_y), (to_x, to_y))| {
+          (
+            from_x + (to_x - from_x) * progress,
+            from_y + (to_y - from_y) * progress,
+          )
+        })
+        .collect(),
+      _ => points.clone(),
+    })
+    .collect()
+}
+
+/// Multiplies the opacity of `color` by `opacity`.
+fn scale_alpha(color: &Color, opacity: f64) -> Color {
+  let (r, g, b, a) = color.as_rgba8();
+  Color::rgba8(r, g, b, (f64::from(a) * opacity).round() as u8)
+}
+
+/// Widens `bounds` to the extremes of the bands, or takes theirs when there are no bounds yet.
+fn union_bands<X, Y>(bounds: Option<DataBounds>, bands: &[Band<X, Y>]) -> Option<DataBounds>
+where
+  X: AsPrimitive<f64>,
+  Y: AsPrimitive<f64>,
+{
+  bands
+    .iter()
+    .filter_map(Band::extent)
+    .fold(bounds, |bounds, band| match bounds {
+      Some(bounds) => Some(bounds.union(band)),
+      None => Some(band),
+    })
+}
+
+/// The path through `points` and the polygon of the area between them and `below`, or the
+/// baseline of the plot. Both join the points in order, so they assume X-sorted points.
+fn line_paths(
+  points: &[(f64, f64)],
+  below: Option<&[(f64, f64)]>,
+  geometry: &ChartGeometry,
+) -> (BezPath, Option<BezPath>) {
+  let baseline = geometry.to_pixel_y(geometry.min_y);
+  let to_pixel = |(x, y): &(f64, f64)| geometry.data_to_pixel(*x, *y);
+
+  let pixels: Vec<Point> = points.iter().map(to_pixel).collect();
+  let line_path = clipped_polyline(&pixels, geometry.plot);
+
+  let (first, last) = match pixels.as_slice() {
+    [first, .., last] => (*first, *last),
+    _ => return (line_path, None),
+  };
+
+  let polygon: Vec<Point> = match below {
+    Some(below) => pixels
+      .iter()
+      .cloned()
+      .chain(below.iter().rev().map(to_pixel))
+      .collect(),
+    None => std::iter::once(Point::new(first.x, baseline))
+      .chain(pixels.iter().cloned())
+      .chain(std::iter::once(Point::new(last.x, baseline)))
+      .collect(),
+  };
+
+  let mut line_polygon = BezPath::new();
+  for (index, point) in clipped_polygon(&polygon, geometry.plot)
+    .into_iter()
+    .enumerate()
+  {
+    if index == 0 {
+      line_polygon.move_to(point);
+    } else {
+      line_polygon.line_to(point);
+    }
+  }
+  line_polygon.close_path();
+
+  (line_path, Some(line_polygon))
+}
+
+/// Pixel position of the only point of a line, which makes no path to stroke. `None` for lines
+/// of any other length, or when the point is outside the plot.
+fn lone_point(points: &[(f64, f64)], geometry: &ChartGeometry) -> Option<Point> {
+  match points {
+    [(x, y)] => {
+      let point = geometry.data_to_pixel(*x, *y);
+      Some(point).filter(|point| in_plot(geometry.plot, *point))
+    }
+    _ => None,
+  }
+}
+
+/// Pixel positions of plotted points.
+fn to_pixels(points: &[(f64, f64)], geometry: &ChartGeometry) -> Vec<Point> {
+  points
+    .iter()
+    .map(|(x, y)| geometry.data_to_pixel(*x, *y))
+    .collect()
+}
+
+/// Where the label of the point at `index` goes, centered above the point, or below it when its
+/// neighbors are higher on average, so the label sits away from the line. `None` when the label
+/// wouldn't fit inside `plot`.
+fn data_label_rect(pixels: &[Point], index: usize, size: Size, plot: Rect) -> Option<Rect> {
+  let point = *pixels.get(index)?;
+  let neighbors: Vec<f64> = [index.checked_sub(1), Some(index + 1)]
+    .iter()
+    .filter_map(|neighbor| pixels.get((*neighbor)?))
+    .map(|neighbor| neighbor.y)
+    .collect();
+
+  // Pixel Y grows downwards, so higher neighbors have smaller values
+  let below =
+    !neighbors.is_empty() && neighbors.iter().sum::<f64>() / (neighbors.len() as f64) < point.y;
+
+  let top = if below {
+    point.y + DATA_LABEL_OFFSET
+  } else {
+    point.y - DATA_LABEL_OFFSET - size.height
+  };
+  let rect = Rect::from_origin_size((point.x - size.width / 2.0, top), size);
+
+  Some(rect).filter(|rect| {
+    in_plot(plot, Point::new(rect.x0, rect.y0)) && in_plot(plot, Point::new(rect.x1, rect.y1))
+  })
+}
+
+/// Pixel position of a highlighted point, on its line whether or not the line is stroked, and
+/// so on the edge of its area. `None` outside the plot.
+fn highlight_position((x, y): (f64, f64), geometry: &ChartGeometry) -> Option<Point> {
+  Some(geometry.data_to_pixel(x, y)).filter(|point| in_plot(geometry.plot, *point))
+}
+
+/// Whether `point` lies within `plot`, edges included.
+fn in_plot(plot: Rect, point: Point) -> bool {
+  point.x >= plot.x0 && point.x <= plot.x1 && point.y >= plot.y0 && point.y <= plot.y1
+}
+
+/// Path through `points`, keeping only the parts of each segment inside `plot`. A segment leaving
+/// and re-entering the plot starts a new subpath.
+fn clipped_polyline(points: &[Point], plot: Rect) -> BezPath {
+  let mut path = BezPath::new();
+
+  if let [point] = points {
+    if in_plot(plot, *point) {
+      path.move_to(*point);
+    }
+    return path;
+  }
+
+  let mut pen: Option<Point> = None;
+  for segment in points.windows(2) {
+    match clip_segment(segment[0], segment[1], plot) {
+      Some((start, end)) => {
+        if pen != Some(start) {
+          path.move_to(start);
+        }
+        path.line_to(end);
+        pen = Some(end);
+      }
+      None => pen = None,
+    }
+  }
+
+  path
+}
+
+/// The part of the segment from `a` to `b` inside `plot`, using Liang-Barsky clipping.
+fn clip_segment(a: Point, b: Point, plot: Rect) -> Option<(Point, Point)> {
+  let delta = b - a;
+  let mut t0: f64 = 0.0;
+  let mut t1: f64 = 1.0;
+
+  for (p, q) in [
+    (-delta.x, a.x - plot.x0),
+    (delta.x, plot.x1 - a.x),
+    (-delta.y, a.y - plot.y0),
+    (delta.y, plot.y1 - a.y),
+  ]
+  .iter()
+  {
+    if *p == 0.0 {
+      if *q < 0.0 {
+        return None;
+      }
+    } else {
+      let t = q / p;
+      if *p < 0.0 {
+        t0 = t0.max(t);
+      } else {
+        t1 = t1.min(t);
+      }
+    }
+  }
+
+  if t0 > t1 {
+    return None;
+  }
+
+  Some((a + delta * t0, a + delta * t1))
+}
+
+/// Clips a closed polygon to `plot` edge by edge (Sutherland-Hodgman). Parts beyond the plot are
+/// replaced by runs along its border.
+fn clipped_polygon(points: &[Point], plot: Rect) -> Vec<Point> {
+  let mut output = points.to_vec();
+
+  // Left, right, top and bottom edges in turn
+  for edge in 0..4 {
+    let inside = |p: Point| match edge {
+      0 => p.x >= plot.x0,
+      1 => p.x <= plot.x1,
+      2 => p.y >= plot.y0,
+      _ => p.y <= plot.y1,
+    };
+    let crossing = |a: Point, b: Point| match edge {
+      0 | 1 => {
+        let x = if edge == 0 { plot.x0 } else { plot.x1 };
+        Point::new(x, a.y + (b.y - a.y) * (x - a.x) / (b.x - a.x))
+      }
+      _ => {
+        let y = if edge == 2 { plot.y0 } else { plot.y1 };
+        Point::new(a.x + (b.x - a.x) * (y - a.y) / (b.y - a.y), y)
+      }
+    };
+
+    let input = std::mem::take(&mut output);
+    let mut previous = match input.last() {
+      Some(last) => *last,
+      None => break,
+    };
+
+    for point in input {
+      match (inside(previous), inside(point)) {
+        (true, true) => output.push(point),
+        (true, false) => output.push(crossing(previous, point)),
+        (false, true) => {
+          output.push(crossing(previous, point));
+          output.push(point);
+        }
+        (false, false) => (),
+      }
+      previous = point;
+    }
+  }
+
+  output
+}
+
+/// Finds the index of the plotted point horizontally closest to `x`, in pixels. Lines are only
+/// read within `CLICK_TOLERANCE` pixels of their first and last points, so a line shorter than
+/// the others isn't read at its end far past it.
+fn closest_plotted(points: &[(f64, f64)], x: f64, geometry: &ChartGeometry) -> Option<usize> {
+  let target = geometry.to_pixel_x(x);
+  let (index, distance) = points
+    .iter()
+    .map(|(point_x, _)| (geometry.to_pixel_x(*point_x) - target).abs())
+    .enumerate()
+    .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))?;
+
+  let (first, last) = (points.first()?.0, points.last()?.0);
+  let within = (x - first) * (x - last) <= 0.0;
+  if within || distance <= CLICK_TOLERANCE {
+    Some(index)
+  } else {
+    None
+  }
+}
+
+/// The point the arrow keys move the focus to, as `(line index, point index)`: `point_step`
+/// points along the focused line, stopping at its ends, or `line_step` visible lines up or down,
+/// at the point closest to the same X. Without a focused point, the first visible line is entered
+/// from the end the step comes from.
+fn step_focus(
+  plotted: &[Vec<(f64, f64)>],
+  hidden: &HashSet<usize>,
+  focused: Option<(usize, usize)>,
+  point_step: isize,
+  line_step: isize,
+) -> Option<(usize, usize)> {
+  let visible: Vec<usize> = (0..plotted.len())
+    .filter(|index| !hidden.contains(index) && !plotted[*index].is_empty())
+    .collect();
+
+  let (line, point) = match focused {
+    Some((line, point)) if visible.contains(&line) && point < plotted[line].len() => (line, point),
+    _ => {
+      let line = *visible.first()?;
+      let point = if point_step < 0 {
+        plotted[line].len() - 1
+      } else {
+        0
+      };
+      return Some((line, point));
+    }
+  };
+
+  if line_step == 0 {
+    let last = plotted[line].len() as isize - 1;
+    return Some((line, (point as isize + point_step).clamp(0, last) as usize));
+  }
+
+  let position = visible.iter().position(|index| *index == line)? as isize;
+  let target = visible[(position + line_step).clamp(0, visible.len() as isize - 1) as usize];
+  let x = plotted[line][point].0;
+  let closest = plotted[target]
+    .iter()
+    .enumerate()
+    .min_by(|(_, (a, _)), (_, (b, _))| {
+      (a - x)
+        .abs()
+        .partial_cmp(&(b - x).abs())
+        .unwrap_or(Ordering::Equal)
+    })
+    .map(|(index, _)| index)?;
+
+  Some((target, closest))
+}
+
+/// The X and Y data ranges, as `(min, max)`, covered by the rectangle between two pixel
+/// positions, clipped to the plot area.
+fn brush_ranges(geometry: &ChartGeometry, start: Point, end: Point) -> ((f64, f64), (f64, f64)) {
+  let rect = Rect::from_points(start, end).intersect(geometry.plot);
+  let (x0, x1) = (geometry.to_value_x(rect.x0), geometry.to_value_x(rect.x1));
+  let (y0, y1) = (geometry.to_value_y(rect.y0), geometry.to_value_y(rect.y1));
+
+  // Reversed axes swap which edge holds the smaller value
+  ((x0.min(x1), x0.max(x1)), (y0.min(y1), y0.max(y1)))
+}
+
+/// Finds the line and point indices of the plotted point closest to `pos`, if any is within
+/// `CLICK_TOLERANCE` pixels. Each line is checked at the point `closest_plotted` picks for the
+/// click's X, the one the cursor highlights there.
+fn clicked_point<X, Y>(
+  lines: &[Line<X, Y>],
+  plotted: &[Vec<(f64, f64)>],
+  geometry: &ChartGeometry,
+  pos: Point,
+) -> Option<(usize, usize)>
+where
+  X: Num + AsPrimitive<f64>,
+{
+  if !geometry.contains(pos) {
+    return None;
+  }
+
+  let x = geometry.to_value_x(pos.x);
+
+  lines
+    .iter()
+    .enumerate()
+    .filter(|(index, _)| !plotted[*index].is_empty())
+    .filter_map(|(index, line)| {
+      let point_index = closest_plotted(&plotted[index], x, geometry)?;
+      let (plot_x, plot_y) = plotted[index][point_index];
+      let line_geometry = geometry.y_axis_for(line.secondary);
+      let point = line_geometry.data_to_pixel(plot_x, plot_y);
+
+      Some((index, point_index, point.distance(pos)))
+    })
+    .filter(|(_, _, distance)| *distance <= CLICK_TOLERANCE)
+    .min_by(|(_, _, a), (_, _, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+    .map(|(line, point, _)| (line, point))
+}
+
+/// Averages `values` over a sliding `window`, ending at each value. Values before the first full
+/// window are `None` unless `leading` asks for partial windows.
+fn moving_averages(
+  values: &[f64],
+  window: usize,
+  kind: AverageKind,
+  leading: LeadingPoints,
+) -> Vec<Option<f64>> {
+  let window = window.max(1);
+  let mut averages = Vec::with_capacity(values.len());
+
+  match kind {
+    AverageKind::Simple => {
+      let mut sum = 0.0;
+      for (index, value) in values.iter().enumerate() {
+        sum += value;
+        if index >= window {
+          sum -= values[index - window];
+        }
+
+        let count = (index + 1).min(window);
+        averages.push(Some(sum / count as f64));
+      }
+    }
+    AverageKind::Exponential => {
+      let factor = 2.0 / (window as f64 + 1.0);
+      let mut average: Option<f64> = None;
+
+      for (index, value) in values.iter().enumerate() {
+        average = Some(match (average, leading) {
+          (Some(average), _) => average + factor * (value - average),
+          // Without partial windows, start from the simple average of the first full window
+          (None, LeadingPoints::Omit) if index + 1 >= window => {
+            values[..=index].iter().sum::<f64>() / window as f64
+          }
+          (None, LeadingPoints::Omit) => {
+            averages.push(None);
+            continue;
+          }
+          (None, LeadingPoints::Partial) => *value,
+        });
+        averages.push(average);
+      }
+    }
+  }
+
+  if leading == LeadingPoints::Omit {
+    for average in averages.iter_mut().take(window - 1) {
+      *average = None;
+    }
+  }
+
+  averages
+}
+
+fn legend_name<X, Y>(line: &Line<X, Y>, index: usize) -> String {
+  line
+    .name
+    .clone()
+    .unwrap_or_else(|| format!("Series {}", index + 1))
+}
+
+/// Finds the plotted X, across all lines, closest to `x`.
+fn closest_x(plotted: &[Vec<(f64, f64)>], x: f64) -> Option<f64> {
+  plotted
+    .iter()
+    .flatten()
+    .map(|(point_x, _)| *point_x)
+    .min_by(|a, b| {
+      (a - x)
+        .abs()
+        .partial_cmp(&(b - x).abs())
+        .unwrap_or(Ordering::Equal)
+    })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use druid::{
+    kurbo::{PathEl, Shape},
+    Size,
+  };
+  use std::cell::Cell;
+  use std::rc::Rc;
+
+  fn decimals(label: &str) -> usize {
+    label.find('.').map_or(0, |dot| label.len() - dot - 1)
+  }
+
+  #[test]
+  fn test_cursor_readout_matches_axis_precision() {
+    let lines = vec![Line::new(
+      vec![(0.0, 0.123_456), (2.5, 1.987_65), (10.0, 0.5)],
+      Color::WHITE,
+    )];
+    let chart = LineChart::new();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+    let (bounds, _) = chart.data_bounds(&lines, &[], &plotted);
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+
+    let x_ticks: Vec<String> = geometry
+      .visible_x_labels()
+      .map(|v| geometry.format_x(v))
+      .collect();
+    let y_ticks: Vec<String> = geometry
+      .visible_y_labels()
+      .map(|v| geometry.format_y(v))
+      .collect();
+    assert!(!x_ticks.is_empty() && !y_ticks.is_empty());
+
+    let cursor = geometry.plot.center();
+    let x_readout = geometry.format_x(geometry.to_value_x(cursor.x));
+    let y_readout = geometry.format_y(geometry.to_value_y(cursor.y));
+
+    assert!(x_ticks.iter().all(|t| decimals(t) == decimals(&x_readout)));
+    assert!(y_ticks.iter().all(|t| decimals(t) == decimals(&y_readout)));
+    assert!(decimals(&y_readout) < bounds.precision_y);
+  }
+
+  #[test]
+  fn test_forced_decimals() {
+    let lines = vec![Line::new(
+      vec![(0.0, 30.84), (2.5, 31.123_456), (10.0, 29.5)],
+      Color::WHITE,
+    )];
+    let chart = LineChart::new()
+      .with_x_decimals(Some(3))
+      .with_y_decimals(Some(1));
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+    let (bounds, _) = chart.data_bounds(&lines, &[], &plotted);
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+    assert_ne!(bounds.precision_y, 1);
+
+    assert!(geometry.x_labels().iter().all(|l| decimals(&l.text) == 3));
+    assert!(geometry.y_labels().iter().all(|l| decimals(&l.text) == 1));
+    let cursor = geometry.plot.center();
+    assert_eq!(
+      decimals(&geometry.format_x(geometry.to_value_x(cursor.x))),
+      3
+    );
+    assert_eq!(
+      decimals(&geometry.format_y(geometry.to_value_y(cursor.y))),
+      1
+    );
+    assert_eq!(chart.format_value(&lines[0], &31.123_456), "31.1");
+
+    // Without them, the precision comes from the data again
+    let chart = chart.with_x_decimals(None).with_y_decimals(None);
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+    assert!(geometry.x_labels().iter().all(|l| decimals(&l.text) == 0));
+  }
+
+  #[test]
+  fn test_compute_geometry() {
+    let data = LineChartData::new()
+      .with_title("Known")
+      .with_line(Line::new(
+        vec![(0, 10.0), (5, 30.0), (10, 20.0)],
+        Color::WHITE,
+      ));
+    let geometry = LineChart::new()
+      .with_padding(50.0)
+      .compute_geometry(&data, Size::new(600.0, 400.0));
+
+    // 50px of padding on every side, plus the 40px title header
+    assert_eq!(geometry.plot(), Rect::new(50.0, 90.0, 550.0, 350.0));
+
+    assert_eq!(geometry.to_pixel_x(0.0), 50.0);
+    assert_eq!(geometry.to_pixel_x(10.0), 550.0);
+    assert!((geometry.to_value_x(300.0) - 5.0).abs() < 1e-9);
+
+    // The Y range is the data widened by 5% of its span, with every label inside it
+    assert!((geometry.to_value_y(350.0) - 9.0).abs() < 1e-9);
+    assert!((geometry.to_value_y(90.0) - 31.0).abs() < 1e-9);
+    let labels: Vec<f64> = geometry.visible_y_labels().collect();
+    assert_eq!(labels.first(), Some(&9.0));
+    assert_eq!(labels.last(), Some(&30.0));
+
+    // Without chrome, neither padding nor the title header take room from the plot
+    let bare = LineChart::new()
+      .with_padding(50.0)
+      .with_chrome(false)
+      .compute_geometry(&data, Size::new(600.0, 400.0));
+    assert_eq!(bare.plot(), Rect::new(0.0, 0.0, 600.0, 400.0));
+
+    // The margin insets the title and padding along with the plot
+    let margined = LineChart::new()
+      .with_padding(50.0)
+      .with_margin(Insets::new(10.0, 20.0, 30.0, 40.0))
+      .compute_geometry(&data, Size::new(600.0, 400.0));
+    assert_eq!(margined.plot(), Rect::new(60.0, 110.0, 520.0, 310.0));
+    assert_eq!(margined.header(), Rect::new(10.0, 20.0, 570.0, 60.0));
+
+    // Insets beyond the size leave an empty plot instead of an inverted one
+    let cramped = LineChart::new()
+      .with_padding(50.0)
+      .with_margin(500.0)
+      .compute_geometry(&data, Size::new(600.0, 400.0));
+    assert_eq!(cramped.area, Rect::new(300.0, 200.0, 300.0, 200.0));
+    assert_eq!(cramped.plot().area(), 0.0);
+  }
+
+  #[test]
+  fn test_render_svg() {
+    let data = LineChartData::new()
+      .with_title(r#"Sales < "Plan" & more"#)
+      .with_line(Line::new(
+        vec![(0, 1.0), (5, 4.0), (10, 2.0)],
+        Color::rgb8(255, 0, 0),
+      ))
+      .with_line(Line::new(vec![(0, 3.0), (10, 5.0)], Color::rgb8(0, 0, 255)))
+      .with_line(Line::new(vec![(0, 2.0), (10, 2.5)], Color::rgb8(0, 255, 0)));
+    let mut chart = LineChart::new();
+    chart.hidden_lines.insert(2);
+    let size = Size::new(400.0, 300.0);
+    let svg = chart.render_svg(&data, size);
+
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains(r#"width="400" height="300" viewBox="0 0 400 300""#));
+    assert!(svg.trim_end().ends_with("</svg>"));
+
+    // One stroked path per visible line
+    let strokes = |color: &str| {
+      svg
+        .lines()
+        .filter(|element| element.starts_with("<path") && element.contains("fill=\"none\""))
+        .filter(|element| element.contains(&format!("stroke=\"{}\"", color)))
+        .count()
+    };
+    assert_eq!(strokes("rgb(255,0,0)"), 1);
+    assert_eq!(strokes("rgb(0,0,255)"), 1);
+    assert_eq!(strokes("rgb(0,255,0)"), 0);
+
+    let geometry = chart.compute_geometry(&data, size);
+    let labels = geometry.x_labels().into_iter().chain(geometry.y_labels());
+    for label in labels {
+      assert!(
+        svg.contains(&format!(">{}</text>", label.text)),
+        "{}",
+        label.text
+      );
+    }
+
+    assert!(svg.contains(">Sales &lt; &quot;Plan&quot; &amp; more</text>"));
+    assert!(svg.contains(r#"stroke="rgb(191,191,191)""#));
+
+    // The frame, text and gridlines follow the theme of the chart
+    let theme = ChartTheme {
+      axis: Color::rgb8(1, 2, 3),
+      grid: Color::rgb8(4, 5, 6),
+      ..ChartTheme::light()
+    };
+    let svg = LineChart::new().with_theme(theme).render_svg(&data, size);
+    assert!(!svg.contains("rgb(191,191,191)"));
+    assert!(svg.contains(r#"fill="rgb(1,2,3)" fill-opacity="1.000">Sales"#));
+    assert!(svg.contains(r#"stroke="rgb(4,5,6)""#));
+  }
+
+  #[test]
+  fn test_resize_keeps_data_window() {
+    let data = LineChartData::new()
+      .with_line(Line::new(
+        vec![(0, 10.0), (5, 30.0), (10, 20.0)],
+        Color::WHITE,
+      ))
+      .with_line(Line::new(vec![(0, 0.2), (10, 0.7)], Color::BLACK).with_secondary_axis(true));
+    let mut chart = LineChart::new();
+    chart.update_reference_data(&data);
+
+    let small = chart.compute_geometry(&data, Size::new(300.0, 200.0));
+    let large = chart.compute_geometry(&data, Size::new(1200.0, 900.0));
+
+    // Only the pixel mapping follows the size
+    assert!(large.proportion_x > small.proportion_x);
+    assert!(large.proportion_y > small.proportion_y);
+    for (small, large) in [
+      (&small, &large),
+      (small.y_axis_for(true), large.y_axis_for(true)),
+    ] {
+      assert_eq!(
+        (small.min_x, small.max_x, small.min_y, small.max_y),
+        (large.min_x, large.max_x, large.min_y, large.max_y)
+      );
+    }
+  }
+
+  #[test]
+  fn test_bounds_stable_across_paints() {
+    let data =
+      LineChartData::new().with_line(Line::new(vec![(0, 0.37), (1, 4.2), (2, 2.9)], Color::WHITE));
+    let size = Size::new(400.0, 300.0);
+    let mut chart = LineChart::new();
+
+    // Each round goes through an update then a paint, as when the same data is set again
+    let mut round = || {
+      chart.update_reference_data(&data);
+      let state = chart.displayed_state(&data);
+      let geometry = chart.geometry(
+        size,
+        &state.bounds,
+        state.secondary_bounds.as_ref(),
+        &data.categories,
+        false,
+      );
+      let bounds = chart.bounds;
+      (
+        (bounds.min_x, bounds.max_x, bounds.min_y, bounds.max_y),
+        (
+          geometry.min_x,
+          geometry.max_x,
+          geometry.min_y,
+          geometry.max_y,
+        ),
+      )
+    };
+
+    let (bounds, axes) = round();
+    // The axes snap outwards to their labels, leaving the data bounds alone
+    assert!(axes.2 <= bounds.2 && axes.3 >= bounds.3);
+    for _ in 0..5 {
+      assert_eq!(round(), (bounds, axes));
+    }
+  }
+
+  #[test]
+  fn test_stacked_bounds() {
+    let lines = vec![
+      Line::new(vec![(0, 1.0), (1, 4.0), (2, 2.0)], Color::WHITE),
+      Line::new(vec![(0, 3.0), (1, 2.0), (2, 5.0)], Color::BLACK),
+    ];
+
+    let hidden = HashSet::new();
+    let plotted = plotted_values(&lines, StackMode::Stacked, &hidden);
+    assert_eq!(plotted[1], vec![(0.0, 4.0), (1.0, 6.0), (2.0, 7.0)]);
+
+    let overlapping = plotted_values(&lines, StackMode::Overlapping, &hidden);
+    let overlapping = DataBounds::from_lines(&lines, &overlapping, false);
+    let stacked = DataBounds::from_lines(&lines, &plotted, false);
+    assert_eq!(overlapping.max_y, 5.0);
+    assert_eq!(stacked.max_y, 7.0);
+  }
+
+  #[test]
+  fn test_footer_below_axis_labels() {
+    let bounds = DataBounds {
+      min_x: 0.0,
+      max_x: 2.0,
+      min_y: 0.0,
+      max_y: 10.0,
+      precision_y: 0,
+    };
+    let size = Size::new(400.0, 300.0);
+    let plain = LineChart::new().geometry(size, &bounds, None, &[], false);
+
+    let chart = LineChart::new()
+      .with_footer("Source: survey")
+      .with_legend(true);
+    let geometry = chart.geometry(size, &bounds, None, &[], false);
+
+    // The plot shrinks by the footer, keeping the padding the X labels are drawn in
+    let footer = chart.footer_height();
+    assert!(footer > 0.0);
+    assert!((plain.plot.y1 - geometry.plot.y1 - footer - chart.legend_height()).abs() < 1e-9);
+
+    let caption_top = chart.footer_center_y(&geometry) - footer / 2.0;
+    let labels_bottom = geometry.plot.y1 + chart.settings.axis.padding_bottom;
+    assert!((caption_top - labels_bottom).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_axis_labels() {
+    let data = LineChartData::new().with_line(Line::new(
+      vec![(0, 3.0), (50, 42.0), (100, 17.5)],
+      Color::WHITE,
+    ));
+    let (x_labels, y_labels) = LineChart::new().axis_labels(&data, Size::new(800.0, 500.0));
+
+    let values: Vec<f64> = x_labels.iter().map(|label| label.value).collect();
+    assert_eq!(
+      values,
+      (0..=10).map(|i| i as f64 * 10.0).collect::<Vec<_>>()
+    );
+    assert_eq!(x_labels[1].text, "10");
+    assert!(x_labels
+      .windows(2)
+      .all(|pair| pair[0].position < pair[1].position));
+
+    let values: Vec<f64> = y_labels.iter().map(|label| label.value).collect();
+    assert_eq!(values, (1..=14).map(|i| i as f64 * 3.0).collect::<Vec<_>>());
+    assert!(y_labels
+      .windows(2)
+      .all(|pair| pair[0].position > pair[1].position));
+  }
+
+  #[test]
+  fn test_inverted_bounds() {
+    let chart = LineChart::new();
+    let inverted = DataBounds {
+      min_x: 0.0,
+      max_x: 2.0,
+      min_y: 10.0,
+      max_y: 0.0,
+      precision_y: 0,
+    };
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &inverted, None, &[], false);
+    assert_eq!((geometry.min_y, geometry.max_y), (0.0, 10.0));
+
+    // Points land inside the plot, high values at the top
+    let (low, high) = (geometry.to_pixel_y(1.0), geometry.to_pixel_y(9.0));
+    assert!(low <= geometry.plot.y1 && high >= geometry.plot.y0 && high < low);
+
+    let reversed = LineChart::new().with_y_reversed(true);
+    let geometry = reversed.geometry(Size::new(400.0, 300.0), &inverted, None, &[], false);
+    assert!(geometry.to_pixel_y(9.0) > geometry.to_pixel_y(1.0));
+
+    // Negative data is padded outwards too
+    let bounds = DataBounds::from_points(vec![(0.0, -10.0), (1.0, -2.0)].into_iter());
+    assert!(bounds.min_y < -10.0 && bounds.max_y > -2.0);
+  }
+
+  #[test]
+  fn test_y_padding() {
+    let lines = vec![Line::new(vec![(0, -10.0), (1, -2.0)], Color::WHITE)];
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+
+    // A tenth of the span of 8, outwards at both ends
+    let chart = LineChart::new().with_y_padding(0.1);
+    let (bounds, _) = chart.data_bounds(&lines, &[], &plotted);
+    assert!((bounds.min_y + 10.8).abs() < 1e-9);
+    assert!((bounds.max_y + 1.2).abs() < 1e-9);
+
+    let exact = LineChart::new().with_y_padding(0.0);
+    let (bounds, _) = exact.data_bounds(&lines, &[], &plotted);
+    assert_eq!((bounds.min_y, bounds.max_y), (-10.0, -2.0));
+  }
+
+  #[test]
+  fn test_all_zero_values() {
+    let data =
+      LineChartData::new().with_line(Line::new(vec![(0, 0.0), (1, 0.0), (2, 0.0)], Color::WHITE));
+    let chart = LineChart::new().with_y_padding(0.0);
+    let geometry = chart.compute_geometry(&data, Size::new(400.0, 300.0));
+
+    assert_eq!((geometry.min_y, geometry.max_y), (-1.0, 1.0));
+    assert!(geometry.proportion_y.is_finite() && geometry.proportion_y > 0.0);
+    assert!(!geometry.y_labels().is_empty());
+    // The flat line runs through the middle of the plot
+    let y = geometry.to_pixel_y(0.0);
+    assert!((y - geometry.plot.center().y).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_percent_stacked() {
+    let lines = vec![
+      Line::new(vec![(0, 1.0), (1, 0.0), (2, 2.0)], Color::WHITE),
+      Line::new(vec![(0, 3.0), (1, 0.0), (2, 6.0)], Color::BLACK),
+    ];
+
+    let plotted = plotted_values(&lines, StackMode::PercentStacked, &HashSet::new());
+    assert_eq!(plotted[0], vec![(0.0, 25.0), (1.0, 0.0), (2.0, 25.0)]);
+    assert_eq!(plotted[1], vec![(0.0, 100.0), (1.0, 0.0), (2.0, 100.0)]);
+
+    let chart = LineChart::new().with_stack_mode(StackMode::PercentStacked);
+    let (bounds, _) = chart.data_bounds(&lines, &[], &plotted);
+    assert_eq!((bounds.min_y, bounds.max_y), (0.0, 100.0));
+
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+    assert_eq!(geometry.y_axis.first(), Some(&0.0));
+    assert_eq!(geometry.y_axis.last(), Some(&100.0));
+    assert_eq!(geometry.format_y(50.0), "50%");
+  }
+
+  #[test]
+  fn test_hidden_lines_leave_bounds() {
+    let lines = vec![
+      Line::new(vec![(0, 1.0), (1, 4.0)], Color::WHITE),
+      Line::new(vec![(0, 30.0), (1, 20.0)], Color::BLACK),
+      Line::new(vec![(0, 2.0), (1, 3.0)], Color::BLACK),
+    ];
+    let hidden: HashSet<usize> = vec![1].into_iter().collect();
+
+    let plotted = plotted_values(&lines, StackMode::Stacked, &hidden);
+    assert!(plotted[1].is_empty());
+    assert_eq!(plotted[2], vec![(0.0, 3.0), (1.0, 7.0)]);
+
+    let bounds = DataBounds::from_lines(&lines, &plotted, false);
+    assert_eq!(bounds.max_y, 7.0);
+  }
+
+  #[test]
+  fn test_push_point() {
+    let mut data = LineChartData::new()
+      .with_line(Line::new(vec![(0, 1.0), (1, 4.0)], Color::WHITE))
+      .with_line(Line::new(vec![(0, 2.5)], Color::BLACK).with_secondary_axis(true));
+    let mut chart = LineChart::new();
+    chart.update_reference_data(&data);
+
+    let old = data.clone();
+    data.push_point(0, (2, -6.0));
+    data.set_line_points(1, vec![(0, 10.0), (3, 20.0)]);
+    assert!(!old.same(&data));
+    assert!(data.same(&data.clone()));
+
+    // Only the changed lines are measured again, to the same bounds as a full scan
+    chart.update_reference_data(&data);
+    let plotted = chart.plotted_values(&data.lines);
+    let (expected, expected_secondary) = chart.data_bounds(&data.lines, &data.bands, &plotted);
+    let secondary = chart.secondary_bounds.unwrap();
+    let expected_secondary = expected_secondary.unwrap();
+
+    assert_eq!(chart.bounds.min_y, expected.min_y);
+    assert_eq!(chart.bounds.max_y, expected.max_y);
+    assert_eq!(chart.bounds.max_x, 2.0);
+    assert_eq!(secondary.max_x, 3.0);
+    assert_eq!(secondary.min_y, expected_secondary.min_y);
+    assert_eq!(secondary.precision_y, expected_secondary.precision_y);
+  }
+
+  #[test]
+  fn test_same_generation() {
+    let build =
+      || LineChartData::new().with_line(Line::new(vec![(0, 1.0), (1, 4.0)], Color::WHITE));
+
+    // Data built apart has no generation, and is compared point by point
+    let (mut a, b) = (build(), build());
+    assert_eq!(a.generation, 0);
+    assert!(a.same(&b));
+
+    a.push_point(0, (2, 3.0));
+    let copy = a.clone();
+    assert_eq!(copy.generation, a.generation);
+    assert!(copy.same(&a));
+    assert!(!a.same(&b));
+
+    // Setting the same points again is a new generation with equal points
+    let mut c = b.clone();
+    c.set_line_points(0, vec![(0, 1.0), (1, 4.0)]);
+    assert_ne!(c.generation, b.generation);
+    assert!(c.same(&b));
+    assert!(!c.clone().with_title("Title").same(&c));
+  }
+
+  #[test]
+  fn test_line_formatter() {
+    let dollars = Line::new(vec![(0, 2.5), (1, 3.0)], Color::WHITE)
+      .with_formatter(|y: &f64| format!("${:.2}", y));
+    let count = Line::new(vec![(0, 12.0), (1, 30.25)], Color::BLACK);
+    let data = LineChartData::new().with_line(dollars).with_line(count);
+
+    let mut chart = LineChart::new();
+    chart.update_reference_data(&data);
+
+    // Copies of the line share its formatter, the others use the precision of the data
+    let lines = data.lines.clone();
+    assert_eq!(chart.format_value(&lines[0], &2.5), "$2.50");
+    assert_eq!(chart.format_value(&lines[1], &12.0), "12.00");
+  }
+
+  #[test]
+  fn test_clicked_point() {
+    let lines = vec![
+      Line::new(vec![(0, 1.0), (1, 4.0), (2, 2.0)], Color::WHITE),
+      Line::new(vec![(0, 3.0), (1, 2.0), (2, 5.0)], Color::BLACK),
+    ];
+    let chart = LineChart::new();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+    let bounds = DataBounds::from_lines(&lines, &plotted, false);
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+
+    let point = Point::new(geometry.to_pixel_x(1.0), geometry.to_pixel_y(2.0));
+    assert_eq!(
+      clicked_point(
+        &lines,
+        &plotted,
+        &geometry,
+        Point::new(point.x + 3.0, point.y - 2.0)
+      ),
+      Some((1, 1))
+    );
+
+    let between = Point::new(geometry.to_pixel_x(1.0), geometry.to_pixel_y(3.0));
+    assert_eq!(clicked_point(&lines, &plotted, &geometry, between), None);
+
+    // Unevenly spaced lines: the clicked point is the one highlighted under the cursor
+    let lines = vec![
+      Line::new(vec![(0, 1.0), (1, 4.0), (10, 2.0)], Color::WHITE),
+      Line::new(vec![(0, 3.0), (6, 5.0), (10, 4.0)], Color::BLACK),
+    ];
+    let mut chart = LineChart::new();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+    let bounds = DataBounds::from_lines(&lines, &plotted, false);
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+
+    for &(x, y) in &[(1.0, 4.0), (6.0, 5.0), (10.0, 2.0)] {
+      let pos = Point::new(geometry.to_pixel_x(x) - 2.0, geometry.to_pixel_y(y) + 1.0);
+      chart.cursor_pos = pos;
+      let hovered = chart.hovered_points(&lines, &plotted, &geometry);
+      let (line, point) = clicked_point(&lines, &plotted, &geometry, pos).unwrap();
+      assert_eq!(hovered[line], Some(point));
+    }
+  }
+
+  #[test]
+  fn test_double_click_reset() {
+    let data = LineChartData::new()
+      .with_line(Line::new(vec![(0, 1.0), (1, 2.0)], Color::WHITE))
+      .with_line(Line::new(vec![(0, 10.0), (1, 20.0)], Color::BLACK));
+    let mut chart = LineChart::new();
+    chart.hidden_lines.insert(1);
+    chart.update_reference_data(&data);
+    chart.brush = Some(Brush {
+      start: Point::new(50.0, 50.0),
+      end: Point::new(100.0, 100.0),
+      done: true,
+    });
+    chart.bounds = DataBounds::default();
+
+    // By default the view goes back to fitting the visible lines, leaving the hidden ones hidden
+    chart.double_click(&data);
+    assert!(chart.brush.is_none());
+    assert!(chart.hidden_lines.contains(&1));
+    assert!(chart.bounds.max_y >= 2.0 && chart.bounds.max_y < 10.0);
+
+    // A custom action replaces the reset
+    let clicks = Rc::new(Cell::new(0));
+    let counter = clicks.clone();
+    let mut chart = LineChart::new().with_on_double_click(move || counter.set(counter.get() + 1));
+    chart.hidden_lines.insert(1);
+    chart.double_click(&data);
+    assert_eq!(clicks.get(), 1);
+    assert!(chart.hidden_lines.contains(&1));
+  }
+
+  #[test]
+  fn test_band() {
+    let band = Band::new(
+      vec![(0, 1.0), (1, 2.0), (2, 1.5)],
+      vec![(0, 4.0), (1, 9.0), (2, 5.0)],
+      Color::WHITE,
+    );
+    let data = LineChartData::new()
+      .with_line(Line::new(vec![(0, 2.0), (1, 3.0), (2, 3.0)], Color::WHITE))
+      .with_band(band.clone());
+
+    // The band reaches above the line, and the axis follows it
+    let mut chart = LineChart::new();
+    chart.update_reference_data(&data);
+    assert!(chart.bounds.max_y >= 9.0);
+    assert!(chart.bounds.min_y <= 1.0);
+    assert!(!data.same(&data.clone().with_band(band.clone())));
+
+    let geometry = chart.compute_geometry(&data, Size::new(400.0, 300.0));
+    assert!(geometry.max_y >= 9.0);
+    let (_, upper, region) = band.paths(&geometry);
+    let region = region.unwrap();
+    let top = Point::new(geometry.to_pixel_x(1.0), geometry.to_pixel_y(9.0));
+    assert!(upper.bounding_box().contains(top));
+    // Between the series, not down to the axis
+    assert!(region.winding(Point::new(top.x, geometry.to_pixel_y(5.0))) != 0);
+    assert_eq!(
+      region.winding(Point::new(top.x, geometry.to_pixel_y(1.0))),
+      0
+    );
+  }
+
+  #[test]
+  fn test_area_fill() {
+    let line = Line::new(vec![(0, 1.0), (1, 3.0)], Color::rgb8(0, 0, 0xFF));
+    let chart = LineChart::new().with_y_reversed(true);
+    let unit = Rect::new(0.0, 0.0, 1.0, 1.0);
+    let (unit_top, unit_bottom) = (Point::new(0.5, 0.0), Point::new(0.5, 1.0));
+
+    // The default fades away from the line, which a reversed Y axis puts at the bottom
+    if let AreaFill::Gradient { direction, stops } = AreaFill::default() {
+      let (start, end, stops) = chart.area_gradient(&line, direction, &stops, 1.0);
+      assert_eq!(
+        (start.resolve(unit), end.resolve(unit)),
+        (unit_bottom, unit_top)
+      );
+      assert_eq!(stops[0].1.as_rgba_u32() & 0xFF, 0x80);
+      assert_eq!(stops[1].1.as_rgba_u32() & 0xFF, 0);
+    }
+
+    let chart = chart.with_x_reversed(true);
+    let (start, end, _) = chart.area_gradient(&line, FillDirection::Horizontal, &[], 1.0);
+    assert_eq!(
+      (start.resolve(unit), end.resolve(unit)),
+      (Point::new(1.0, 0.5), Point::new(0.0, 0.5))
+    );
+
+    let gradient = AreaFill::Gradient {
+      direction: FillDirection::Horizontal,
+      stops: vec![(0.0, 0.2), (0.5, 0.6), (1.0, 0.2)],
+    };
+    let data = LineChartData::new().with_line(line.clone().with_area_fill(gradient));
+    let svg = LineChart::new().render_svg(&data, Size::new(400.0, 300.0));
+    assert!(svg.contains(r#"x1="0" y1="0.5" x2="1" y2="0.5""#));
+    assert!(svg.contains(r#"<stop offset="0.5" stop-color="rgb(0,0,255)" stop-opacity="0.600"/>"#));
+
+    let data = LineChartData::new().with_line(line.clone().with_area_fill(AreaFill::Solid(0.3)));
+    let svg = LineChart::new().render_svg(&data, Size::new(400.0, 300.0));
+    assert!(!svg.contains("linearGradient"));
+    assert!(svg.contains(r#"stroke="rgb(0,0,255)""#));
+
+    // Only the area is left without the stroke
+    let data = LineChartData::new().with_line(line.with_stroke(false));
+    let svg = LineChart::new().render_svg(&data, Size::new(400.0, 300.0));
+    assert!(svg.contains("linearGradient"));
+    assert!(!svg.contains(r#"stroke="rgb(0,0,255)""#));
+  }
+
+  #[test]
+  fn test_clean_points() {
+    let line = Line::new(
+      vec![(3, 1.0), (1, 2.0), (2, 3.0), (1, 4.0), (3, 5.0)],
+      Color::WHITE,
+    );
+
+    let sorted = line.sorted_by_x();
+    assert_eq!(
+      sorted.points,
+      vec![(1, 2.0), (1, 4.0), (2, 3.0), (3, 1.0), (3, 5.0)]
+    );
+    assert_ne!(sorted.revision, line.revision);
+    assert_eq!(sorted.dedup_x().points, vec![(1, 2.0), (2, 3.0), (3, 1.0)]);
+    // Only consecutive duplicates are dropped without sorting
+    assert_eq!(line.dedup_x().points.len(), 5);
+  }
+
+  #[test]
+  fn test_repaint_throttle() {
+    let mut throttle = Throttle {
+      interval: Duration::from_millis(16),
+      last_paint: None,
+      pending: false,
+    };
+    let start = Instant::now();
+
+    assert!(throttle.try_paint(start));
+    assert!(!throttle.try_paint(start + Duration::from_millis(5)));
+    assert!(throttle.pending);
+    // The frame past the interval paints the moves held back
+    assert!(throttle.try_paint(start + Duration::from_millis(17)));
+    assert!(!throttle.pending);
+  }
+
+  #[test]
+  fn test_step_focus() {
+    let plotted = vec![
+      vec![(0.0, 1.0), (1.0, 2.0), (2.0, 3.0)],
+      vec![],
+      vec![(0.5, 1.0), (1.8, 2.0)],
+    ];
+    let mut hidden = HashSet::new();
+
+    assert_eq!(step_focus(&plotted, &hidden, None, 1, 0), Some((0, 0)));
+    assert_eq!(step_focus(&plotted, &hidden, None, -1, 0), Some((0, 2)));
+    assert_eq!(
+      step_focus(&plotted, &hidden, Some((0, 2)), 1, 0),
+      Some((0, 2))
+    );
+    assert_eq!(
+      step_focus(&plotted, &hidden, Some((0, 1)), -1, 0),
+      Some((0, 0))
+    );
+
+    // Empty lines are skipped, landing on the point closest to the same X
+    assert_eq!(
+      step_focus(&plotted, &hidden, Some((0, 2)), 0, 1),
+      Some((2, 1))
+    );
+    assert_eq!(
+      step_focus(&plotted, &hidden, Some((2, 0)), 0, -1),
+      Some((0, 0))
+    );
+    assert_eq!(
+      step_focus(&plotted, &hidden, Some((2, 0)), 0, 1),
+      Some((2, 0))
+    );
+
+    // A point on a line hidden since starts over
+    hidden.insert(0);
+    assert_eq!(
+      step_focus(&plotted, &hidden, Some((0, 1)), 1, 0),
+      Some((2, 0))
+    );
+    hidden.insert(2);
+    assert_eq!(step_focus(&plotted, &hidden, Some((2, 0)), 1, 0), None);
+  }
+
+  #[test]
+  fn test_brush_ranges() {
+    let lines = vec![Line::new(vec![(0, 0.0), (10, 8.0)], Color::WHITE)];
+    let chart = LineChart::new();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+    let bounds = DataBounds::from_lines(&lines, &plotted, false);
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+
+    // Dragged up and to the left, the ranges still come out in order
+    let start = Point::new(geometry.to_pixel_x(6.0), geometry.to_pixel_y(2.0));
+    let end = Point::new(geometry.to_pixel_x(2.0), geometry.to_pixel_y(5.0));
+    let ((x0, x1), (y0, y1)) = brush_ranges(&geometry, start, end);
+    assert!((x0 - 2.0).abs() < 1e-9 && (x1 - 6.0).abs() < 1e-9);
+    assert!((y0 - 2.0).abs() < 1e-9 && (y1 - 5.0).abs() < 1e-9);
+
+    // Past the plot area, the ranges stop at its edges
+    let ((x0, x1), _) = brush_ranges(&geometry, Point::ZERO, end);
+    assert!((x0 - geometry.to_value_x(geometry.plot.x0)).abs() < 1e-9);
+    assert!((x1 - 2.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_hovered_points() {
+    let lines = vec![
+      Line::new(vec![(0, 1.0), (1, 4.0), (2, 2.0)], Color::WHITE),
+      Line::new(vec![(0, 3.0), (2, 5.0)], Color::BLACK),
+    ];
+    let mut chart = LineChart::new();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+    let bounds = DataBounds::from_lines(&lines, &plotted, false);
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+
+    chart.cursor_pos = Point::new(geometry.to_pixel_x(0.8), geometry.to_pixel_y(3.0));
+    assert_eq!(
+      chart.hovered_points(&lines, &plotted, &geometry),
+      vec![Some(1), Some(0)]
+    );
+
+    chart.hidden_lines.insert(0);
+    assert_eq!(
+      chart.hovered_points(&lines, &plotted, &geometry),
+      vec![None, Some(0)]
+    );
+
+    chart.cursor_pos = Point::new(-1.0, -1.0);
+    assert_eq!(
+      chart.hovered_points(&lines, &plotted, &geometry),
+      vec![None, None]
+    );
+
+    // Within the tolerance of the point of the second line, far above the first one
+    let mut chart = LineChart::new().with_hover_tolerance(10.0);
+    let near = Point::new(
+      geometry.to_pixel_x(2.0) - 3.0,
+      geometry.to_pixel_y(5.0) + 6.0,
+    );
+    chart.cursor_pos = near;
+    assert_eq!(
+      chart.hovered_points(&lines, &plotted, &geometry),
+      vec![None, Some(1)]
+    );
+
+    chart.cursor_pos = Point::new(near.x, near.y + 20.0);
+    assert_eq!(
+      chart.hovered_points(&lines, &plotted, &geometry),
+      vec![None, None]
+    );
+  }
+
+  #[test]
+  fn test_hover_without_stroke() {
+    let lines =
+      vec![Line::new(vec![(0, 1.0), (1, 4.0), (2, 2.0)], Color::WHITE).with_stroke(false)];
+    let mut chart = LineChart::new();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+    let bounds = DataBounds::from_lines(&lines, &plotted, false);
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+
+    chart.cursor_pos = Point::new(geometry.to_pixel_x(1.1), geometry.to_pixel_y(2.0));
+    let hovered = chart.hovered_points(&lines, &plotted, &geometry);
+    assert_eq!(hovered, vec![Some(1)]);
+
+    // Highlighted at the value of the point, a corner of the filled area
+    let position = highlight_position(plotted[0][1], &geometry).unwrap();
+    assert_eq!(position.y, geometry.to_pixel_y(4.0));
+    let (_, area) = line_paths(&plotted[0], None, &geometry);
+    assert!(area
+      .unwrap()
+      .elements()
+      .iter()
+      .any(|element| match element {
+        PathEl::LineTo(point) | PathEl::MoveTo(point) => (*point - position).hypot() < 1e-9,
+        _ => false,
+      }));
+  }
+
+  #[test]
+  fn test_hover_with_different_sampling() {
+    let lines = vec![
+      Line::new(
+        vec![(0.0, 1.0), (1.0, 2.0), (2.0, 3.0), (3.0, 4.0)],
+        Color::WHITE,
+      ),
+      Line::new(vec![(0.5, 5.0), (2.5, 6.0)], Color::BLACK),
+      Line::new(vec![(0.0, 7.0), (0.25, 8.0)], Color::BLACK),
+    ];
+    let mut chart = LineChart::new();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+    let bounds = DataBounds::from_lines(&lines, &plotted, false);
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+
+    chart.cursor_pos = Point::new(geometry.to_pixel_x(2.2), geometry.to_pixel_y(3.0));
+    // The last line ends well before the cursor and isn't read
+    assert_eq!(
+      chart.hovered_points(&lines, &plotted, &geometry),
+      vec![Some(2), Some(1), None]
+    );
+
+    // Unified reads every line at the data X closest to the cursor, here 2.0
+    chart.settings.tooltip_mode = TooltipMode::Unified;
+    assert_eq!(
+      chart.hovered_points(&lines, &plotted, &geometry),
+      vec![Some(2), Some(1), None]
+    );
+
+    // At X 0.0, the second line hasn't started yet
+    chart.cursor_pos = Point::new(geometry.to_pixel_x(0.1), geometry.to_pixel_y(3.0));
+    assert_eq!(
+      chart.hovered_points(&lines, &plotted, &geometry),
+      vec![Some(0), None, Some(0)]
+    );
+  }
+
+  #[test]
+  fn test_moving_average() {
+    let line = Line::new(
+      vec![(0, 2.0), (1, 4.0), (2, 6.0), (3, 8.0), (4, 4.0)],
+      Color::WHITE,
+    );
+
+    let simple = line.moving_average(3, AverageKind::Simple, LeadingPoints::Omit, Color::BLACK);
+    assert_eq!(simple.points, vec![(2, 4.0), (3, 6.0), (4, 6.0)]);
+
+    let partial = line.moving_average(3, AverageKind::Simple, LeadingPoints::Partial, Color::BLACK);
+    assert_eq!(
+      partial.points,
+      vec![(0, 2.0), (1, 3.0), (2, 4.0), (3, 6.0), (4, 6.0)]
+    );
+
+    // Seeded with the first full window, then weighted by 2 / (3 + 1)
+    let exponential = line.moving_average(
+      3,
+      AverageKind::Exponential,
+      LeadingPoints::Omit,
+      Color::BLACK,
+    );
+    assert_eq!(exponential.points, vec![(2, 4.0), (3, 6.0), (4, 5.0)]);
+  }
+
+  #[test]
+  fn test_interpolate_plotted() {
+    let from = vec![vec![(0.0, 0.0), (1.0, 10.0)], vec![(0.0, 5.0)]];
+    let to = vec![
+      vec![(0.0, 4.0), (2.0, 20.0)],
+      vec![(0.0, 1.0), (1.0, 2.0)],
+      vec![(0.0, 3.0)],
+    ];
+
+    let halfway = interpolate_plotted(&from, &to, 0.5);
+    assert_eq!(halfway[0], vec![(0.0, 2.0), (1.5, 15.0)]);
+    // Lines that gained points, or are new, are drawn at their target
+    assert_eq!(halfway[1], to[1]);
+    assert_eq!(halfway[2], to[2]);
+
+    assert_eq!(interpolate_plotted(&from, &to, 1.0), to);
+  }
+
+  #[test]
+  fn test_points_land_on_plot_geometry() {
+    let lines = vec![Line::new(
+      vec![(0.0, 0.0), (5.0, 50.0), (10.0, 100.0)],
+      Color::WHITE,
+    )];
+    let mut chart = LineChart::new();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+    let bounds = DataBounds::from_lines(&lines, &plotted, false);
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+    let plot = geometry.plot;
+
+    // Worked out from the plot rect and the Y range alone, not through the pixel conversions
+    let expected = Point::new(
+      plot.x0 + (5.0 - geometry.min_x) / (geometry.max_x - geometry.min_x) * plot.width(),
+      plot.y1 - (50.0 - geometry.min_y) / (geometry.max_y - geometry.min_y) * plot.height(),
+    );
+    let close = |point: Point| (point - expected).hypot() < 1e-6;
+
+    let (line_path, _) = line_paths(&plotted[0], None, &geometry);
+    assert!(line_path.elements().iter().any(|element| match element {
+      PathEl::LineTo(point) => close(*point),
+      _ => false,
+    }));
+
+    // The highlighted point and its value box are placed from the same geometry
+    chart.cursor_pos = expected;
+    let hovered = chart.hovered_points(&lines, &plotted, &geometry);
+    assert_eq!(hovered, vec![Some(1)]);
+    let (x, y) = plotted[0][1];
+    assert!(close(Point::new(
+      geometry.to_pixel_x(x),
+      geometry.to_pixel_y(y)
+    )));
+  }
+
+  #[test]
+  fn test_paths_clipped_to_plot() {
+    let chart = LineChart::new();
+    let bounds = DataBounds {
+      min_x: 0.0,
+      max_x: 2.0,
+      min_y: 0.0,
+      max_y: 10.0,
+      precision_y: 0,
+    };
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+    let top = geometry.plot.y0;
+
+    // The middle point is far above the top of the Y axis
+    let points = vec![(0.0, 5.0), (1.0, 1000.0), (2.0, 5.0)];
+    let (line_path, line_polygon) = line_paths(&points, None, &geometry);
+    let line_polygon = line_polygon.unwrap();
+
+    for path in &[line_path, line_polygon] {
+      let bounds = path.bounding_box();
+      assert!(bounds.y0 >= top - 1e-9, "{:?} above {}", bounds, top);
+      assert!(bounds.x0 >= geometry.plot.x0 - 1e-9 && bounds.x1 <= geometry.plot.x1 + 1e-9);
+    }
+  }
+
+  #[test]
+  fn test_empty_and_single_point_lines() {
+    let lines = vec![
+      Line::new(vec![(0, 2.0), (4, 8.0)], Color::WHITE),
+      Line::new(vec![], Color::BLACK),
+      Line::new(vec![(2, 5.0)], Color::BLACK),
+    ];
+    let chart = LineChart::new();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+
+    // Only the points that exist count towards the bounds
+    let bounds = DataBounds::from_lines(&lines, &plotted, false);
+    let alone = DataBounds::from_lines(&lines[..1], &plotted[..1], false);
+    assert_eq!(
+      (bounds.min_x, bounds.max_x, bounds.min_y, bounds.max_y),
+      (alone.min_x, alone.max_x, alone.min_y, alone.max_y)
+    );
+
+    let geometry = chart.geometry(Size::new(400.0, 300.0), &bounds, None, &[], false);
+
+    let (line_path, line_polygon) = line_paths(&plotted[1], None, &geometry);
+    assert!(line_path.elements().is_empty());
+    assert!(line_polygon.is_none());
+    assert_eq!(lone_point(&plotted[1], &geometry), None);
+
+    // A single point is a dot, with no area below it
+    let (_, line_polygon) = line_paths(&plotted[2], None, &geometry);
+    assert!(line_polygon.is_none());
+    assert_eq!(
+      lone_point(&plotted[2], &geometry),
+      Some(Point::new(
+        geometry.to_pixel_x(2.0),
+        geometry.to_pixel_y(5.0)
+      ))
+    );
+    assert_eq!(lone_point(&plotted[0], &geometry), None);
+  }
+
+  #[test]
+  fn test_data_label_placement() {
+    let plot = Rect::new(0.0, 0.0, 100.0, 100.0);
+    let size = Size::new(20.0, 10.0);
+    let pixels = vec![
+      Point::new(10.0, 50.0),
+      Point::new(30.0, 20.0),
+      Point::new(50.0, 80.0),
+      Point::new(70.0, 50.0),
+      Point::new(95.0, 50.0),
+    ];
+
+    // Above a peak, below a valley
+    let peak = data_label_rect(&pixels, 1, size, plot).unwrap();
+    assert_eq!(peak.y1, 20.0 - DATA_LABEL_OFFSET);
+    assert_eq!(peak.center().x, 30.0);
+    let valley = data_label_rect(&pixels, 2, size, plot).unwrap();
+    assert_eq!(valley.y0, 80.0 + DATA_LABEL_OFFSET);
+
+    // Labels crossing the edge of the plot are left out
+    assert_eq!(data_label_rect(&pixels, 4, size, plot), None);
+    assert_eq!(data_label_rect(&pixels, 5, size, plot), None);
+  }
+
+  #[test]
+  fn test_secondary_axis_scale() {
+    let lines = vec![
+      Line::new(vec![(0, 10.0), (1, 12.0), (2, 11.0)], Color::WHITE),
+      Line::new(vec![(0, 5000.0), (1, 9000.0), (2, 7000.0)], Color::BLACK)
+        .with_secondary_axis(true),
+    ];
+    let chart = LineChart::new();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &HashSet::new());
+
+    let bounds = DataBounds::from_lines(&lines, &plotted, false);
+    let secondary = DataBounds::secondary_from_lines(&lines, &plotted).unwrap();
+    assert!(bounds.max_y < 20.0);
+    assert!(secondary.min_y > 1000.0);
+
+    let geometry = chart.geometry(
+      Size::new(400.0, 300.0),
+      &bounds,
+      Some(&secondary),
+      &[],
+      false,
+    );
+    let secondary_geometry = geometry.y_axis_for(true);
+    assert_eq!(geometry.plot, secondary_geometry.plot);
+
+    // Both lines span most of the plot height on their own scale
+    for (line, points) in lines.iter().zip(&plotted) {
+      let line_geometry = geometry.y_axis_for(line.secondary);
+      let pixels: Vec<f64> = points
+        .iter()
+        .map(|(_, y)| line_geometry.to_pixel_y(*y))
+        .collect();
+      let span = pixels.iter().cloned().fold(f64::MIN, f64::max)
+        - pixels.iter().cloned().fold(f64::MAX, f64::min);
+      assert!(span > geometry.plot.height() / 3.0);
+    }
+
+    let hidden: HashSet<usize> = vec![1].into_iter().collect();
+    let plotted = plotted_values(&lines, StackMode::Overlapping, &hidden);
+    assert!(DataBounds::secondary_from_lines(&lines, &plotted).is_none());
+  }
 }
@@ -12,5 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod axis;
+pub mod bar;
+pub mod candle;
+pub mod csv;
+pub mod histogram;
 pub mod line;
+pub mod palette;
+pub mod pie;
+pub mod radar;
+#[cfg(feature = "png")]
+mod raster;
+pub mod scatter;
+pub mod spark;
+mod svg;
+pub mod theme;
+pub mod time;
 pub mod wilkinson;
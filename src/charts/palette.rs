@@ -0,0 +1,41 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Color palettes for telling series apart.
+
+use druid::Color;
+
+/// The Okabe-Ito palette, whose colors stay distinct for viewers with the common forms of color
+/// blindness. Its black is left out, to stay visible on dark backgrounds. In order: orange, sky
+/// blue, bluish green, yellow, blue, vermillion and reddish purple.
+pub const OKABE_ITO: [Color; 7] = [
+  Color::rgb8(0xE6, 0x9F, 0x00),
+  Color::rgb8(0x56, 0xB4, 0xE9),
+  Color::rgb8(0x00, 0x9E, 0x73),
+  Color::rgb8(0xF0, 0xE4, 0x42),
+  Color::rgb8(0x00, 0x72, 0xB2),
+  Color::rgb8(0xD5, 0x5E, 0x00),
+  Color::rgb8(0xCC, 0x79, 0xA7),
+];
+
+/// Color of the series at `index`, cycling through `palette`, or `OKABE_ITO` when it's empty.
+pub fn cycle(palette: &[Color], index: usize) -> Color {
+  let palette = if palette.is_empty() {
+    &OKABE_ITO[..]
+  } else {
+    palette
+  };
+
+  palette[index % palette.len()].clone()
+}
@@ -0,0 +1,544 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A chart showing the share of each value in a total, as a pie or a donut.
+
+use std::f64::consts::PI;
+
+use druid::piet::{FontBuilder, Text, TextLayout, TextLayoutBuilder};
+use druid::widget::prelude::*;
+use druid::{
+  kurbo::{Arc, BezPath, Circle, Shape, Vec2},
+  theme, Color, Data, Point, Rect,
+};
+use num_traits::AsPrimitive;
+
+use crate::charts::axis;
+use crate::charts::theme as chart_theme;
+
+/// Angle the first slice starts at, straight up.
+const START_ANGLE: f64 = -PI / 2.0;
+
+/// A share of the total, drawn as one slice.
+#[derive(Clone, Debug)]
+pub struct Slice<V> {
+  label: String,
+  value: V,
+  color: Color,
+}
+
+#[derive(Clone, Debug)]
+pub struct PieChartData<V>
+where
+  V: Data,
+{
+  title: Option<String>,
+  slices: Vec<Slice<V>>,
+}
+
+pub struct PieChart {
+  cursor_pos: Point,
+  settings: PieChartSettings,
+}
+
+struct PieChartSettings {
+  font_size: f64,
+  padding: f64,
+  header_height: f64,
+  /// Radius of the hole, as a fraction of the outer radius.
+  inner_radius: f64,
+  show_percentages: bool,
+  /// How far the hovered slice moves out from the center.
+  explode_offset: f64,
+}
+
+/// Where the pie is drawn.
+struct PieGeometry {
+  center: Point,
+  outer_radius: f64,
+  inner_radius: f64,
+  header_height: f64,
+  /// Left edge of the legend.
+  legend_left: f64,
+}
+
+impl PieChart {
+  pub fn new() -> Self {
+    Self {
+      cursor_pos: Point::new(-1.0, -1.0),
+      settings: PieChartSettings {
+        font_size: 12.0,
+        padding: 20.0,
+        header_height: 40.0,
+        inner_radius: 0.0,
+        show_percentages: true,
+        explode_offset: 8.0,
+      },
+    }
+  }
+
+  pub fn with_padding(mut self, padding: f64) -> Self {
+    self.settings.padding = padding;
+    self
+  }
+
+  pub fn with_font_size(mut self, font_size: f64) -> Self {
+    self.settings.font_size = font_size;
+    self
+  }
+
+  /// Cuts a hole in the middle, turning the pie into a donut. The radius is a fraction of the
+  /// outer radius, between 0 for a full pie and 1.
+  pub fn with_inner_radius(mut self, radius: f64) -> Self {
+    self.settings.inner_radius = radius.clamp(0.0, 1.0);
+    self
+  }
+
+  /// Prints the percentage of the total on each slice large enough to hold it.
+  pub fn with_percentages(mut self, show: bool) -> Self {
+    self.settings.show_percentages = show;
+    self
+  }
+
+  /// Places the pie in the space left by the title and a legend `legend_width` wide.
+  fn geometry(&self, size: Size, has_title: bool, legend_width: f64) -> PieGeometry {
+    let settings = &self.settings;
+    let header_height = if has_title {
+      settings.header_height
+    } else {
+      0.0
+    };
+
+    let legend_left = size.width - settings.padding - legend_width;
+    let area = Rect::new(
+      settings.padding,
+      settings.padding + header_height,
+      legend_left - settings.padding,
+      size.height - settings.padding,
+    );
+
+    // Leave room for the hovered slice to move out
+    let outer_radius = (area.width().min(area.height()) / 2.0 - settings.explode_offset).max(0.0);
+
+    PieGeometry {
+      center: area.center(),
+      outer_radius,
+      inner_radius: outer_radius * settings.inner_radius,
+      header_height,
+      legend_left,
+    }
+  }
+
+  fn paint_slices<V>(
+    &self,
+    ctx: &mut PaintCtx,
+    slices: &[Slice<V>],
+    geometry: &PieGeometry,
+    env: &Env,
+  ) where
+    V: AsPrimitive<f64>,
+  {
+    let angles = slice_angles(slices);
+
+    // Nothing to share, only outline where the pie would be
+    if angles.iter().all(|(_, sweep)| *sweep == 0.0) {
+      ctx.stroke(
+        Circle::new(geometry.center, geometry.outer_radius),
+        &chart_theme::axis_color(env).with_alpha(0.3),
+        1.0,
+      );
+      return;
+    }
+
+    let hovered = slice_at(
+      &angles,
+      geometry.center,
+      geometry.inner_radius,
+      geometry.outer_radius,
+      self.cursor_pos,
+    );
+
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), self.settings.font_size)
+      .build()
+      .unwrap();
+
+    for (index, (slice, (start, sweep))) in slices.iter().zip(angles.iter()).enumerate() {
+      if *sweep == 0.0 {
+        continue;
+      }
+
+      let middle = start + sweep / 2.0;
+      let center = if hovered == Some(index) {
+        geometry.center + Vec2::from_angle(middle) * self.settings.explode_offset
+      } else {
+        geometry.center
+      };
+
+      let path = slice_path(
+        center,
+        geometry.inner_radius,
+        geometry.outer_radius,
+        *start,
+        *sweep,
+      );
+      ctx.fill(path.clone(), &slice.color);
+      ctx.stroke(path, &chart_theme::outline_color(env), 1.0);
+
+      // Percentages only go on slices wide enough for them
+      if self.settings.show_percentages && *sweep > 0.25 {
+        let layout = ctx
+          .text()
+          .new_text_layout(
+            &label_font,
+            &format!("{:.0}%", sweep / (2.0 * PI) * 100.0),
+            f64::INFINITY,
+          )
+          .build()
+          .unwrap();
+
+        let radius = if geometry.inner_radius > 0.0 {
+          (geometry.inner_radius + geometry.outer_radius) / 2.0
+        } else {
+          geometry.outer_radius * 0.65
+        };
+        let position = center + Vec2::from_angle(middle) * radius;
+
+        ctx.draw_text(
+          &layout,
+          (
+            position.x - layout.width() / 2.0,
+            position.y + self.settings.font_size * 0.334,
+          ),
+          &chart_theme::outline_color(env),
+        );
+      }
+    }
+  }
+
+  /// Lists a swatch and the label of every slice, top to bottom, right of the pie.
+  fn paint_legend<V>(
+    &self,
+    ctx: &mut PaintCtx,
+    slices: &[Slice<V>],
+    geometry: &PieGeometry,
+    env: &Env,
+  ) {
+    let font_size = self.settings.font_size;
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+
+    let row_height = font_size * 1.8;
+    let swatch_size = font_size * 0.8;
+    let mut center_y = geometry.center.y - row_height * (slices.len() as f64 - 1.0) / 2.0;
+
+    for slice in slices.iter() {
+      let layout = ctx
+        .text()
+        .new_text_layout(&label_font, &slice.label, f64::INFINITY)
+        .build()
+        .unwrap();
+
+      let swatch = Rect::from_origin_size(
+        (geometry.legend_left, center_y - swatch_size / 2.0),
+        (swatch_size, swatch_size),
+      );
+      ctx.fill(swatch, &slice.color);
+
+      ctx.draw_text(
+        &layout,
+        (
+          geometry.legend_left + swatch_size + 5.0,
+          center_y + font_size * 0.334,
+        ),
+        &chart_theme::axis_color(env),
+      );
+
+      center_y += row_height;
+    }
+  }
+}
+
+impl Default for PieChart {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<V> Slice<V> {
+  pub fn new(label: impl Into<String>, value: V, color: Color) -> Self {
+    Self {
+      label: label.into(),
+      value,
+      color,
+    }
+  }
+}
+
+impl<V> PieChartData<V>
+where
+  V: Data + AsPrimitive<f64>,
+{
+  pub fn new() -> Self {
+    Self {
+      title: None,
+      slices: Vec::new(),
+    }
+  }
+
+  pub fn with_title(mut self, title: impl Into<String>) -> Self {
+    self.title = Some(title.into());
+    self
+  }
+
+  /// Adds a slice after the previous ones, clockwise. Negative values count as zero.
+  pub fn with_slice(mut self, slice: Slice<V>) -> Self {
+    self.slices.push(slice);
+    self
+  }
+}
+
+impl<V> Default for PieChartData<V>
+where
+  V: Data + AsPrimitive<f64>,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<V> Data for PieChartData<V>
+where
+  V: Data + PartialEq,
+{
+  fn same(&self, other: &Self) -> bool {
+    self.title == other.title
+      && self.slices.len() == other.slices.len()
+      && self.slices.iter().zip(other.slices.iter()).all(|(a, b)| {
+        a.label == b.label && a.value == b.value && a.color.as_rgba_u32() == b.color.as_rgba_u32()
+      })
+  }
+}
+
+impl<V> Widget<PieChartData<V>> for PieChart
+where
+  V: Data + AsPrimitive<f64> + PartialEq,
+{
+  fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut PieChartData<V>, _env: &Env) {
+    if let Event::MouseMove(e) = event {
+      self.cursor_pos = e.pos;
+      ctx.request_paint();
+    }
+  }
+
+  fn lifecycle(
+    &mut self,
+    _ctx: &mut LifeCycleCtx,
+    _event: &LifeCycle,
+    _data: &PieChartData<V>,
+    _env: &Env,
+  ) {
+  }
+
+  fn update(
+    &mut self,
+    ctx: &mut UpdateCtx,
+    old_data: &PieChartData<V>,
+    data: &PieChartData<V>,
+    _env: &Env,
+  ) {
+    if !old_data.same(data) {
+      ctx.request_paint();
+    }
+  }
+
+  fn layout(
+    &mut self,
+    _layout_ctx: &mut LayoutCtx,
+    bc: &BoxConstraints,
+    _data: &PieChartData<V>,
+    _env: &Env,
+  ) -> Size {
+    axis::chart_size(bc)
+  }
+
+  fn paint(&mut self, ctx: &mut PaintCtx, data: &PieChartData<V>, env: &Env) {
+    let font_size = self.settings.font_size;
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+
+    let legend_width = data
+      .slices
+      .iter()
+      .map(|slice| {
+        ctx
+          .text()
+          .new_text_layout(&label_font, &slice.label, f64::INFINITY)
+          .build()
+          .unwrap()
+          .width()
+      })
+      .fold(0.0, f64::max)
+      + font_size * 0.8
+      + 5.0;
+
+    let geometry = self.geometry(ctx.size(), data.title.is_some(), legend_width);
+
+    if let Some(title) = &data.title {
+      let header = Rect::new(0.0, 0.0, ctx.size().width, geometry.header_height);
+      axis::paint_title(ctx, title, header, env);
+    }
+    self.paint_slices(ctx, &data.slices, &geometry, env);
+    self.paint_legend(ctx, &data.slices, &geometry, env);
+  }
+}
+
+/// Start angle and sweep of every slice, clockwise from the top. All sweeps are zero when the
+/// values don't add up to anything.
+fn slice_angles<V: AsPrimitive<f64>>(slices: &[Slice<V>]) -> Vec<(f64, f64)> {
+  let values: Vec<f64> = slices
+    .iter()
+    .map(|slice| slice.value.as_().max(0.0))
+    .collect();
+  let total: f64 = values.iter().sum();
+
+  let mut start = START_ANGLE;
+  values
+    .iter()
+    .map(|value| {
+      let sweep = if total > 0.0 {
+        value / total * 2.0 * PI
+      } else {
+        0.0
+      };
+      let angles = (start, sweep);
+      start += sweep;
+      angles
+    })
+    .collect()
+}
+
+/// Outline of a slice, or of a ring segment when `inner` isn't zero. A slice sweeping all the way
+/// round is a whole disc or ring, without the spoke its edges would otherwise leave.
+fn slice_path(center: Point, inner: f64, outer: f64, start: f64, sweep: f64) -> BezPath {
+  let arc = |radius: f64, start_angle: f64, sweep_angle: f64| Arc {
+    center,
+    radii: Vec2::new(radius, radius),
+    start_angle,
+    sweep_angle,
+    x_rotation: 0.0,
+  };
+
+  if sweep >= 2.0 * PI {
+    let mut path = Circle::new(center, outer).into_bez_path(0.1);
+    if inner > 0.0 {
+      // Wound against the outer circle, so the hole isn't filled
+      path.move_to(center + Vec2::from_angle(start) * inner);
+      path.extend(arc(inner, start, -2.0 * PI).append_iter(0.1));
+      path.close_path();
+    }
+    return path;
+  }
+
+  let mut path = BezPath::new();
+  path.move_to(center + Vec2::from_angle(start) * outer);
+  path.extend(arc(outer, start, sweep).append_iter(0.1));
+
+  if inner > 0.0 {
+    path.line_to(center + Vec2::from_angle(start + sweep) * inner);
+    path.extend(arc(inner, start + sweep, -sweep).append_iter(0.1));
+  } else {
+    path.line_to(center);
+  }
+
+  path.close_path();
+  path
+}
+
+/// Finds the slice under `point`, between the `inner` and `outer` radii around `center`.
+fn slice_at(
+  angles: &[(f64, f64)],
+  center: Point,
+  inner: f64,
+  outer: f64,
+  point: Point,
+) -> Option<usize> {
+  let offset = point - center;
+  let distance = offset.hypot();
+  if distance < inner || distance > outer {
+    return None;
+  }
+
+  // Measured clockwise from the top, like the slices
+  let angle = (offset.atan2() - START_ANGLE).rem_euclid(2.0 * PI);
+
+  angles.iter().position(|(start, sweep)| {
+    let from = start - START_ANGLE;
+    angle >= from && angle < from + sweep
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use druid::kurbo::PathEl;
+
+  #[test]
+  fn test_slice_angles() {
+    let slices = vec![
+      Slice::new("A", 1.0, Color::WHITE),
+      Slice::new("B", 3.0, Color::BLACK),
+    ];
+    let angles = slice_angles(&slices);
+    assert_eq!(angles[0], (START_ANGLE, PI / 2.0));
+    assert_eq!(angles[1], (START_ANGLE + PI / 2.0, PI * 1.5));
+
+    let center = Point::new(100.0, 100.0);
+    // Right of the center is a quarter turn clockwise from the top
+    let right = Point::new(150.0, 99.0);
+    assert_eq!(slice_at(&angles, center, 0.0, 80.0, right), Some(0));
+    let left = Point::new(50.0, 100.0);
+    assert_eq!(slice_at(&angles, center, 0.0, 80.0, left), Some(1));
+    // Inside the hole of a donut
+    assert_eq!(slice_at(&angles, center, 60.0, 80.0, left), None);
+
+    // A single slice takes the whole circle
+    let single = slice_angles(&[Slice::new("A", 5, Color::WHITE)]);
+    assert_eq!(single, vec![(START_ANGLE, 2.0 * PI)]);
+    assert_eq!(slice_at(&single, center, 0.0, 80.0, left), Some(0));
+    // Drawn as a disc or a ring, without a spoke out to the start
+    let disc = slice_path(center, 0.0, 80.0, START_ANGLE, 2.0 * PI);
+    assert!(disc.iter().all(|el| !matches!(el, PathEl::LineTo(_))));
+    assert_ne!(disc.winding(center), 0);
+    let ring = slice_path(center, 60.0, 80.0, START_ANGLE, 2.0 * PI);
+    assert!(ring.iter().all(|el| !matches!(el, PathEl::LineTo(_))));
+    assert_eq!(ring.winding(center), 0);
+    assert_ne!(ring.winding(Point::new(30.0, 100.0)), 0);
+
+    // Nothing to share
+    let zero = slice_angles(&[
+      Slice::new("A", 0.0, Color::WHITE),
+      Slice::new("B", 0.0, Color::BLACK),
+    ]);
+    assert!(zero.iter().all(|(_, sweep)| *sweep == 0.0));
+    assert_eq!(slice_at(&zero, center, 0.0, 80.0, left), None);
+  }
+}
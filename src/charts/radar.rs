@@ -0,0 +1,631 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A chart comparing series across several metrics, each on its own radial axis.
+
+use std::f64::consts::PI;
+use std::fmt::Display;
+
+use druid::piet::{FontBuilder, Text, TextLayout, TextLayoutBuilder};
+use druid::widget::prelude::*;
+use druid::{
+  kurbo::{BezPath, Circle, Line, Vec2},
+  theme, Color, Data, Point, Rect,
+};
+use num_traits::AsPrimitive;
+
+use crate::charts::axis;
+use crate::charts::theme as chart_theme;
+use crate::charts::wilkinson;
+
+/// Angle of the first axis, straight up.
+const START_ANGLE: f64 = -PI / 2.0;
+
+/// Fewest axes a radar can be drawn with.
+const MIN_AXES: usize = 3;
+
+/// How close to a vertex, in pixels, the cursor shows its value.
+const HOVER_RADIUS: f64 = 6.0;
+
+/// Number of gridline rings when every axis has its own range.
+const NORMALIZED_RINGS: usize = 4;
+
+/// How values are placed along the axes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RadarScale {
+  /// Every axis uses the same range, labeled along the first axis.
+  Shared,
+  /// Each axis spans its own range, from zero to its largest value, so metrics of different
+  /// magnitudes can be compared. The rings aren't labeled.
+  PerAxis,
+}
+
+/// The values of one entity, one per axis, drawn as a closed polygon.
+#[derive(Clone, Debug)]
+pub struct RadarSeries<V> {
+  name: String,
+  values: Vec<V>,
+  color: Color,
+}
+
+#[derive(Clone, Debug)]
+pub struct RadarChartData<V>
+where
+  V: Data,
+{
+  title: Option<String>,
+  axes: Vec<String>,
+  series: Vec<RadarSeries<V>>,
+}
+
+pub struct RadarChart {
+  cursor_pos: Point,
+  settings: RadarChartSettings,
+}
+
+struct RadarChartSettings {
+  font_size: f64,
+  padding: f64,
+  header_height: f64,
+  scale: RadarScale,
+  /// Opacity of the polygon fills, on top of the series colors.
+  fill_alpha: f64,
+}
+
+/// Where the radar is drawn and how its axes are scaled.
+struct RadarGeometry {
+  center: Point,
+  radius: f64,
+  header_height: f64,
+  /// Values at the center and at the end of every axis.
+  ranges: Vec<(f64, f64)>,
+  /// Values of the rings, in the shared scale, or fractions of the radius with one per axis.
+  rings: Vec<f64>,
+}
+
+impl RadarChart {
+  pub fn new() -> Self {
+    Self {
+      cursor_pos: Point::new(-1.0, -1.0),
+      settings: RadarChartSettings {
+        font_size: 12.0,
+        padding: 20.0,
+        header_height: 40.0,
+        scale: RadarScale::Shared,
+        fill_alpha: 0.2,
+      },
+    }
+  }
+
+  pub fn with_padding(mut self, padding: f64) -> Self {
+    self.settings.padding = padding;
+    self
+  }
+
+  pub fn with_font_size(mut self, font_size: f64) -> Self {
+    self.settings.font_size = font_size;
+    self
+  }
+
+  pub fn with_scale(mut self, scale: RadarScale) -> Self {
+    self.settings.scale = scale;
+    self
+  }
+
+  /// Opacity of the area inside each series, 0 to leave only the outlines.
+  pub fn with_fill_opacity(mut self, alpha: f64) -> Self {
+    self.settings.fill_alpha = alpha.clamp(0.0, 1.0);
+    self
+  }
+
+  /// Places the radar in the space left by the title, with room around it for the axis names.
+  fn geometry<V>(&self, size: Size, data: &RadarChartData<V>) -> RadarGeometry
+  where
+    V: Data + AsPrimitive<f64>,
+  {
+    let settings = &self.settings;
+    let header_height = if data.title.is_some() {
+      settings.header_height
+    } else {
+      0.0
+    };
+
+    let area = Rect::new(
+      settings.padding,
+      settings.padding + header_height,
+      size.width - settings.padding,
+      size.height - settings.padding,
+    );
+    let name_room = settings.font_size * 2.0;
+    let radius = (area.width().min(area.height()) / 2.0 - name_room).max(0.0);
+
+    let (ranges, rings) = axis_ranges(&data.series, data.axes.len(), settings.scale);
+
+    RadarGeometry {
+      center: area.center(),
+      radius,
+      header_height,
+      ranges,
+      rings,
+    }
+  }
+
+  fn paint_grid<V>(
+    &self,
+    ctx: &mut PaintCtx,
+    data: &RadarChartData<V>,
+    geometry: &RadarGeometry,
+    env: &Env,
+  ) where
+    V: Data,
+  {
+    let count = data.axes.len();
+    let color = chart_theme::axis_color(env);
+    let grid_color = chart_theme::grid_color(env).with_alpha(0.3);
+    let font_size = self.settings.font_size;
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+
+    for ring in geometry.rings.iter() {
+      let fraction = match self.settings.scale {
+        RadarScale::Shared => geometry.fraction(0, *ring),
+        RadarScale::PerAxis => *ring,
+      };
+
+      let mut path = BezPath::new();
+      for axis in 0..count {
+        let point = vertex(geometry.center, geometry.radius * fraction, axis, count);
+        if axis == 0 {
+          path.move_to(point);
+        } else {
+          path.line_to(point);
+        }
+      }
+      path.close_path();
+      ctx.stroke(path, &grid_color, 1.0);
+
+      // Shared rings are labeled along the first axis, pointing up
+      if self.settings.scale == RadarScale::Shared {
+        let layout = ctx
+          .text()
+          .new_text_layout(&label_font, &format_value(*ring), f64::INFINITY)
+          .build()
+          .unwrap();
+        let point = vertex(geometry.center, geometry.radius * fraction, 0, count);
+        ctx.draw_text(
+          &layout,
+          (point.x + 4.0, point.y + font_size * 0.334),
+          &color,
+        );
+      }
+    }
+
+    for (axis, name) in data.axes.iter().enumerate() {
+      let end = vertex(geometry.center, geometry.radius, axis, count);
+      ctx.stroke(Line::new(geometry.center, end), &grid_color, 1.0);
+
+      let layout = ctx
+        .text()
+        .new_text_layout(&label_font, name, f64::INFINITY)
+        .build()
+        .unwrap();
+
+      // Names sit past the end of their axis, on the side away from the center
+      let direction = Vec2::from_angle(axis_angle(axis, count));
+      let anchor = end + direction * (font_size * 0.8);
+      let x = if direction.x > 0.1 {
+        anchor.x
+      } else if direction.x < -0.1 {
+        anchor.x - layout.width()
+      } else {
+        anchor.x - layout.width() / 2.0
+      };
+      let y = anchor.y + font_size * 0.334 + direction.y * font_size * 0.3;
+
+      ctx.draw_text(&layout, (x, y), &color);
+    }
+  }
+
+  fn paint_series<V>(
+    &self,
+    ctx: &mut PaintCtx,
+    data: &RadarChartData<V>,
+    geometry: &RadarGeometry,
+    env: &Env,
+  ) where
+    V: Data + AsPrimitive<f64> + Display,
+  {
+    let vertices = series_vertices(&data.series, geometry);
+
+    for (series, points) in data.series.iter().zip(vertices.iter()) {
+      let mut path = BezPath::new();
+      for (index, point) in points.iter().enumerate() {
+        if index == 0 {
+          path.move_to(*point);
+        } else {
+          path.line_to(*point);
+        }
+      }
+      path.close_path();
+
+      ctx.fill(
+        path.clone(),
+        &series.color.clone().with_alpha(self.settings.fill_alpha),
+      );
+      ctx.stroke(path, &series.color, 2.0);
+    }
+
+    let (series_index, axis) = match vertex_at(&vertices, self.cursor_pos) {
+      Some(hovered) => hovered,
+      None => return,
+    };
+    let series = &data.series[series_index];
+    let point = vertices[series_index][axis];
+
+    let marker = Circle::new(point, 4.0);
+    ctx.fill(marker, &series.color);
+    ctx.stroke(marker, &chart_theme::outline_color(env), 2.0);
+
+    let font_size = self.settings.font_size;
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+    let layout = ctx
+      .text()
+      .new_text_layout(
+        &label_font,
+        &vertex_label(&data.axes[axis], series.values.get(axis)),
+        f64::INFINITY,
+      )
+      .build()
+      .unwrap();
+
+    let padding = 5.0;
+    let size = Size::new(layout.width() + padding * 2.0, font_size + padding * 2.0);
+    let left = if point.x + 8.0 + size.width < ctx.size().width {
+      point.x + 8.0
+    } else {
+      point.x - 8.0 - size.width
+    };
+    let rect = Rect::from_origin_size((left, point.y - size.height / 2.0), size);
+
+    ctx.fill(rect, &chart_theme::tooltip_bg(env));
+    ctx.draw_text(
+      &layout,
+      (left + padding, point.y + font_size * 0.334),
+      &chart_theme::tooltip_fg(env),
+    );
+  }
+}
+
+impl Default for RadarChart {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl RadarGeometry {
+  /// Distance of `value` from the center along `axis`, as a fraction of the radius.
+  fn fraction(&self, axis: usize, value: f64) -> f64 {
+    let (low, high) = self.ranges.get(axis).cloned().unwrap_or((0.0, 1.0));
+    if high > low {
+      ((value - low) / (high - low)).max(0.0)
+    } else {
+      0.0
+    }
+  }
+}
+
+impl<V> RadarSeries<V> {
+  /// Values are matched to the axes in order. Missing values count as the start of their axis,
+  /// and extra ones are ignored.
+  pub fn new(name: impl Into<String>, values: Vec<V>, color: Color) -> Self {
+    Self {
+      name: name.into(),
+      values,
+      color,
+    }
+  }
+}
+
+impl<V> RadarChartData<V>
+where
+  V: Data + AsPrimitive<f64>,
+{
+  /// Creates a chart with one axis per name, clockwise from the top. Nothing but the title is
+  /// drawn with fewer than three axes.
+  pub fn new(axes: Vec<String>) -> Self {
+    Self {
+      title: None,
+      axes,
+      series: Vec::new(),
+    }
+  }
+
+  pub fn with_title(mut self, title: impl Into<String>) -> Self {
+    self.title = Some(title.into());
+    self
+  }
+
+  pub fn with_series(mut self, series: RadarSeries<V>) -> Self {
+    self.series.push(series);
+    self
+  }
+}
+
+impl<V> Data for RadarChartData<V>
+where
+  V: Data + PartialEq,
+{
+  fn same(&self, other: &Self) -> bool {
+    self.title == other.title
+      && self.axes == other.axes
+      && self.series.len() == other.series.len()
+      && self.series.iter().zip(other.series.iter()).all(|(a, b)| {
+        a.name == b.name && a.values == b.values && a.color.as_rgba_u32() == b.color.as_rgba_u32()
+      })
+  }
+}
+
+impl<V> Widget<RadarChartData<V>> for RadarChart
+where
+  V: Data + AsPrimitive<f64> + PartialEq + Display,
+{
+  fn event(
+    &mut self,
+    ctx: &mut EventCtx,
+    event: &Event,
+    _data: &mut RadarChartData<V>,
+    _env: &Env,
+  ) {
+    if let Event::MouseMove(e) = event {
+      self.cursor_pos = e.pos;
+      ctx.request_paint();
+    }
+  }
+
+  fn lifecycle(
+    &mut self,
+    _ctx: &mut LifeCycleCtx,
+    _event: &LifeCycle,
+    _data: &RadarChartData<V>,
+    _env: &Env,
+  ) {
+  }
+
+  fn update(
+    &mut self,
+    ctx: &mut UpdateCtx,
+    old_data: &RadarChartData<V>,
+    data: &RadarChartData<V>,
+    _env: &Env,
+  ) {
+    if !old_data.same(data) {
+      ctx.request_paint();
+    }
+  }
+
+  fn layout(
+    &mut self,
+    _layout_ctx: &mut LayoutCtx,
+    bc: &BoxConstraints,
+    _data: &RadarChartData<V>,
+    _env: &Env,
+  ) -> Size {
+    axis::chart_size(bc)
+  }
+
+  fn paint(&mut self, ctx: &mut PaintCtx, data: &RadarChartData<V>, env: &Env) {
+    let geometry = self.geometry(ctx.size(), data);
+
+    if let Some(title) = &data.title {
+      let header = Rect::new(0.0, 0.0, ctx.size().width, geometry.header_height);
+      axis::paint_title(ctx, title, header, env);
+    }
+
+    if data.axes.len() < MIN_AXES {
+      return;
+    }
+
+    self.paint_grid(ctx, data, &geometry, env);
+    self.paint_series(ctx, data, &geometry, env);
+  }
+}
+
+/// Angle of an axis, clockwise from the top.
+fn axis_angle(axis: usize, count: usize) -> f64 {
+  START_ANGLE + axis as f64 * 2.0 * PI / count as f64
+}
+
+/// Point `distance` away from `center` along an axis.
+fn vertex(center: Point, distance: f64, axis: usize, count: usize) -> Point {
+  center + Vec2::from_angle(axis_angle(axis, count)) * distance
+}
+
+/// Range of every axis and the values of the gridline rings. Ranges start at zero, or lower
+/// for negative values, and end on a nice number. With `RadarScale::PerAxis`, the rings are
+/// fractions of the radius instead of values.
+fn axis_ranges<V>(
+  series: &[RadarSeries<V>],
+  count: usize,
+  scale: RadarScale,
+) -> (Vec<(f64, f64)>, Vec<f64>)
+where
+  V: AsPrimitive<f64>,
+{
+  let values_on = |axis: usize| {
+    series
+      .iter()
+      .filter_map(move |series| series.values.get(axis))
+      .map(|value| value.as_())
+      .filter(|value| value.is_finite())
+  };
+  let nice_range = |low: f64, high: f64| {
+    let ticks = wilkinson::nice_ticks(low, high, 5);
+    let first = ticks.first().cloned().unwrap_or(low);
+    let last = ticks.last().cloned().unwrap_or(high);
+    (first, last, ticks)
+  };
+
+  match scale {
+    RadarScale::Shared => {
+      let (low, high) = (0..count)
+        .flat_map(values_on)
+        .fold((0.0, f64::NEG_INFINITY), |(low, high), value| {
+          (f64::min(low, value), f64::max(high, value))
+        });
+      let high = if high > low { high } else { low + 1.0 };
+
+      let (first, last, ticks) = nice_range(low, high);
+      let rings = ticks.into_iter().filter(|tick| *tick > first).collect();
+      (vec![(first, last); count], rings)
+    }
+    RadarScale::PerAxis => {
+      let ranges = (0..count)
+        .map(|axis| {
+          let (low, high) = values_on(axis).fold((0.0, f64::NEG_INFINITY), |(low, high), value| {
+            (f64::min(low, value), f64::max(high, value))
+          });
+          let high = if high > low { high } else { low + 1.0 };
+          let (first, last, _) = nice_range(low, high);
+          (first, last)
+        })
+        .collect();
+      let rings = (1..=NORMALIZED_RINGS)
+        .map(|ring| ring as f64 / NORMALIZED_RINGS as f64)
+        .collect();
+      (ranges, rings)
+    }
+  }
+}
+
+/// Position of every value of every series, one vertex per axis.
+fn series_vertices<V>(series: &[RadarSeries<V>], geometry: &RadarGeometry) -> Vec<Vec<Point>>
+where
+  V: AsPrimitive<f64>,
+{
+  let count = geometry.ranges.len();
+
+  series
+    .iter()
+    .map(|series| {
+      (0..count)
+        .map(|axis| {
+          let value = series
+            .values
+            .get(axis)
+            .map(|value| value.as_())
+            .filter(|value| value.is_finite())
+            .unwrap_or(geometry.ranges[axis].0);
+          let fraction = geometry.fraction(axis, value);
+          vertex(geometry.center, geometry.radius * fraction, axis, count)
+        })
+        .collect()
+    })
+    .collect()
+}
+
+/// Finds the series and axis of the vertex within `HOVER_RADIUS` of `point`, preferring the last
+/// series drawn, which is on top.
+fn vertex_at(vertices: &[Vec<Point>], point: Point) -> Option<(usize, usize)> {
+  vertices
+    .iter()
+    .enumerate()
+    .rev()
+    .find_map(|(series, points)| {
+      points
+        .iter()
+        .position(|vertex| (*vertex - point).hypot() <= HOVER_RADIUS)
+        .map(|axis| (series, axis))
+    })
+}
+
+/// Text of the tooltip of a vertex. Series shorter than the axes still have a vertex, at the
+/// start of the axes they lack a value for, shown without one.
+fn vertex_label<V: Display>(axis: &str, value: Option<&V>) -> String {
+  match value {
+    Some(value) => format!("{}: {}", axis, value),
+    None => format!("{}: n/a", axis),
+  }
+}
+
+/// Formats a ring value without trailing zeros.
+fn format_value(value: f64) -> String {
+  format!("{:.prec$}", value, prec = axis::get_precision(value).min(4))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_axis_ranges() {
+    let series = vec![
+      RadarSeries::new("A", vec![10.0, 0.5, 300.0], Color::WHITE),
+      RadarSeries::new("B", vec![40.0, 0.8, 100.0], Color::BLACK),
+    ];
+
+    let (ranges, rings) = axis_ranges(&series, 3, RadarScale::Shared);
+    assert_eq!(ranges, vec![(0.0, 300.0); 3]);
+    assert_eq!(rings.last(), Some(&300.0));
+    assert!(!rings.contains(&0.0));
+
+    // Each axis ends on a nice number just past its largest value
+    let (ranges, rings) = axis_ranges(&series, 3, RadarScale::PerAxis);
+    assert_eq!(ranges, vec![(0.0, 40.0), (0.0, 0.8), (0.0, 300.0)]);
+    assert_eq!(rings, vec![0.25, 0.5, 0.75, 1.0]);
+
+    let geometry = RadarGeometry {
+      center: Point::new(100.0, 100.0),
+      radius: 80.0,
+      header_height: 0.0,
+      ranges,
+      rings,
+    };
+    let vertices = series_vertices(&series, &geometry);
+    // B reaches the end of the first axis, straight up
+    let top = vertices[1][0];
+    assert!((top - Point::new(100.0, 20.0)).hypot() < 1e-9);
+    assert_eq!(vertex_at(&vertices, Point::new(102.0, 22.0)), Some((1, 0)));
+    assert_eq!(vertex_at(&vertices, Point::new(150.0, 150.0)), None);
+  }
+
+  #[test]
+  fn test_short_series() {
+    let axes = ["Speed", "Range", "Cost"];
+    let series = vec![RadarSeries::new("A", vec![30.0], Color::WHITE)];
+    let geometry = RadarGeometry {
+      center: Point::new(100.0, 100.0),
+      radius: 80.0,
+      header_height: 0.0,
+      ranges: vec![(0.0, 40.0); 3],
+      rings: vec![0.25, 0.5, 0.75, 1.0],
+    };
+
+    // The missing values sit at the center, where they can still be hovered
+    let vertices = series_vertices(&series, &geometry);
+    assert_eq!(vertices[0].len(), axes.len());
+    let (index, axis) = vertex_at(&vertices, Point::new(100.0, 100.0)).unwrap();
+    assert_eq!((index, axis), (0, 1));
+
+    let values = &series[index].values;
+    assert_eq!(vertex_label(axes[axis], values.get(axis)), "Range: n/a");
+    assert_eq!(vertex_label(axes[0], values.first()), "Speed: 30");
+  }
+}
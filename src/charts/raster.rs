@@ -0,0 +1,155 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rasterizes chart exports to PNG with piet's bitmap backend, without opening a window, so
+//! charts can be embedded in emails or reports.
+
+use druid::kurbo::{Affine, Circle};
+use druid::piet::{
+  Device, FontBuilder, GradientStop, ImageFormat, Text, TextLayout, TextLayoutBuilder,
+};
+use druid::{LinearGradient, RenderContext};
+
+use crate::charts::svg::{Element, SvgDocument, TextAnchor};
+
+/// Paints the elements of `document` onto a bitmap of its size and encodes it as a PNG image.
+///
+/// # Panics
+///
+/// Panics if the bitmap can't be created or rendered, which the software backends only do when
+/// out of memory.
+pub(crate) fn rasterize(document: &SvgDocument) -> Vec<u8> {
+  let size = document.size();
+  let (width, height) = (size.width.ceil() as usize, size.height.ceil() as usize);
+
+  let mut device = Device::new().expect("no bitmap device");
+  let mut target = device
+    .bitmap_target(width, height, 1.0)
+    .expect("bitmap not created");
+
+  {
+    let mut ctx = target.render_context();
+    for element in document.elements() {
+      paint_element(&mut ctx, element);
+    }
+    ctx.finish().expect("bitmap not rendered");
+  }
+
+  let mut pixels = target
+    .into_raw_pixels(ImageFormat::RgbaPremul)
+    .expect("bitmap not read");
+  unpremultiply(&mut pixels);
+
+  let mut output = Vec::new();
+  let mut encoder = png::Encoder::new(&mut output, width as u32, height as u32);
+  encoder.set_color(png::ColorType::RGBA);
+  encoder.set_depth(png::BitDepth::Eight);
+  encoder
+    .write_header()
+    .and_then(|mut writer| writer.write_image_data(&pixels))
+    .expect("PNG not encoded");
+
+  output
+}
+
+fn paint_element(ctx: &mut impl RenderContext, element: &Element) {
+  match element {
+    Element::FillRect(rect, color) => ctx.fill(rect, color),
+    Element::StrokeRect(rect, color, width) => ctx.stroke(rect, color, *width),
+    Element::StrokePath(path, color, width) => ctx.stroke(path, color, *width),
+    Element::FillCircle(center, radius, color) => ctx.fill(Circle::new(*center, *radius), color),
+    Element::FillPath(path, color) => ctx.fill(path, color),
+    Element::FillGradient {
+      path,
+      start,
+      end,
+      stops,
+    } => {
+      let stops: Vec<GradientStop> = stops
+        .iter()
+        .map(|(offset, color)| GradientStop {
+          pos: *offset as f32,
+          color: color.clone(),
+        })
+        .collect();
+      ctx.fill(path, &LinearGradient::new(*start, *end, stops));
+    }
+    Element::Text {
+      text,
+      position,
+      anchor,
+      font_size,
+      color,
+      rotation,
+      dy,
+    } => {
+      // The same generic family as the SVG export
+      let font = match ctx
+        .text()
+        .new_font_by_name("sans-serif", *font_size)
+        .build()
+      {
+        Ok(font) => font,
+        Err(_) => return,
+      };
+      let layout = match ctx
+        .text()
+        .new_text_layout(&font, text, f64::INFINITY)
+        .build()
+      {
+        Ok(layout) => layout,
+        Err(_) => return,
+      };
+
+      let x = match anchor {
+        TextAnchor::Start => 0.0,
+        TextAnchor::Middle => -layout.width() / 2.0,
+        TextAnchor::End => -layout.width(),
+      };
+
+      // Piet rotates clockwise, like the SVG transform
+      let _ = ctx.with_save(|ctx| {
+        ctx.transform(
+          Affine::translate(position.to_vec2()) * Affine::rotate(-rotation.to_radians()),
+        );
+        ctx.draw_text(&layout, (x, *dy), color);
+        Ok(())
+      });
+    }
+  }
+}
+
+/// Converts premultiplied RGBA pixels, as read from the bitmap, to the straight alpha of PNG.
+fn unpremultiply(pixels: &mut [u8]) {
+  for pixel in pixels.chunks_exact_mut(4) {
+    let alpha = u32::from(pixel[3]);
+    if alpha > 0 && alpha < 255 {
+      for channel in pixel.iter_mut().take(3) {
+        *channel = ((u32::from(*channel) * 255 + alpha / 2) / alpha).min(255) as u8;
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_unpremultiply() {
+    let mut pixels = vec![128, 64, 0, 128, 10, 20, 30, 255, 0, 0, 0, 0];
+    unpremultiply(&mut pixels);
+    assert_eq!(pixels, vec![255, 128, 0, 128, 10, 20, 30, 255, 0, 0, 0, 0]);
+  }
+}
@@ -0,0 +1,593 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A chart plotting discrete points as markers, optionally sized by a third value.
+
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+use druid::piet::{FontBuilder, Text, TextLayout, TextLayoutBuilder};
+use druid::widget::prelude::*;
+use druid::{kurbo::Circle, theme, Color, Data, Insets, Point, Rect};
+use num_traits::{AsPrimitive, Num};
+
+use crate::charts::axis::{chart_size, AxisSettings, ChartGeometry, DataBounds};
+#[cfg(feature = "png")]
+use crate::charts::raster;
+use crate::charts::svg::SvgDocument;
+use crate::charts::theme::{self as chart_theme, ChartTheme};
+
+pub use crate::charts::axis::{AxisSides, GridConfig, XAxisSide, YAxisSide};
+
+/// A set of points drawn with the same color, or each colored by its value.
+#[derive(Clone, Debug)]
+pub struct Series<X, Y> {
+  points: Vec<(X, Y)>,
+  sizes: Option<Vec<f64>>,
+  color: Color,
+  color_fn: Option<fn(&Y) -> Color>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ScatterChartData<X, Y>
+where
+  X: Data,
+  Y: Data,
+{
+  title: Option<String>,
+  series: Vec<Series<X, Y>>,
+}
+
+pub struct ScatterChart {
+  cursor_pos: Point,
+  settings: ScatterChartSettings,
+  bounds: DataBounds,
+}
+
+struct ScatterChartSettings {
+  axis: AxisSettings,
+  marker_radius: f64,
+}
+
+impl ScatterChart {
+  pub fn new() -> Self {
+    Self {
+      cursor_pos: Point::new(-1.0, -1.0),
+      settings: ScatterChartSettings {
+        axis: AxisSettings::default(),
+        marker_radius: 4.0,
+      },
+      bounds: DataBounds::default(),
+    }
+  }
+
+  pub fn with_padding(mut self, padding: f64) -> Self {
+    self.settings.axis.set_padding(padding);
+    self
+  }
+
+  pub fn with_padding_top(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_top = padding;
+    self
+  }
+
+  pub fn with_padding_bottom(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_bottom = padding;
+    self
+  }
+
+  pub fn with_padding_left(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_left = padding;
+    self
+  }
+
+  pub fn with_padding_right(mut self, padding: f64) -> Self {
+    self.settings.axis.padding_right = padding;
+    self
+  }
+
+  /// Leaves `margin` empty around everything the chart draws, outside the padding the axis
+  /// labels are drawn in, as when aligning charts in a grid. Takes one width for all sides or an
+  /// `Insets` for each.
+  pub fn with_margin(mut self, margin: impl Into<Insets>) -> Self {
+    self.settings.axis.margin = margin.into();
+    self
+  }
+
+  pub fn with_font_size(mut self, font_size: f64) -> Self {
+    self.settings.axis.font_size = font_size;
+    self
+  }
+
+  /// Radius of the markers of series without per-point sizes.
+  pub fn with_marker_radius(mut self, radius: f64) -> Self {
+    self.settings.marker_radius = radius;
+    self
+  }
+
+  pub fn with_grid(mut self, grid: GridConfig) -> Self {
+    self.settings.axis.grid = grid;
+    self
+  }
+
+  /// Draws `count` fainter gridlines between each pair of major ticks, on the axes whose
+  /// gridlines are shown.
+  pub fn with_minor_gridlines(mut self, count: usize) -> Self {
+    self.settings.axis.minor_gridlines = count;
+    self
+  }
+
+  /// Emphasizes the lines through X = 0 and Y = 0, drawn when the axes cross zero, over the
+  /// gridlines. On by default.
+  pub fn with_zero_line(mut self, show: bool) -> Self {
+    self.settings.axis.zero_line = show;
+    self
+  }
+
+  /// Fills the plot area with `color`, underneath the gridlines and the data.
+  pub fn with_plot_background(mut self, color: Color) -> Self {
+    self.settings.axis.plot_background = Some(color);
+    self
+  }
+
+  /// Flips the X axis so its values decrease from left to right.
+  pub fn with_x_reversed(mut self, reversed: bool) -> Self {
+    self.settings.axis.reversed_x = reversed;
+    self
+  }
+
+  /// Flips the Y axis so its values decrease from bottom to top.
+  pub fn with_y_reversed(mut self, reversed: bool) -> Self {
+    self.settings.axis.reversed_y = reversed;
+    self
+  }
+
+  /// Chooses on which sides of the plot the tick labels are drawn. The padding of a side without
+  /// labels shrinks to a small margin.
+  pub fn with_axis_sides(mut self, sides: AxisSides) -> Self {
+    self.settings.axis.axis_sides = sides;
+    self
+  }
+
+  fn update_reference_data<X, Y>(&mut self, data: &ScatterChartData<X, Y>)
+  where
+    X: Num + Data + AsPrimitive<f64>,
+    Y: Num + Data + AsPrimitive<f64>,
+  {
+    self.bounds = DataBounds::from_points(
+      data
+        .series
+        .iter()
+        .flat_map(|s| s.points.iter())
+        .map(|(x, y)| (x.as_(), y.as_())),
+    );
+  }
+
+  fn paint_markers<X, Y>(
+    &self,
+    ctx: &mut PaintCtx,
+    series: &[Series<X, Y>],
+    geometry: &ChartGeometry,
+    env: &Env,
+  ) where
+    X: Num + AsPrimitive<f64> + Display,
+    Y: Num + AsPrimitive<f64> + Display,
+  {
+    for serie in series.iter() {
+      for index in 0..serie.points.len() {
+        let (center, radius) = marker(serie, index, geometry, self.settings.marker_radius);
+        let circle = Circle::new(center, radius);
+        let color = serie.point_color(index);
+
+        ctx.fill(circle, &color.clone().with_alpha(0.6));
+        ctx.stroke(circle, &color, 1.0);
+      }
+    }
+
+    if !geometry.contains(self.cursor_pos) {
+      return;
+    }
+
+    let (serie_index, point_index) = match hovered_point(
+      series,
+      geometry,
+      self.cursor_pos,
+      self.settings.marker_radius,
+    ) {
+      Some(hovered) => hovered,
+      None => return,
+    };
+
+    let serie = &series[serie_index];
+    let (x, y) = &serie.points[point_index];
+    let (center, radius) = marker(serie, point_index, geometry, self.settings.marker_radius);
+
+    // Emphasize the hovered marker
+    let circle = Circle::new(center, radius);
+    ctx.fill(circle, &serie.point_color(point_index));
+    ctx.stroke(circle, &chart_theme::outline_color(env), 2.0);
+
+    let font_size = self.settings.axis.font_size;
+    let label_font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+
+    let layout = ctx
+      .text()
+      .new_text_layout(&label_font, &format!("({}, {})", x, y), f64::INFINITY)
+      .build()
+      .unwrap();
+
+    let text_height = if let Some(metric) = layout.line_metric(0) {
+      font_size - (metric.cumulative_height - metric.baseline.floor())
+    } else {
+      font_size
+    };
+
+    // Keep the box beside the marker, flipping to the left near the right edge
+    let width = layout.width() + 10.0;
+    let left = if center.x + radius + 4.0 + width < geometry.plot.x1 {
+      center.x + radius + 4.0
+    } else {
+      center.x - radius - 4.0 - width
+    };
+
+    let rect = Rect::from_points(
+      Point::new(left, center.y - 5.0 - text_height / 2.0),
+      Point::new(left + width, center.y + 5.0 + text_height / 2.0),
+    );
+
+    ctx.fill(rect, &chart_theme::tooltip_bg(env));
+
+    ctx.draw_text(
+      &layout,
+      (left + 5.0, center.y + (font_size * 0.334)),
+      &chart_theme::tooltip_fg(env),
+    );
+  }
+
+  /// Renders the chart as a standalone SVG document of the given size.
+  pub fn render_svg<X, Y>(&self, data: &ScatterChartData<X, Y>, size: Size) -> String
+  where
+    X: Data + AsPrimitive<f64> + Num,
+    Y: Data + AsPrimitive<f64> + Num,
+  {
+    self.export(data, size).finish()
+  }
+
+  /// Renders the chart as a PNG image of the given size, laid out as `render_svg`.
+  #[cfg(feature = "png")]
+  pub fn render_png<X, Y>(&self, data: &ScatterChartData<X, Y>, size: Size) -> Vec<u8>
+  where
+    X: Data + AsPrimitive<f64> + Num,
+    Y: Data + AsPrimitive<f64> + Num,
+  {
+    raster::rasterize(&self.export(data, size))
+  }
+
+  /// Draws the chart into a document shared by the SVG and PNG exports.
+  fn export<X, Y>(&self, data: &ScatterChartData<X, Y>, size: Size) -> SvgDocument
+  where
+    X: Data + AsPrimitive<f64> + Num,
+    Y: Data + AsPrimitive<f64> + Num,
+  {
+    let bounds = DataBounds::from_points(
+      data
+        .series
+        .iter()
+        .flat_map(|s| s.points.iter())
+        .map(|(x, y)| (x.as_(), y.as_())),
+    );
+    let geometry = self
+      .settings
+      .axis
+      .geometry(size, &bounds, data.title.is_some());
+    let mut svg = SvgDocument::new(size);
+
+    self.settings.axis.render_svg_axes(
+      &mut svg,
+      data.title.as_deref(),
+      &geometry,
+      &ChartTheme::druid_default(),
+    );
+
+    for serie in data.series.iter() {
+      for index in 0..serie.points.len() {
+        let (center, radius) = marker(serie, index, &geometry, self.settings.marker_radius);
+        svg.fill_circle(center, radius, &serie.point_color(index).with_alpha(0.6));
+      }
+    }
+
+    svg
+  }
+}
+
+impl Default for ScatterChart {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<X, Y> Series<X, Y> {
+  pub fn new(points: Vec<(X, Y)>, color: Color) -> Self {
+    Self {
+      points,
+      sizes: None,
+      color,
+      color_fn: None,
+    }
+  }
+
+  /// Sets the marker radius of each point, in pixels, turning the series into a bubble chart.
+  ///
+  /// Points beyond the end of `sizes` use the chart's marker radius.
+  pub fn with_sizes(mut self, sizes: Vec<f64>) -> Self {
+    self.sizes = Some(sizes);
+    self
+  }
+
+  /// Colors each marker from its Y value instead of the color of the series.
+  pub fn with_color_fn(mut self, color_fn: fn(&Y) -> Color) -> Self {
+    self.color_fn = Some(color_fn);
+    self
+  }
+
+  fn point_color(&self, index: usize) -> Color {
+    match self.color_fn {
+      Some(color_fn) => color_fn(&self.points[index].1),
+      None => self.color.clone(),
+    }
+  }
+}
+
+impl<X, Y> ScatterChartData<X, Y>
+where
+  X: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+  Y: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+{
+  pub fn new() -> Self {
+    Self {
+      title: None,
+      series: Vec::new(),
+    }
+  }
+
+  pub fn with_title(mut self, title: impl Into<String>) -> Self {
+    self.title = Some(title.into());
+    self
+  }
+
+  pub fn with_series(mut self, series: Series<X, Y>) -> Self {
+    self.series.push(series);
+    self
+  }
+}
+
+impl<X, Y> Default for ScatterChartData<X, Y>
+where
+  X: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+  Y: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<X, Y> Data for ScatterChartData<X, Y>
+where
+  X: AsPrimitive<f64> + Data + PartialEq,
+  Y: AsPrimitive<f64> + Data + PartialEq,
+{
+  fn same(&self, other: &Self) -> bool {
+    let basic_test = self.title == other.title && self.series.len() == other.series.len();
+
+    basic_test
+      && self
+        .series
+        .iter()
+        .zip(other.series.iter())
+        .all(|(series_a, series_b)| {
+          series_a.points == series_b.points
+            && series_a.color.as_rgba_u32() == series_b.color.as_rgba_u32()
+            && series_a.sizes == series_b.sizes
+            && series_a.color_fn.map(|f| f as usize) == series_b.color_fn.map(|f| f as usize)
+        })
+  }
+}
+
+impl<X, Y> Widget<ScatterChartData<X, Y>> for ScatterChart
+where
+  X: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+  Y: Display + Data + AsPrimitive<f64> + PartialOrd + Num,
+{
+  fn event(
+    &mut self,
+    ctx: &mut EventCtx,
+    event: &Event,
+    _data: &mut ScatterChartData<X, Y>,
+    _env: &Env,
+  ) {
+    if let Event::MouseMove(e) = event {
+      self.cursor_pos = e.pos;
+      ctx.request_paint();
+    }
+  }
+
+  fn lifecycle(
+    &mut self,
+    _ctx: &mut LifeCycleCtx,
+    event: &LifeCycle,
+    data: &ScatterChartData<X, Y>,
+    _env: &Env,
+  ) {
+    if let LifeCycle::WidgetAdded = event {
+      self.update_reference_data(data);
+    }
+  }
+
+  fn update(
+    &mut self,
+    ctx: &mut UpdateCtx,
+    old_data: &ScatterChartData<X, Y>,
+    data: &ScatterChartData<X, Y>,
+    _env: &Env,
+  ) {
+    if !old_data.same(data) {
+      self.update_reference_data(data);
+      ctx.request_paint();
+    }
+  }
+
+  fn layout(
+    &mut self,
+    _layout_ctx: &mut LayoutCtx,
+    bc: &BoxConstraints,
+    _data: &ScatterChartData<X, Y>,
+    _env: &Env,
+  ) -> Size {
+    chart_size(bc)
+  }
+
+  fn paint(&mut self, ctx: &mut PaintCtx, data: &ScatterChartData<X, Y>, env: &Env) {
+    let geometry = self
+      .settings
+      .axis
+      .geometry(ctx.size(), &self.bounds, data.title.is_some());
+
+    self
+      .settings
+      .axis
+      .paint_axes(ctx, data.title.as_deref(), &geometry, env);
+    self.paint_markers(ctx, &data.series, &geometry, env);
+  }
+}
+
+/// Center and radius of the marker of a point.
+fn marker<X, Y>(
+  series: &Series<X, Y>,
+  index: usize,
+  geometry: &ChartGeometry,
+  default_radius: f64,
+) -> (Point, f64)
+where
+  X: Num + AsPrimitive<f64>,
+  Y: Num + AsPrimitive<f64>,
+{
+  let (x, y) = &series.points[index];
+  let center = Point::new(geometry.to_pixel_x(x.as_()), geometry.to_pixel_y(y.as_()));
+  let radius = series
+    .sizes
+    .as_ref()
+    .and_then(|sizes| sizes.get(index))
+    .copied()
+    .unwrap_or(default_radius);
+
+  (center, radius)
+}
+
+/// Finds the marker under `cursor` as `(series index, point index)`, preferring the one whose
+/// center is closest when markers overlap.
+fn hovered_point<X, Y>(
+  series: &[Series<X, Y>],
+  geometry: &ChartGeometry,
+  cursor: Point,
+  default_radius: f64,
+) -> Option<(usize, usize)>
+where
+  X: Num + AsPrimitive<f64>,
+  Y: Num + AsPrimitive<f64>,
+{
+  series
+    .iter()
+    .enumerate()
+    .flat_map(|(serie_index, serie)| {
+      (0..serie.points.len()).map(move |point_index| (serie_index, point_index))
+    })
+    .filter_map(|(serie_index, point_index)| {
+      let (center, radius) = marker(&series[serie_index], point_index, geometry, default_radius);
+      let distance = center.distance(cursor);
+
+      // Small markers stay easy to hover
+      if distance <= radius.max(default_radius) + 2.0 {
+        Some(((serie_index, point_index), distance))
+      } else {
+        None
+      }
+    })
+    .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+    .map(|(hovered, _)| hovered)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_hovered_point() {
+    let series = vec![
+      Series::new(vec![(0.0, 0.0), (5.0, 5.0)], Color::WHITE),
+      Series::new(vec![(10.0, 10.0)], Color::BLACK).with_sizes(vec![20.0]),
+    ];
+    let bounds = DataBounds::from_points(
+      series
+        .iter()
+        .flat_map(|s| s.points.iter())
+        .map(|(x, y)| (x.as_(), y.as_())),
+    );
+    let geometry = AxisSettings::default().geometry(Size::new(400.0, 300.0), &bounds, false);
+
+    let middle = Point::new(geometry.to_pixel_x(5.0), geometry.to_pixel_y(5.0));
+    assert_eq!(hovered_point(&series, &geometry, middle, 4.0), Some((0, 1)));
+
+    // Far from the small marker, but inside the bubble
+    let bubble = Point::new(geometry.to_pixel_x(10.0) - 15.0, geometry.to_pixel_y(10.0));
+    assert_eq!(hovered_point(&series, &geometry, bubble, 4.0), Some((1, 0)));
+
+    let empty = Point::new(geometry.to_pixel_x(2.5), geometry.to_pixel_y(7.5));
+    assert_eq!(hovered_point(&series, &geometry, empty, 4.0), None);
+  }
+
+  #[test]
+  fn test_color_fn() {
+    fn threshold(value: &f64) -> Color {
+      if *value >= 10.0 {
+        Color::rgb8(0, 255, 0)
+      } else {
+        Color::rgb8(255, 0, 0)
+      }
+    }
+    fn grey(_: &f64) -> Color {
+      Color::grey8(0x80)
+    }
+
+    let points = vec![(0.0, 12.0), (1.0, 4.0)];
+    let plain = ScatterChartData::new().with_series(Series::new(points.clone(), Color::WHITE));
+    let colored = ScatterChartData::new()
+      .with_series(Series::new(points.clone(), Color::WHITE).with_color_fn(threshold));
+
+    let svg = ScatterChart::new().render_svg(&colored, Size::new(400.0, 300.0));
+    assert!(svg.contains(r#"fill="rgb(0,255,0)" fill-opacity="0.600""#));
+    assert!(svg.contains(r#"fill="rgb(255,0,0)" fill-opacity="0.600""#));
+    assert!(!svg.contains(r#"fill="rgb(255,255,255)" fill-opacity="0.600""#));
+
+    // Setting or swapping the function is a change of data
+    let greyed =
+      ScatterChartData::new().with_series(Series::new(points, Color::WHITE).with_color_fn(grey));
+    assert!(!plain.same(&colored));
+    assert!(!colored.same(&greyed));
+    assert!(colored.same(&colored.clone()));
+  }
+}
@@ -0,0 +1,199 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A bare line of values, small enough to sit inline, such as in table cells.
+
+use std::sync::Arc;
+
+use druid::widget::prelude::*;
+use druid::{
+  kurbo::{BezPath, Circle},
+  Color, Data, Point, Rect,
+};
+
+use crate::charts::theme as chart_theme;
+
+/// Size the sparkline asks for when its constraints leave the choice.
+const DEFAULT_SIZE: Size = Size::new(80.0, 20.0);
+
+/// A line through a series of values, at evenly spaced X, scaled to fill the widget. There are no
+/// axes, labels or padding.
+pub struct Sparkline {
+  color: Option<Color>,
+  stroke_width: f64,
+  last_point: bool,
+}
+
+impl Sparkline {
+  pub fn new() -> Self {
+    Self {
+      color: None,
+      stroke_width: 1.0,
+      last_point: false,
+    }
+  }
+
+  /// Color of the line, `charts::theme::AXIS_COLOR` by default.
+  pub fn with_color(mut self, color: Color) -> Self {
+    self.color = Some(color);
+    self
+  }
+
+  pub fn with_stroke_width(mut self, width: f64) -> Self {
+    self.stroke_width = width;
+    self
+  }
+
+  /// Marks the last value with a dot.
+  pub fn with_last_point(mut self, show: bool) -> Self {
+    self.last_point = show;
+    self
+  }
+
+  fn marker_radius(&self) -> f64 {
+    self.stroke_width + 1.0
+  }
+}
+
+impl Default for Sparkline {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Widget<Arc<Vec<f64>>> for Sparkline {
+  fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut Arc<Vec<f64>>, _env: &Env) {}
+
+  fn lifecycle(
+    &mut self,
+    _ctx: &mut LifeCycleCtx,
+    _event: &LifeCycle,
+    _data: &Arc<Vec<f64>>,
+    _env: &Env,
+  ) {
+  }
+
+  fn update(
+    &mut self,
+    ctx: &mut UpdateCtx,
+    old_data: &Arc<Vec<f64>>,
+    data: &Arc<Vec<f64>>,
+    _env: &Env,
+  ) {
+    if !old_data.same(data) {
+      ctx.request_paint();
+    }
+  }
+
+  fn layout(
+    &mut self,
+    _ctx: &mut LayoutCtx,
+    bc: &BoxConstraints,
+    _data: &Arc<Vec<f64>>,
+    _env: &Env,
+  ) -> Size {
+    bc.constrain(DEFAULT_SIZE)
+  }
+
+  fn paint(&mut self, ctx: &mut PaintCtx, data: &Arc<Vec<f64>>, env: &Env) {
+    // Keep the stroke and the marker inside the widget
+    let inset = if self.last_point {
+      self.marker_radius()
+    } else {
+      self.stroke_width / 2.0
+    };
+    let area = ctx.size().to_rect().inset(-inset);
+
+    let points = spark_points(data, area);
+    if points.is_empty() {
+      return;
+    }
+
+    let color = self
+      .color
+      .clone()
+      .unwrap_or_else(|| chart_theme::axis_color(env));
+
+    let mut path = BezPath::new();
+    path.move_to(points[0]);
+    for point in points.iter().skip(1) {
+      path.line_to(*point);
+    }
+    ctx.stroke(path, &color, self.stroke_width);
+
+    if self.last_point {
+      if let Some(last) = points.last() {
+        ctx.fill(Circle::new(*last, self.marker_radius()), &color);
+      }
+    }
+  }
+}
+
+/// Positions of `values` spread across `area`, the smallest at the bottom and the largest at the
+/// top. Values that are all equal sit in the middle. Non-finite values are skipped.
+fn spark_points(values: &[f64], area: Rect) -> Vec<Point> {
+  let (min, max) = values
+    .iter()
+    .filter(|value| value.is_finite())
+    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+      (min.min(*value), max.max(*value))
+    });
+
+  let step = if values.len() > 1 {
+    area.width() / (values.len() - 1) as f64
+  } else {
+    0.0
+  };
+
+  values
+    .iter()
+    .enumerate()
+    .filter(|(_, value)| value.is_finite())
+    .map(|(index, value)| {
+      let y = if max > min {
+        area.y1 - (value - min) / (max - min) * area.height()
+      } else {
+        area.center().y
+      };
+
+      Point::new(area.x0 + step * index as f64, y)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_spark_points() {
+    let area = Rect::new(0.0, 0.0, 100.0, 20.0);
+
+    let points = spark_points(&[1.0, 3.0, 2.0], area);
+    assert_eq!(
+      points,
+      vec![
+        Point::new(0.0, 20.0),
+        Point::new(50.0, 0.0),
+        Point::new(100.0, 10.0)
+      ]
+    );
+
+    // A flat series sits in the middle
+    let flat = spark_points(&[4.0, 4.0], area);
+    assert!(flat.iter().all(|point| point.y == 10.0));
+
+    assert!(spark_points(&[], area).is_empty());
+  }
+}
@@ -0,0 +1,392 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A minimal SVG writer, used to export charts without a live druid context.
+//!
+//! Exports are laid out like the on-screen widgets, with the colors of druid's default theme
+//! unless the chart has a theme of its own.
+
+use std::fmt::Write;
+
+use druid::{kurbo::BezPath, Color, Point, Rect, Size, UnitPoint};
+
+/// Horizontal alignment of a text element relative to its anchor point.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum TextAnchor {
+  Start,
+  Middle,
+  End,
+}
+
+/// A drawing operation, kept until the document is written out so it can also be rasterized.
+pub(crate) enum Element {
+  FillRect(Rect, Color),
+  StrokeRect(Rect, Color, f64),
+  StrokePath(BezPath, Color, f64),
+  FillCircle(Point, f64, Color),
+  FillPath(BezPath, Color),
+  /// A path filled with a linear gradient between two points of its bounding box, through
+  /// colors at offsets from 0 at `start` to 1 at `end`.
+  FillGradient {
+    path: BezPath,
+    start: UnitPoint,
+    end: UnitPoint,
+    stops: Vec<(f64, Color)>,
+  },
+  Text {
+    text: String,
+    position: Point,
+    anchor: TextAnchor,
+    font_size: f64,
+    color: Color,
+    /// Counter-clockwise rotation about `position`, in degrees.
+    rotation: f64,
+    /// Offset of the baseline from `position`, along the rotated text.
+    dy: f64,
+  },
+}
+
+pub(crate) struct SvgDocument {
+  size: Size,
+  elements: Vec<Element>,
+}
+
+impl SvgDocument {
+  pub fn new(size: Size) -> Self {
+    Self {
+      size,
+      elements: Vec::new(),
+    }
+  }
+
+  #[cfg(feature = "png")]
+  pub fn size(&self) -> Size {
+    self.size
+  }
+
+  /// The drawing operations, in painting order.
+  #[cfg(feature = "png")]
+  pub fn elements(&self) -> &[Element] {
+    &self.elements
+  }
+
+  pub fn fill_rect(&mut self, rect: Rect, color: &Color) {
+    self.elements.push(Element::FillRect(rect, color.clone()));
+  }
+
+  pub fn stroke_rect(&mut self, rect: Rect, color: &Color, width: f64) {
+    self
+      .elements
+      .push(Element::StrokeRect(rect, color.clone(), width));
+  }
+
+  pub fn stroke_path(&mut self, path: &BezPath, color: &Color, width: f64) {
+    self
+      .elements
+      .push(Element::StrokePath(path.clone(), color.clone(), width));
+  }
+
+  pub fn fill_circle(&mut self, center: Point, radius: f64, color: &Color) {
+    self
+      .elements
+      .push(Element::FillCircle(center, radius, color.clone()));
+  }
+
+  pub fn fill_path(&mut self, path: &BezPath, color: &Color) {
+    self
+      .elements
+      .push(Element::FillPath(path.clone(), color.clone()));
+  }
+
+  /// Fills a path with a linear gradient from `start` to `end`, as points of its bounding box,
+  /// through colors at offsets from 0 to 1.
+  pub fn fill_path_gradient(
+    &mut self,
+    path: &BezPath,
+    start: UnitPoint,
+    end: UnitPoint,
+    stops: &[(f64, Color)],
+  ) {
+    self.elements.push(Element::FillGradient {
+      path: path.clone(),
+      start,
+      end,
+      stops: stops.to_vec(),
+    });
+  }
+
+  pub fn text(
+    &mut self,
+    text: &str,
+    position: Point,
+    anchor: TextAnchor,
+    font_size: f64,
+    color: &Color,
+  ) {
+    self.rotated_text(text, position, 0.0, 0.0, anchor, font_size, color);
+  }
+
+  /// Text rotated counter-clockwise by `rotation` degrees about `position`, with its baseline
+  /// `dy` below that point before the rotation.
+  #[allow(clippy::too_many_arguments)]
+  pub fn rotated_text(
+    &mut self,
+    text: &str,
+    position: Point,
+    dy: f64,
+    rotation: f64,
+    anchor: TextAnchor,
+    font_size: f64,
+    color: &Color,
+  ) {
+    self.elements.push(Element::Text {
+      text: text.to_string(),
+      position,
+      anchor,
+      font_size,
+      color: color.clone(),
+      rotation,
+      dy,
+    });
+  }
+
+  pub fn finish(self) -> String {
+    let mut defs = String::new();
+    let mut body = String::new();
+    let mut gradient_count = 0;
+
+    for element in self.elements.iter() {
+      write_element(element, &mut defs, &mut body, &mut gradient_count);
+    }
+
+    let mut output = String::new();
+    writeln!(
+      output,
+      r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+      w = self.size.width,
+      h = self.size.height
+    )
+    .unwrap();
+
+    if !defs.is_empty() {
+      output.push_str("<defs>\n");
+      output.push_str(&defs);
+      output.push_str("</defs>\n");
+    }
+
+    output.push_str(&body);
+    output.push_str("</svg>\n");
+    output
+  }
+}
+
+/// Writes an element to the body, and the gradient it uses, if any, to the definitions.
+fn write_element(element: &Element, defs: &mut String, body: &mut String, gradients: &mut usize) {
+  match element {
+    Element::FillRect(rect, color) => {
+      let (fill, opacity) = svg_color(color);
+      writeln!(
+        body,
+        r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}" fill-opacity="{:.3}"/>"#,
+        rect.x0,
+        rect.y0,
+        rect.width(),
+        rect.height(),
+        fill,
+        opacity
+      )
+      .unwrap();
+    }
+    Element::StrokeRect(rect, color, width) => {
+      let (stroke, opacity) = svg_color(color);
+      writeln!(
+        body,
+        r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="none" stroke="{}" stroke-opacity="{:.3}" stroke-width="{}"/>"#,
+        rect.x0,
+        rect.y0,
+        rect.width(),
+        rect.height(),
+        stroke,
+        opacity,
+        width
+      )
+      .unwrap();
+    }
+    Element::StrokePath(path, color, width) => {
+      let (stroke, opacity) = svg_color(color);
+      writeln!(
+        body,
+        r#"<path d="{}" fill="none" stroke="{}" stroke-opacity="{:.3}" stroke-width="{}"/>"#,
+        path.to_svg(),
+        stroke,
+        opacity,
+        width
+      )
+      .unwrap();
+    }
+    Element::FillCircle(center, radius, color) => {
+      let (fill, opacity) = svg_color(color);
+      writeln!(
+        body,
+        r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}" fill-opacity="{:.3}"/>"#,
+        center.x, center.y, radius, fill, opacity
+      )
+      .unwrap();
+    }
+    Element::FillPath(path, color) => {
+      let (fill, opacity) = svg_color(color);
+      writeln!(
+        body,
+        r#"<path d="{}" fill="{}" fill-opacity="{:.3}" stroke="none"/>"#,
+        path.to_svg(),
+        fill,
+        opacity
+      )
+      .unwrap();
+    }
+    Element::FillGradient {
+      path,
+      start,
+      end,
+      stops,
+    } => {
+      *gradients += 1;
+      let id = format!("gradient{}", gradients);
+
+      // Unit points are fractions of the bounding box, as SVG gradient coordinates default to
+      let unit = Rect::new(0.0, 0.0, 1.0, 1.0);
+      let (start, end) = (start.resolve(unit), end.resolve(unit));
+      write!(
+        defs,
+        r#"<linearGradient id="{}" x1="{}" y1="{}" x2="{}" y2="{}">"#,
+        id, start.x, start.y, end.x, end.y
+      )
+      .unwrap();
+      for (offset, color) in stops {
+        let (color, opacity) = svg_color(color);
+        write!(
+          defs,
+          r#"<stop offset="{}" stop-color="{}" stop-opacity="{:.3}"/>"#,
+          offset, color, opacity
+        )
+        .unwrap();
+      }
+      writeln!(defs, "</linearGradient>").unwrap();
+
+      writeln!(
+        body,
+        r#"<path d="{}" fill="url(#{})" stroke="none"/>"#,
+        path.to_svg(),
+        id
+      )
+      .unwrap();
+    }
+    Element::Text {
+      text,
+      position,
+      anchor,
+      font_size,
+      color,
+      rotation,
+      dy,
+    } => {
+      let anchor = match anchor {
+        TextAnchor::Start => "start",
+        TextAnchor::Middle => "middle",
+        TextAnchor::End => "end",
+      };
+
+      let mut placement = String::new();
+      if *dy != 0.0 {
+        write!(placement, r#" dy="{:.2}""#, dy).unwrap();
+      }
+      if *rotation != 0.0 {
+        write!(
+          placement,
+          r#" transform="rotate({:.2} {:.2} {:.2})""#,
+          -rotation, position.x, position.y
+        )
+        .unwrap();
+      }
+
+      let (fill, opacity) = svg_color(color);
+      writeln!(
+        body,
+        r#"<text x="{:.2}" y="{:.2}"{} font-family="sans-serif" font-size="{}" text-anchor="{}" fill="{}" fill-opacity="{:.3}">{}</text>"#,
+        position.x,
+        position.y,
+        placement,
+        font_size,
+        anchor,
+        fill,
+        opacity,
+        escape(text)
+      )
+      .unwrap();
+    }
+  }
+}
+
+/// Splits a color into an SVG `rgb()` value and its opacity.
+fn svg_color(color: &Color) -> (String, f64) {
+  let (r, g, b, a) = color.as_rgba8();
+  (format!("rgb({},{},{})", r, g, b), f64::from(a) / 255.0)
+}
+
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_finish() {
+    let mut svg = SvgDocument::new(Size::new(120.0, 80.5));
+    let mut path = BezPath::new();
+    path.move_to((0.0, 0.0));
+    path.line_to((10.0, 20.0));
+    svg.stroke_path(&path, &Color::rgba8(255, 0, 0, 128), 2.0);
+    svg.text(
+      r#"<a href="x">Q&A</a>"#,
+      Point::new(5.0, 5.0),
+      TextAnchor::Middle,
+      12.0,
+      &Color::BLACK,
+    );
+    let output = svg.finish();
+
+    assert!(output.starts_with(
+      r#"<svg xmlns="http://www.w3.org/2000/svg" width="120" height="80.5" viewBox="0 0 120 80.5">"#
+    ));
+    assert!(output.ends_with("</svg>\n"));
+    assert!(!output.contains("<defs>"));
+    assert!(output.contains(r#"<path d="M0 0L10 20" fill="none" stroke="rgb(255,0,0)" stroke-opacity="0.502" stroke-width="2"/>"#));
+    assert!(output.contains(">&lt;a href=&quot;x&quot;&gt;Q&amp;A&lt;/a&gt;</text>"));
+  }
+
+  #[test]
+  fn test_escape() {
+    assert_eq!(escape("plain"), "plain");
+    assert_eq!(
+      escape(r#"a < b & "c" > d"#),
+      "a &lt; b &amp; &quot;c&quot; &gt; d"
+    );
+  }
+}
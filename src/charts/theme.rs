@@ -0,0 +1,201 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Environment keys for the colors of the charts.
+//!
+//! Each key is optional: when it isn't set, the charts fall back to the druid theme color they
+//! have always used, noted on the key. Setting them styles the charts without touching other
+//! widgets.
+//!
+//! A [`ChartTheme`] sets all of them at once, either for every chart of an app through
+//! [`ChartTheme::add_to_env`] or for a single chart, like `LineChart::with_theme`.
+
+use druid::{theme, Color, Env, Key};
+
+use crate::charts::axis::DEFAULT_FOREGROUND;
+
+/// Axes, ticks, labels, titles and the cursor crosshair. Falls back to `FOREGROUND_DARK`.
+pub const AXIS_COLOR: Key<Color> = Key::new("birog.charts.axis-color");
+/// Gridlines, unless a `GridConfig` sets its own color. Falls back to `FOREGROUND_DARK`.
+pub const GRID_COLOR: Key<Color> = Key::new("birog.charts.grid-color");
+/// Background of the value boxes. Falls back to `FOREGROUND_DARK`.
+pub const TOOLTIP_BG: Key<Color> = Key::new("birog.charts.tooltip-bg");
+/// Text of the value boxes. Falls back to `BACKGROUND_DARK`.
+pub const TOOLTIP_FG: Key<Color> = Key::new("birog.charts.tooltip-fg");
+/// Drawn over the data colors: outlines of highlighted points and slices, and text on slices.
+/// Falls back to `BACKGROUND_DARK`.
+pub const OUTLINE_COLOR: Key<Color> = Key::new("birog.charts.outline-color");
+/// Ring around the point focused with the keyboard. Falls back to `PRIMARY_LIGHT`.
+pub const FOCUS_COLOR: Key<Color> = Key::new("birog.charts.focus-color");
+
+/// Sets every chart color in `env` to its fallback, as currently set in `env`. Later changes to
+/// the druid theme colors no longer reach the charts.
+pub fn add_to_env(env: &mut Env) {
+  let foreground = env.get(theme::FOREGROUND_DARK);
+  let background = env.get(theme::BACKGROUND_DARK);
+
+  env.set(AXIS_COLOR, foreground.clone());
+  env.set(GRID_COLOR, foreground.clone());
+  env.set(TOOLTIP_BG, foreground);
+  env.set(TOOLTIP_FG, background.clone());
+  env.set(OUTLINE_COLOR, background);
+  env.set(FOCUS_COLOR, env.get(theme::PRIMARY_LIGHT));
+}
+
+/// A coherent set of chart colors, for charts drawn on a light or a dark background.
+#[derive(Clone, Debug)]
+pub struct ChartTheme {
+  /// See [`AXIS_COLOR`].
+  pub axis: Color,
+  /// See [`GRID_COLOR`].
+  pub grid: Color,
+  /// See [`TOOLTIP_BG`].
+  pub tooltip_bg: Color,
+  /// See [`TOOLTIP_FG`].
+  pub tooltip_fg: Color,
+  /// See [`OUTLINE_COLOR`].
+  pub outline: Color,
+  /// See [`FOCUS_COLOR`].
+  pub focus: Color,
+}
+
+impl ChartTheme {
+  /// Dark text and lines, for charts on a light background.
+  pub fn light() -> Self {
+    ChartTheme {
+      axis: Color::rgb8(0x3A, 0x3F, 0x4B),
+      grid: Color::rgb8(0x3A, 0x3F, 0x4B),
+      tooltip_bg: Color::rgb8(0x2B, 0x30, 0x3B),
+      tooltip_fg: Color::rgb8(0xF5, 0xF5, 0xF5),
+      outline: Color::WHITE,
+      focus: Color::rgb8(0x00, 0x8D, 0xDD),
+    }
+  }
+
+  /// Light text and lines, for charts on a dark background. These are the colors the examples
+  /// have always used.
+  pub fn dark() -> Self {
+    ChartTheme {
+      axis: Color::rgb8(0xCB, 0xCC, 0xC6),
+      grid: Color::rgb8(0xCB, 0xCC, 0xC6),
+      tooltip_bg: Color::rgb8(0xCB, 0xCC, 0xC6),
+      tooltip_fg: Color::rgb8(0x1F, 0x24, 0x30),
+      outline: Color::rgb8(0x1F, 0x24, 0x30),
+      focus: Color::rgb8(0x5C, 0xC4, 0xFF),
+    }
+  }
+
+  /// The light or the dark theme, whichever contrasts with `background`, such as the
+  /// `WINDOW_BACKGROUND_COLOR` of the app.
+  pub fn for_background(background: &Color) -> Self {
+    if relative_luminance(background) > 0.18 {
+      Self::light()
+    } else {
+      Self::dark()
+    }
+  }
+
+  /// The fallbacks of the keys in druid's default theme, for exports drawn without an `Env`.
+  pub(crate) fn druid_default() -> Self {
+    let background = Color::rgb8(0x31, 0x31, 0x31);
+    ChartTheme {
+      axis: DEFAULT_FOREGROUND,
+      grid: DEFAULT_FOREGROUND,
+      tooltip_bg: DEFAULT_FOREGROUND,
+      tooltip_fg: background.clone(),
+      outline: background,
+      focus: Color::rgb8(0x5c, 0xc4, 0xff),
+    }
+  }
+
+  /// Sets every chart color in `env` to the colors of the theme.
+  pub fn add_to_env(&self, env: &mut Env) {
+    env.set(AXIS_COLOR, self.axis.clone());
+    env.set(GRID_COLOR, self.grid.clone());
+    env.set(TOOLTIP_BG, self.tooltip_bg.clone());
+    env.set(TOOLTIP_FG, self.tooltip_fg.clone());
+    env.set(OUTLINE_COLOR, self.outline.clone());
+    env.set(FOCUS_COLOR, self.focus.clone());
+  }
+
+  /// A copy of `env` with the colors of the theme.
+  pub(crate) fn scoped(&self, env: &Env) -> Env {
+    let mut env = env.clone();
+    self.add_to_env(&mut env);
+    env
+  }
+}
+
+/// Luminance of an sRGB color as defined by WCAG, from 0 for black to 1 for white. Past about
+/// 0.18, black text contrasts more than white text.
+fn relative_luminance(color: &Color) -> f64 {
+  let linear = |channel: f64| {
+    if channel <= 0.03928 {
+      channel / 12.92
+    } else {
+      ((channel + 0.055) / 1.055).powf(2.4)
+    }
+  };
+  let (r, g, b, _) = color.as_rgba();
+  0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+pub(crate) fn axis_color(env: &Env) -> Color {
+  get_or(env, AXIS_COLOR, theme::FOREGROUND_DARK)
+}
+
+pub(crate) fn grid_color(env: &Env) -> Color {
+  get_or(env, GRID_COLOR, theme::FOREGROUND_DARK)
+}
+
+pub(crate) fn tooltip_bg(env: &Env) -> Color {
+  get_or(env, TOOLTIP_BG, theme::FOREGROUND_DARK)
+}
+
+pub(crate) fn tooltip_fg(env: &Env) -> Color {
+  get_or(env, TOOLTIP_FG, theme::BACKGROUND_DARK)
+}
+
+pub(crate) fn outline_color(env: &Env) -> Color {
+  get_or(env, OUTLINE_COLOR, theme::BACKGROUND_DARK)
+}
+
+pub(crate) fn focus_color(env: &Env) -> Color {
+  get_or(env, FOCUS_COLOR, theme::PRIMARY_LIGHT)
+}
+
+fn get_or(env: &Env, key: Key<Color>, fallback: Key<Color>) -> Color {
+  env.try_get(key).unwrap_or_else(|| env.get(fallback))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_for_background() {
+    let dark = ChartTheme::dark();
+    let light = ChartTheme::light();
+
+    let on_white = ChartTheme::for_background(&Color::WHITE);
+    assert_eq!(on_white.axis.as_rgba_u32(), light.axis.as_rgba_u32());
+    let on_default = ChartTheme::for_background(&Color::rgb8(0x29, 0x29, 0x29));
+    assert_eq!(on_default.axis.as_rgba_u32(), dark.axis.as_rgba_u32());
+    let on_grey = ChartTheme::for_background(&Color::rgb8(0xC0, 0xC0, 0xC0));
+    assert_eq!(
+      on_grey.tooltip_fg.as_rgba_u32(),
+      light.tooltip_fg.as_rgba_u32()
+    );
+  }
+}
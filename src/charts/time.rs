@@ -0,0 +1,375 @@
+// Copyright 2020 The Birog Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ticks and labels for time axes, whose values are seconds since the Unix epoch, in UTC.
+//!
+//! Ticks are placed by stepping through calendar dates rather than by fixed numbers of seconds,
+//! so months of any length and leap years still get their tick on the first day. UTC has no
+//! daylight saving time, so days are always 24 hours long.
+
+const SECONDS_PER_MINUTE: i64 = 60;
+const SECONDS_PER_HOUR: i64 = 3600;
+const SECONDS_PER_DAY: i64 = 86400;
+
+const MONTH_NAMES: [&str; 12] = [
+  "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Calendar units, from the finest to the coarsest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimeUnit {
+  Second,
+  Minute,
+  Hour,
+  Day,
+  /// Weeks start on Monday.
+  Week,
+  Month,
+  Year,
+}
+
+/// A tick of a time axis, with the coarsest calendar boundary it falls on: a tick at midnight on
+/// January 1st has a `Year` unit, whatever the step between ticks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeTick {
+  pub value: f64,
+  pub unit: TimeUnit,
+}
+
+/// Steps between ticks, from the shortest, as a number of calendar units. Steps of years go on
+/// with nice multiples past the last one.
+const STEPS: [(TimeUnit, i64); 19] = [
+  (TimeUnit::Second, 1),
+  (TimeUnit::Second, 5),
+  (TimeUnit::Second, 15),
+  (TimeUnit::Second, 30),
+  (TimeUnit::Minute, 1),
+  (TimeUnit::Minute, 5),
+  (TimeUnit::Minute, 15),
+  (TimeUnit::Minute, 30),
+  (TimeUnit::Hour, 1),
+  (TimeUnit::Hour, 3),
+  (TimeUnit::Hour, 6),
+  (TimeUnit::Hour, 12),
+  (TimeUnit::Day, 1),
+  (TimeUnit::Day, 2),
+  (TimeUnit::Week, 1),
+  (TimeUnit::Month, 1),
+  (TimeUnit::Month, 3),
+  (TimeUnit::Month, 6),
+  (TimeUnit::Year, 1),
+];
+
+impl TimeUnit {
+  /// Length of the unit in seconds, on average for months and years.
+  fn seconds(self) -> f64 {
+    match self {
+      TimeUnit::Second => 1.0,
+      TimeUnit::Minute => SECONDS_PER_MINUTE as f64,
+      TimeUnit::Hour => SECONDS_PER_HOUR as f64,
+      TimeUnit::Day => SECONDS_PER_DAY as f64,
+      TimeUnit::Week => 7.0 * SECONDS_PER_DAY as f64,
+      TimeUnit::Month => 30.436875 * SECONDS_PER_DAY as f64,
+      TimeUnit::Year => 365.2425 * SECONDS_PER_DAY as f64,
+    }
+  }
+}
+
+/// Generates ticks between `min` and `max` on calendar boundaries, at most about `max_count` of
+/// them, along with the unit of the step between them. Ticks whose own unit is coarser than the
+/// step, such as month starts among daily ticks, are the major ones.
+///
+/// Empty or non-finite ranges get no ticks.
+pub fn ticks(min: f64, max: f64, max_count: usize) -> (Vec<TimeTick>, TimeUnit) {
+  if !(min.is_finite() && max.is_finite() && max > min) {
+    return (Vec::new(), TimeUnit::Second);
+  }
+
+  let span = max - min;
+  let max_count = max_count.max(1) as f64;
+  let (unit, count) = STEPS
+    .iter()
+    .copied()
+    .find(|(unit, count)| span / (unit.seconds() * *count as f64) < max_count)
+    .unwrap_or_else(|| (TimeUnit::Year, year_step(span, max_count)));
+
+  let ticks = step_values(min, max, unit, count)
+    .into_iter()
+    .map(|value| TimeTick {
+      value: value as f64,
+      unit: granularity(value as f64),
+    })
+    .collect();
+
+  (ticks, unit)
+}
+
+/// A nice number of years, 1, 2 or 5 times a power of ten, giving fewer than `max_count` ticks.
+fn year_step(span: f64, max_count: f64) -> i64 {
+  let years = span / TimeUnit::Year.seconds() / max_count;
+  let magnitude = 10.0f64.powf(years.log10().floor().max(0.0));
+  let factor = [1.0, 2.0, 5.0, 10.0]
+    .iter()
+    .copied()
+    .find(|factor| factor * magnitude > years)
+    .unwrap_or(10.0);
+
+  (factor * magnitude) as i64
+}
+
+/// Timestamps of every `count` units between `min` and `max`, counted from the start of the
+/// next coarser unit: every 6 hours from midnight, every 3 months from January.
+fn step_values(min: f64, max: f64, unit: TimeUnit, count: i64) -> Vec<i64> {
+  let (first, last) = (min.ceil() as i64, max.floor() as i64);
+  let mut values = Vec::new();
+
+  match unit {
+    TimeUnit::Second | TimeUnit::Minute | TimeUnit::Hour => {
+      // These steps divide a day evenly, so multiples since the epoch start at midnight
+      let step = unit.seconds() as i64 * count;
+      let mut value = first.div_euclid(step) * step;
+      if value < first {
+        value += step;
+      }
+      while value <= last {
+        values.push(value);
+        value += step;
+      }
+    }
+    TimeUnit::Day => {
+      // Restart from the first of each month, so a month's ticks don't depend on the previous one
+      let mut day = first.div_euclid(SECONDS_PER_DAY);
+      while day * SECONDS_PER_DAY <= last {
+        let (_, _, day_of_month) = civil_from_days(day);
+        if i64::from(day_of_month - 1) % count == 0 && day * SECONDS_PER_DAY >= first {
+          values.push(day * SECONDS_PER_DAY);
+        }
+        day += 1;
+      }
+    }
+    TimeUnit::Week => {
+      let mut day = first.div_euclid(SECONDS_PER_DAY);
+      day += (7 - weekday(day)) % 7;
+      if day * SECONDS_PER_DAY < first {
+        day += 7;
+      }
+      while day * SECONDS_PER_DAY <= last {
+        values.push(day * SECONDS_PER_DAY);
+        day += 7 * count;
+      }
+    }
+    TimeUnit::Month => {
+      let (year, month, _) = civil_from_days(first.div_euclid(SECONDS_PER_DAY));
+      let mut index = (year * 12 + i64::from(month) - 1).div_euclid(count) * count;
+      loop {
+        let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+        let value = days_from_civil(year, month, 1) * SECONDS_PER_DAY;
+        if value > last {
+          break;
+        }
+        if value >= first {
+          values.push(value);
+        }
+        index += count;
+      }
+    }
+    TimeUnit::Year => {
+      let (year, _, _) = civil_from_days(first.div_euclid(SECONDS_PER_DAY));
+      let mut year = year.div_euclid(count) * count;
+      loop {
+        let value = days_from_civil(year, 1, 1) * SECONDS_PER_DAY;
+        if value > last {
+          break;
+        }
+        if value >= first {
+          values.push(value);
+        }
+        year += count;
+      }
+    }
+  }
+
+  values
+}
+
+/// The coarsest calendar unit whose boundary `value` falls on.
+pub fn granularity(value: f64) -> TimeUnit {
+  if value.fract() != 0.0 {
+    return TimeUnit::Second;
+  }
+
+  let seconds = value as i64;
+  let time_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+  if time_of_day != 0 {
+    return if time_of_day % SECONDS_PER_HOUR == 0 {
+      TimeUnit::Hour
+    } else if time_of_day % SECONDS_PER_MINUTE == 0 {
+      TimeUnit::Minute
+    } else {
+      TimeUnit::Second
+    };
+  }
+
+  let days = seconds.div_euclid(SECONDS_PER_DAY);
+  match civil_from_days(days) {
+    (_, 1, 1) => TimeUnit::Year,
+    (_, _, 1) => TimeUnit::Month,
+    _ if weekday(days) == 0 => TimeUnit::Week,
+    _ => TimeUnit::Day,
+  }
+}
+
+/// Labels a tick with just what changes at its boundary: the year on January 1st, the month on
+/// other month starts, the day at midnight and the time of day otherwise.
+pub fn format_tick(value: f64) -> String {
+  let (year, month, day, hour, minute, second) = split(value);
+
+  match granularity(value) {
+    TimeUnit::Year => format!("{}", year),
+    TimeUnit::Month => MONTH_NAMES[month as usize - 1].to_string(),
+    TimeUnit::Week | TimeUnit::Day => format!("{} {}", MONTH_NAMES[month as usize - 1], day),
+    TimeUnit::Hour | TimeUnit::Minute => format!("{:02}:{:02}", hour, minute),
+    TimeUnit::Second => format!("{:02}:{:02}:{:02}", hour, minute, second),
+  }
+}
+
+/// Formats any time in full, down to the unit of the axis step.
+pub fn format_time(value: f64, step: TimeUnit) -> String {
+  let (year, month, day, hour, minute, second) = split(value);
+  let month = MONTH_NAMES[month as usize - 1];
+
+  match step {
+    TimeUnit::Year => format!("{}", year),
+    TimeUnit::Month => format!("{} {}", month, year),
+    TimeUnit::Week | TimeUnit::Day => format!("{} {}, {}", month, day, year),
+    TimeUnit::Hour | TimeUnit::Minute => {
+      format!("{} {}, {} {:02}:{:02}", month, day, year, hour, minute)
+    }
+    TimeUnit::Second => format!(
+      "{} {}, {} {:02}:{:02}:{:02}",
+      month, day, year, hour, minute, second
+    ),
+  }
+}
+
+/// Splits a timestamp into year, month, day, hour, minute and second.
+fn split(value: f64) -> (i64, u32, u32, i64, i64, i64) {
+  let seconds = value.floor() as i64;
+  let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+  let time_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+
+  (
+    year,
+    month,
+    day,
+    time_of_day / SECONDS_PER_HOUR,
+    time_of_day % SECONDS_PER_HOUR / SECONDS_PER_MINUTE,
+    time_of_day % SECONDS_PER_MINUTE,
+  )
+}
+
+/// Day of the week of a day since the epoch, from 0 for Monday to 6 for Sunday.
+fn weekday(days: i64) -> i64 {
+  // The epoch was a Thursday
+  (days + 3).rem_euclid(7)
+}
+
+/// Days since the epoch of a date of the proleptic Gregorian calendar.
+///
+/// Reference: Hinnant, H. chrono-Compatible Low-Level Date Algorithms.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+  // Years start in March, so the leap day ends them
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = year.div_euclid(400);
+  let year_of_era = year.rem_euclid(400);
+  let day_of_year = (153 * ((i64::from(month) + 9) % 12) + 2) / 5 + i64::from(day) - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+  era * 146_097 + day_of_era - 719_468
+}
+
+/// Date of the proleptic Gregorian calendar of a day since the epoch.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+  let days = days + 719_468;
+  let era = days.div_euclid(146_097);
+  let day_of_era = days.rem_euclid(146_097);
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month_from_march = (5 * day_of_year + 2) / 153;
+  let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+  let month = if month_from_march < 10 {
+    month_from_march + 3
+  } else {
+    month_from_march - 9
+  } as u32;
+
+  let year = year_of_era + era * 400;
+  (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn timestamp(year: i64, month: u32, day: u32) -> f64 {
+    (days_from_civil(year, month, day) * SECONDS_PER_DAY) as f64
+  }
+
+  #[test]
+  fn test_calendar() {
+    assert_eq!(days_from_civil(1970, 1, 1), 0);
+    assert_eq!(civil_from_days(0), (1970, 1, 1));
+    assert_eq!(civil_from_days(days_from_civil(2020, 2, 29)), (2020, 2, 29));
+    assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    assert_eq!(weekday(days_from_civil(2020, 6, 1)), 0);
+  }
+
+  #[test]
+  fn test_daily_ticks() {
+    // Daily steps across the end of February in a leap year
+    let (ticks, step) = ticks(timestamp(2020, 2, 26), timestamp(2020, 3, 4), 10);
+    assert_eq!(step, TimeUnit::Day);
+    assert_eq!(ticks.len(), 8);
+    assert_eq!(ticks[4].value, timestamp(2020, 3, 1));
+    assert_eq!(ticks[4].unit, TimeUnit::Month);
+    assert_eq!(ticks[5].unit, TimeUnit::Week);
+    assert_eq!(ticks[6].unit, TimeUnit::Day);
+
+    let labels: Vec<String> = ticks.iter().map(|tick| format_tick(tick.value)).collect();
+    assert_eq!(&labels[3..6], &["Feb 29", "Mar", "Mar 2"]);
+  }
+
+  #[test]
+  fn test_monthly_ticks() {
+    // Months of different lengths still get their ticks on the first
+    let (ticks, step) = ticks(timestamp(2019, 11, 15), timestamp(2020, 6, 15), 12);
+    assert_eq!(step, TimeUnit::Month);
+    let months = [(2019, 12), (2020, 1), (2020, 2), (2020, 3)];
+    let expected: Vec<f64> = months
+      .iter()
+      .chain(&[(2020, 4), (2020, 5), (2020, 6)])
+      .map(|(year, month)| timestamp(*year, *month, 1))
+      .collect();
+    assert_eq!(
+      ticks.iter().map(|tick| tick.value).collect::<Vec<_>>(),
+      expected
+    );
+    assert_eq!(ticks[1].unit, TimeUnit::Year);
+    assert_eq!(
+      format_time(ticks[1].value + 3600.0, TimeUnit::Day),
+      "Jan 1, 2020"
+    );
+  }
+}
@@ -30,13 +30,38 @@ pub enum LabelRange {
   Excluded,
 }
 
-/// Generates a sequence of labels to be displayed across an axis.
+/// Fewest labels `generate_labels` returns, unless a caller asks otherwise.
+pub(crate) const DEFAULT_MIN_TICKS: usize = 2;
+
+/// Most labels `generate_labels` returns, unless a caller asks otherwise.
+pub(crate) const DEFAULT_MAX_TICKS: usize = 20;
+
+/// Generates a sequence of labels to be displayed across an axis, or `None` when the search finds
+/// no labeling of at least two labels, as happens with empty or non-finite ranges.
+///
+/// The search aims for `max_labels` clamped to `min_ticks..=max_ticks`, and the result always
+/// has a number of labels in that range. When the best labeling found doesn't, nice-number ticks
+/// over the range are used instead.
 pub(crate) fn generate_labels(
   dmin: f64,
   dmax: f64,
   max_labels: f64,
   label_inclusion: LabelRange,
-) -> Vec<f64> {
+  min_ticks: usize,
+  max_ticks: usize,
+) -> Option<Vec<f64>> {
+  if !(dmin.is_finite() && dmax.is_finite() && dmax > dmin) {
+    return None;
+  }
+
+  // The density terms divide by `max_labels - 1`, so a single label can't be aimed for
+  let max_labels = max_labels
+    .max(min_ticks as f64)
+    .min(max_ticks as f64)
+    .max(2.0);
+  let count_fits = |count: usize| count >= min_ticks && count <= max_ticks;
+
+  let mut found = false;
   let mut outmin: f64 = 1.0;
   let mut outmax: f64 = 1.0;
   let mut outstep: f64 = 1.0;
@@ -63,8 +88,10 @@ pub(crate) fn generate_labels(
 
         let delta = (dmax - dmin) / (k + 1.0) / j / q;
 
-        'z_loop: for z in (delta.log10().ceil() as u64)..=u64::MAX {
-          let z = z as f64;
+        // Steps below 1 need negative powers of ten
+        let mut z = delta.log10().ceil() - 1.0;
+        'z_loop: loop {
+          z += 1.0;
 
           let step = j * q * 10.0f64.powf(z);
           let cm = coverage_max(dmin, dmax, step * (k - 1.0));
@@ -104,6 +131,7 @@ pub(crate) fn generate_labels(
                 continue;
               }
               _ => {
+                found = true;
                 best_score = score;
                 outmin = lmin;
                 outmax = lmax;
@@ -116,13 +144,79 @@ pub(crate) fn generate_labels(
     }
   }
 
+  if !found {
+    return None;
+  }
+
   let mut result = Vec::<f64>::with_capacity(((outmax - outmin) / outstep) as usize);
   while outmin <= outmax {
     result.push(outmin);
     outmin += outstep;
   }
 
-  result
+  if result.len() < 2 {
+    return None;
+  }
+
+  if count_fits(result.len()) {
+    return Some(result);
+  }
+
+  // Nice-number ticks can overshoot their target by a tick or two, so try the targets around
+  // the wanted count until one fits
+  let target = max_labels as usize;
+  (min_ticks.max(2)..=target)
+    .rev()
+    .chain(target + 1..=max_ticks)
+    .map(|count| nice_ticks(dmin, dmax, count))
+    .find(|ticks| count_fits(ticks.len()))
+}
+
+/// Classic "nice number" ticks: about `target_count` of them, stepping by 1, 2, 2.5 or 5 times a
+/// power of ten, from the last tick at or below the range to the first at or above it.
+///
+/// Reversed ranges are ticked as if given in order, and a zero-width range is widened around its
+/// value, so finite bounds always give at least two ticks. Non-finite bounds give none.
+pub fn nice_ticks(dmin: f64, dmax: f64, target_count: usize) -> Vec<f64> {
+  if !(dmin.is_finite() && dmax.is_finite()) {
+    return Vec::new();
+  }
+
+  let (mut min, mut max) = if dmin <= dmax {
+    (dmin, dmax)
+  } else {
+    (dmax, dmin)
+  };
+  if min == max {
+    let pad = if min == 0.0 { 1.0 } else { min.abs() * 0.1 };
+    min -= pad;
+    max += pad;
+  }
+
+  let rough_step = (max - min) / (target_count.max(2) - 1) as f64;
+  let exponent = rough_step.log10().floor() as i32;
+  let magnitude = 10.0f64.powi(exponent);
+  let factor = [1.0, 2.0, 2.5, 5.0, 10.0]
+    .iter()
+    .cloned()
+    .find(|factor| factor * magnitude >= rough_step)
+    .unwrap_or(10.0);
+  let step = factor * magnitude;
+
+  // Tolerate rounding in the division, so bounds on a tick don't reach for the next one
+  let first = (min / step + 1e-9).floor() as i64;
+  let last = (max / step - 1e-9).ceil() as i64;
+
+  // Dividing by a power of ten, rather than multiplying by its inverse, keeps decimal steps exact
+  let tick = |index: i64| {
+    if exponent < 0 {
+      index as f64 * factor / 10.0f64.powi(-exponent)
+    } else {
+      index as f64 * step
+    }
+  };
+
+  (first..=last.max(first + 1)).map(tick).collect()
 }
 
 const W: [f64; 4] = [0.2, 0.25, 0.5, 0.05];
@@ -180,9 +274,66 @@ fn score(c: f64, s: f64, g: f64, l: f64) -> f64 {
 
 #[cfg(test)]
 mod test {
+  use super::{DEFAULT_MAX_TICKS as MAX, DEFAULT_MIN_TICKS as MIN};
+
   #[test]
   fn test_wilkinson_extended() {
-    let labels = super::generate_labels(1.0, 10.0, 5.0, super::LabelRange::Any);
-    assert_eq!(labels, vec![0.0, 2.5, 5.0, 7.5, 10.0]);
+    let labels = super::generate_labels(1.0, 10.0, 5.0, super::LabelRange::Any, MIN, MAX);
+    assert_eq!(labels, Some(vec![0.0, 2.5, 5.0, 7.5, 10.0]));
+  }
+
+  #[test]
+  fn test_degenerate_ranges() {
+    use super::LabelRange::Included;
+
+    assert_eq!(
+      super::generate_labels(3.0, 3.0, 5.0, Included, MIN, MAX),
+      None
+    );
+    assert_eq!(
+      super::generate_labels(f64::NAN, 3.0, 5.0, Included, MIN, MAX),
+      None
+    );
+
+    // Ranges far below 1 still get labels
+    let labels = super::generate_labels(0.001, 0.009, 5.0, Included, MIN, MAX).unwrap();
+    assert!(labels.len() >= 2);
+    assert!(labels.iter().all(|v| *v >= 0.0 && *v <= 0.01));
+  }
+
+  #[test]
+  fn test_tick_count_clamp() {
+    use super::LabelRange::Included;
+
+    // A single label can't be aimed for, at least two are given
+    let labels = super::generate_labels(0.0, 100.0, 1.0, Included, MIN, MAX).unwrap();
+    assert!(labels.len() >= MIN && labels.len() <= MAX);
+
+    let labels = super::generate_labels(0.0, 100.0, 50.0, Included, MIN, MAX).unwrap();
+    assert!(labels.len() >= MIN && labels.len() <= MAX);
+    assert!(labels.first() <= Some(&0.0) && labels.last() >= Some(&100.0));
+
+    let labels = super::generate_labels(0.0, 1.0, 50.0, Included, 3, 4).unwrap();
+    assert!(labels.len() >= 3 && labels.len() <= 4);
+  }
+
+  #[test]
+  fn test_nice_ticks() {
+    use super::nice_ticks;
+
+    assert_eq!(nice_ticks(0.0, 1.0, 5), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+    assert_eq!(
+      nice_ticks(0.001, 0.009, 5),
+      vec![0.0, 0.002, 0.004, 0.006, 0.008, 0.01]
+    );
+    assert_eq!(
+      nice_ticks(-50.0, 50.0, 5),
+      vec![-50.0, -25.0, 0.0, 25.0, 50.0]
+    );
+
+    assert_eq!(nice_ticks(1.0, 0.0, 5), nice_ticks(0.0, 1.0, 5));
+    assert_eq!(nice_ticks(3.0, 3.0, 5), vec![2.6, 2.8, 3.0, 3.2, 3.4]);
+    assert_eq!(nice_ticks(0.0, 0.0, 1), vec![-2.0, 0.0, 2.0]);
+    assert!(nice_ticks(f64::NAN, 1.0, 5).is_empty());
   }
 }
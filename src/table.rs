@@ -1,164 +1,2010 @@
 use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::Arc;
 
-use druid::widget::{Flex, Label, LabelText, ListIter, SizedBox};
+use druid::piet::{FontBuilder, Text, TextLayout, TextLayoutBuilder};
+use druid::widget::{Checkbox, Either, Label, ListIter, TextBox};
 use druid::{
-  BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, Point,
-  Rect, Size, UpdateCtx, Widget, WidgetPod,
+  kurbo::Line, theme, BoxConstraints, Cursor, Data, Env, Event, EventCtx, KeyCode, LayoutCtx, Lens,
+  LifeCycle, LifeCycleCtx, PaintCtx, Point, Rect, RenderContext, Size, UpdateCtx, Vec2, Widget,
+  WidgetExt, WidgetPod,
 };
 
-pub struct Table<T> {
-  headers: WidgetPod<(), Flex<()>>,
+use crate::charts::csv;
+
+/// Number of rows skipped by Page Up and Page Down.
+const PAGE_ROWS: usize = 10;
+
+/// How close to a divider between two headers, in pixels, a click starts resizing the column.
+const DIVIDER_TOLERANCE: f64 = 4.0;
+
+/// Pixels scrolled horizontally by the Left and Right arrow keys.
+const SCROLL_STEP: f64 = 40.0;
+
+/// Narrowest a column can be resized to.
+const MIN_RESIZED_WIDTH: f64 = 20.0;
+
+/// Horizontal space left on each side of wrapped text, as druid's `Label` does.
+const TEXT_X_PADDING: f64 = 2.0;
+
+/// Height of a line of wrapped text relative to the font size.
+const LINE_HEIGHT_FACTOR: f64 = 1.2;
+
+/// Width of the space left of the columns holding the disclosure triangles of expandable rows.
+const DISCLOSURE_WIDTH: f64 = 16.0;
+
+/// How far, in pixels, a row has to be dragged vertically before the drag reorders it rather than
+/// just selecting it.
+const REORDER_THRESHOLD: f64 = 4.0;
+
+/// Builds a new widget for a row, once per row.
+type WidgetBuilder<T> = Box<dyn Fn() -> Box<dyn Widget<T>>>;
+
+/// Builds a new cell for the row at the given index, once per row.
+type CellBuilder<T> = Box<dyn Fn(usize) -> Box<dyn Widget<T>>>;
+
+/// Callback receiving a row, along with its index in the collection.
+type RowCallback<T> = Box<dyn Fn(&mut EventCtx, &mut T, usize, &Env)>;
+
+/// Callback receiving the index of a column.
+type ColumnCallback = Box<dyn Fn(&mut EventCtx, usize)>;
+
+/// Callback receiving the range of visible rows.
+type RowsCallback = Box<dyn Fn(Range<usize>)>;
+
+/// Callback receiving the index a row was dragged from and the index it was dropped at.
+type ReorderCallback = Box<dyn Fn(&mut EventCtx, usize, usize)>;
+
+/// Callback receiving the index of a column and how the rows should now be sorted by it.
+type SortCallback = Box<dyn Fn(&mut EventCtx, usize, SortState)>;
+
+/// Header of a column in CSV exports, and the text of a row under it.
+type CsvColumn<T> = (String, fn(&T) -> String);
+
+/// Ordering of two rows, shared by the copies of a `SortedView`.
+type Comparator<T> = Arc<dyn Fn(&T, &T) -> Ordering>;
+
+/// How the rows are sorted by a column, as shown in its header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortState {
+  Ascending,
+  Descending,
+  /// Rows in their original order.
+  Unsorted,
+}
+
+/// A list of rows with one widget per column, under a row of headers and above an optional
+/// footer. `T` is the type of each row and `L` the collection holding them.
+///
+/// When the columns are wider than the table, they scroll sideways together, headers and footer
+/// included, with the horizontal wheel, Shift and the wheel, or the Left and Right arrow keys.
+pub struct Table<T, L> {
+  headers: WidgetPod<(), Row<()>>,
+  footer: WidgetPod<L, Row<L>>,
   columns: Vec<Column<T>>,
-  children: Vec<WidgetPod<T, Flex<T>>>,
+  children: Vec<WidgetPod<T, Row<T>>>,
+  /// Resolved width of every column, `None` when auto-sized columns need to be measured again.
+  widths: Option<Vec<f64>>,
+  selected: Option<usize>,
+  on_select: Option<RowCallback<T>>,
+  on_activate: Option<RowCallback<T>>,
+  on_header_click: Option<ColumnCallback>,
+  on_sort: Option<SortCallback>,
+  /// The column the rows are sorted by, and in which direction. Never `SortState::Unsorted`.
+  sort: Option<(usize, SortState)>,
+  on_need_rows: Option<RowsCallback>,
+  /// Number of rows at the last call to `on_need_rows`, until the end of the rows leaves the view.
+  need_rows_sent: Option<usize>,
+  /// Part of the table in view at the last paint, `None` until painted.
+  visible: Option<Rect>,
+  /// The column being resized by dragging its divider, if any.
+  resizing: Option<Resize>,
+  /// Number of leading columns that stay in place when the others scroll horizontally.
+  frozen_columns: usize,
+  /// How far the columns after the frozen ones are scrolled to the left.
+  scroll_x: f64,
+  /// Furthest `scroll_x` goes, so the last column ends at the right edge of the table.
+  max_scroll_x: f64,
+  /// Builds the section shown below the cells of an expanded row.
+  section: Option<WidgetBuilder<T>>,
+  /// The full-width section of every row, when the rows are expandable, by row index.
+  sections: Vec<WidgetPod<T, Box<dyn Widget<T>>>>,
+  /// Indices of the rows showing their section.
+  expanded: HashSet<usize>,
+  /// Top of every row as of the last layout, followed by the bottom of the last one. A row spans
+  /// its section when expanded.
+  row_offsets: Vec<f64>,
+  on_reorder: Option<ReorderCallback>,
+  /// The row being dragged to a new position, if any.
+  reordering: Option<Reorder>,
+}
+
+/// A row drag in progress.
+struct Reorder {
+  /// Index of the dragged row.
+  from: usize,
+  /// Cursor Y where the drag started.
+  start_y: f64,
+  /// Index of the gap between rows the row would be dropped in, `None` until the cursor has moved
+  /// past `REORDER_THRESHOLD`.
+  gap: Option<usize>,
+}
+
+/// Stacks the headers, rows, sections and footer top to bottom as they are laid out.
+struct RowStack {
+  /// Left edge of everything stacked, right of the disclosure triangles.
+  x: f64,
+  /// Bottom of the last widget placed.
+  y: f64,
+  /// Where each row starts, followed by where the last one ends, as in `Table::row_offsets`.
+  offsets: Vec<f64>,
+}
+
+/// A column resize in progress.
+struct Resize {
+  column: usize,
+  /// Cursor X where the drag started.
+  start_x: f64,
+  /// Width of the column when the drag started.
+  start_width: f64,
 }
 
 struct Column<T> {
-  widget: Box<dyn Fn() -> Box<dyn Widget<T>>>,
-  width: f64,
+  widget: CellBuilder<T>,
+  width: ColumnWidth,
+  /// Bounds of the width, whether fixed, measured or resized.
+  min_width: Option<f64>,
+  max_width: Option<f64>,
+  /// Whether the cells take focus to edit the row, which clicks on them leave alone.
+  editable: bool,
+  /// Header and text of each row in CSV exports, for columns added with `with_column_value`.
+  csv: Option<CsvColumn<T>>,
+}
+
+/// How the width of a column is decided.
+#[derive(Clone, Copy)]
+enum ColumnWidth {
+  Fixed(f64),
+  /// As wide as the widest cell of the column, header and footer included, within the bounds
+  /// of the column.
+  Auto,
 }
 
-impl<T: Data> Table<T> {
+/// A row of cells, each laid out at exactly its column width and clipped to it, so the headers,
+/// the rows and the footer stay aligned whatever their content.
+struct Row<T> {
+  cells: Vec<WidgetPod<T, Box<dyn Widget<T>>>>,
+  widths: Vec<f64>,
+  /// Number of leading cells that don't scroll.
+  frozen: usize,
+  scroll_x: f64,
+}
+
+impl<T: Data, L: ListIter<T>> Table<T, L> {
   pub fn new() -> Self {
     Self {
-      headers: WidgetPod::new(Flex::row()),
+      headers: WidgetPod::new(Row::new()),
+      footer: WidgetPod::new(Row::new()),
       columns: Vec::new(),
       children: Vec::new(),
+      widths: None,
+      selected: None,
+      on_select: None,
+      on_activate: None,
+      on_header_click: None,
+      on_sort: None,
+      sort: None,
+      on_need_rows: None,
+      need_rows_sent: None,
+      visible: None,
+      resizing: None,
+      frozen_columns: 0,
+      scroll_x: 0.0,
+      max_scroll_x: 0.0,
+      section: None,
+      sections: Vec::new(),
+      expanded: HashSet::new(),
+      row_offsets: Vec::new(),
+      on_reorder: None,
+      reordering: None,
     }
   }
 
   pub fn with_column<H: Widget<()> + 'static, W: Widget<T> + 'static>(
-    mut self,
+    self,
+    header: H,
+    closure: impl Fn() -> W + 'static,
+    width: f64,
+  ) -> Self {
+    self.add_column(header, move |_| closure(), ColumnWidth::Fixed(width))
+  }
+
+  /// Adds a column whose cells are built knowing the index of their row, as for row numbers:
+  ///
+  /// ```no_run
+  /// use birog::table::Table;
+  /// use druid::widget::Label;
+  /// use std::sync::Arc;
+  ///
+  /// let table: Table<String, Arc<Vec<String>>> = Table::new().with_indexed_column(
+  ///   Label::new("#"),
+  ///   |index| Label::new(format!("{}", index + 1)),
+  ///   40.0,
+  /// );
+  /// ```
+  ///
+  /// The index is the position of the row in the collection, which each cell keeps for as long
+  /// as it exists: rows are added and removed at the end, so the cells at an index always show
+  /// the row at that index.
+  pub fn with_indexed_column<H: Widget<()> + 'static, W: Widget<T> + 'static>(
+    self,
+    header: H,
+    closure: impl Fn(usize) -> W + 'static,
+    width: f64,
+  ) -> Self {
+    self.add_column(header, closure, ColumnWidth::Fixed(width))
+  }
+
+  /// Adds a column as wide as its widest cell for the current data, header and footer included,
+  /// clamped to the optional bounds. Widths are measured again whenever the data changes.
+  pub fn with_auto_column<H: Widget<()> + 'static, W: Widget<T> + 'static>(
+    self,
     header: H,
     closure: impl Fn() -> W + 'static,
+    min_width: Option<f64>,
+    max_width: Option<f64>,
+  ) -> Self {
+    self
+      .add_column(header, move |_| closure(), ColumnWidth::Auto)
+      .with_width_bounds(min_width, max_width)
+  }
+
+  /// Keeps the width of the column added last between `min_width` and `max_width`, when given,
+  /// whether it is fixed, measured from its cells or resized by dragging. The minimum wins over
+  /// a smaller maximum.
+  pub fn with_width_bounds(mut self, min_width: Option<f64>, max_width: Option<f64>) -> Self {
+    if let Some(column) = self.columns.last_mut() {
+      column.min_width = min_width;
+      column.max_width = max_width;
+    }
+    self.widths = None;
+
+    self
+  }
+
+  /// Adds a column showing the text `value` returns for each row, or a dimmed `placeholder`
+  /// when it returns `None`.
+  pub fn with_optional_column<H: Widget<()> + 'static>(
+    self,
+    header: H,
+    value: fn(&T) -> Option<String>,
+    placeholder: impl Into<String>,
+    width: f64,
+  ) -> Self {
+    let placeholder = placeholder.into();
+
+    self.with_column(
+      header,
+      move || {
+        Either::new(
+          move |row: &T, _env: &Env| value(row).is_some(),
+          Label::new(move |row: &T, _env: &Env| value(row).unwrap_or_default()),
+          Label::new(placeholder.clone()).with_text_color(theme::PLACEHOLDER_COLOR),
+        )
+      },
+      width,
+    )
+  }
+
+  /// Adds a column showing the text `value` returns for each row, wrapped at word boundaries to
+  /// the column width. Rows grow as tall as their wrapped text.
+  pub fn with_wrapped_column<H: Widget<()> + 'static>(
+    self,
+    header: H,
+    value: fn(&T) -> String,
+    width: f64,
+  ) -> Self {
+    self.with_column(header, move || WrappedText::new(value), width)
+  }
+
+  /// Adds a column of text boxes editing the text `lens` selects from each row, writing every
+  /// change back to the row. Numbers and other values can be edited through a lens converting
+  /// them to and from text, as made with `LensExt::map`.
+  pub fn with_editable_column<H: Widget<()> + 'static>(
+    self,
+    header: H,
+    lens: impl Lens<T, String> + Clone + 'static,
     width: f64,
   ) -> Self {
+    let mut table = self.with_column(header, move || TextBox::new().lens(lens.clone()), width);
+    if let Some(column) = table.columns.last_mut() {
+      column.editable = true;
+    }
+
+    table
+  }
+
+  /// Adds a column under a `header` label showing the text `value` returns for each row. Unlike
+  /// columns of widgets, it is written out by `to_csv`, with the same header and text.
+  pub fn with_column_value(
+    self,
+    header: impl Into<String>,
+    value: fn(&T) -> String,
+    width: f64,
+  ) -> Self {
+    let header = header.into();
+    let mut table = self.with_column(
+      Label::new(header.clone()),
+      move || Label::new(move |row: &T, _env: &Env| value(row)),
+      width,
+    );
+    if let Some(column) = table.columns.last_mut() {
+      column.csv = Some((header, value));
+    }
+
+    table
+  }
+
+  /// Adds a column of checkboxes showing the flag `lens` selects from each row, as for selection
+  /// or flag columns. Clicking a checkbox toggles the flag in the row.
+  pub fn with_checkbox_column<H: Widget<()> + 'static>(
+    self,
+    header: H,
+    lens: impl Lens<T, bool> + Clone + 'static,
+    width: f64,
+  ) -> Self {
+    self.with_column(header, move || Checkbox::new("").lens(lens.clone()), width)
+  }
+
+  fn add_column<H: Widget<()> + 'static, W: Widget<T> + 'static>(
+    mut self,
+    header: H,
+    closure: impl Fn(usize) -> W + 'static,
+    width: ColumnWidth,
+  ) -> Self {
+    let column = Column {
+      widget: Box::new(move |index| Box::new((closure)(index))),
+      width,
+      min_width: None,
+      max_width: None,
+      editable: false,
+      csv: None,
+    };
     self
       .headers
       .widget_mut()
-      .add_child(SizedBox::new(header).width(width));
+      .add_cell(header, column.initial_width());
+    self.columns.push(column);
+    self.widths = None;
 
-    self.columns.push(Column {
-      widget: Box::new(move || Box::new((closure)())),
-      width,
-    });
+    self
+  }
+
+  /// Writes the columns added with `with_column_value` as comma separated values: a line of
+  /// their headers, then a line for each row of `data`, in the order the table lists them.
+  /// Columns of widgets have no text to write, so they are left out.
+  pub fn to_csv(&self, data: &L) -> String {
+    let columns: Vec<&CsvColumn<T>> = self
+      .columns
+      .iter()
+      .filter_map(|column| column.csv.as_ref())
+      .collect();
+
+    let mut output = String::new();
+    csv::write_row(&mut output, columns.iter().map(|(header, _)| header));
+    data
+      .for_each(|row, _| csv::write_row(&mut output, columns.iter().map(|(_, value)| value(row))));
+
+    output
+  }
+
+  /// Adds the footer cell of the next column, in the order the columns were added. Footer cells
+  /// see the whole collection, so they can show totals or other summaries, and are laid out
+  /// below the last row.
+  ///
+  /// # Panics
+  ///
+  /// Panics if every column already has a footer cell.
+  pub fn with_footer<W: Widget<L> + 'static>(mut self, cell: W) -> Self {
+    let width = self
+      .columns
+      .get(self.footer.widget().cells.len())
+      .expect("footer cell added without a matching column")
+      .initial_width();
+
+    self.footer.widget_mut().add_cell(cell, width);
+
+    self
+  }
+
+  /// Calls `on_select` with the newly selected row, whether it was clicked or reached with the
+  /// keyboard.
+  pub fn with_on_select(
+    mut self,
+    f: impl Fn(&mut EventCtx, &mut T, usize, &Env) + 'static,
+  ) -> Self {
+    self.on_select = Some(Box::new(f));
+    self
+  }
+
+  /// Calls `on_activate` with the selected row when Enter is pressed.
+  pub fn with_on_activate(
+    mut self,
+    f: impl Fn(&mut EventCtx, &mut T, usize, &Env) + 'static,
+  ) -> Self {
+    self.on_activate = Some(Box::new(f));
+    self
+  }
+
+  /// Calls `on_header_click` with the index of the column whose header was clicked, unless the
+  /// header widget handled the click itself.
+  pub fn with_header_click(mut self, f: impl Fn(&mut EventCtx, usize) + 'static) -> Self {
+    self.on_header_click = Some(Box::new(f));
+    self
+  }
+
+  /// Makes the headers sortable: each click on a header cycles its column through ascending,
+  /// descending and unsorted, marked by a glyph in the header, and calls `on_sort` with the column
+  /// and its new state. Only one column is sorted at a time. The table doesn't reorder the rows
+  /// itself; `on_sort` should reorder the data, or restore its original order.
+  pub fn with_on_sort(mut self, f: impl Fn(&mut EventCtx, usize, SortState) + 'static) -> Self {
+    self.on_sort = Some(Box::new(f));
+    self
+  }
+
+  /// Calls `on_need_rows` with the range of visible rows when it comes within `PAGE_ROWS` rows of
+  /// the end of the data, typically as the table scrolls down, so more rows can be fetched and
+  /// appended. It isn't called again until rows are added or the end leaves the view and comes
+  /// back.
+  ///
+  /// The check happens as events reach the table and as rows are added or removed, against the
+  /// part in view when it was last painted. The callback can't change the data directly; send a
+  /// command through an `ExtEventSink` instead.
+  pub fn with_on_need_rows(mut self, f: impl Fn(Range<usize>) + 'static) -> Self {
+    self.on_need_rows = Some(Box::new(f));
+    self
+  }
+
+  /// Lets rows be dragged to a new position. A line shows where the row would land, and Escape
+  /// cancels the drag. On drop, `f` receives the index the row was dragged from and the index it
+  /// should end up at; the table doesn't own the data, so moving the row is up to `f`, typically
+  /// by submitting a command.
+  pub fn with_on_reorder(mut self, f: impl Fn(&mut EventCtx, usize, usize) + 'static) -> Self {
+    self.on_reorder = Some(Box::new(f));
+    self
+  }
 
+  /// Keeps the first `n` columns in place, in headers, rows and footer alike, while the others
+  /// scroll horizontally under them. A separator line marks where the frozen columns end.
+  pub fn with_frozen_columns(mut self, n: usize) -> Self {
+    self.frozen_columns = n;
     self
   }
 
-  fn update_child_count(&mut self, data: &impl ListIter<T>, _env: &Env) -> bool {
+  /// Makes the rows expandable: a disclosure triangle left of the columns shows or hides a widget
+  /// built by `closure` below the cells of its row. The widget sees the whole row and spans the
+  /// width of the table, staying in place when the columns scroll sideways. Rows start collapsed,
+  /// and stay expanded by index when the data changes.
+  pub fn with_full_width_row_section<W: Widget<T> + 'static>(
+    mut self,
+    closure: impl Fn() -> W + 'static,
+  ) -> Self {
+    self.section = Some(Box::new(move || Box::new((closure)())));
+    self
+  }
+
+  /// Whether the row at `index` shows its full-width section.
+  pub fn is_expanded(&self, index: usize) -> bool {
+    self.expanded.contains(&index)
+  }
+
+  /// Width taken left of the columns by the disclosure triangles, if the rows are expandable.
+  fn gutter(&self) -> f64 {
+    if self.section.is_some() {
+      DISCLOSURE_WIDTH
+    } else {
+      0.0
+    }
+  }
+
+  /// Shows or hides the full-width section of the row at `index`.
+  fn toggle_expanded(&mut self, ctx: &mut EventCtx, index: usize) {
+    if !self.expanded.remove(&index) {
+      self.expanded.insert(index);
+    }
+    ctx.request_layout();
+  }
+
+  /// Draws ▶ or ▼ in the gutter of each expandable row, whether it's collapsed or expanded.
+  fn paint_disclosures(&self, ctx: &mut PaintCtx, env: &Env) {
+    let font_size = env.get(theme::TEXT_SIZE_NORMAL) * 0.7;
+    let font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+    let color = env.get(theme::LABEL_COLOR);
+
+    for (index, child) in self.children.iter().enumerate() {
+      let glyph = if self.is_expanded(index) {
+        "\u{25bc}"
+      } else {
+        "\u{25b6}"
+      };
+      let layout = ctx
+        .text()
+        .new_text_layout(&font, glyph, f64::INFINITY)
+        .build()
+        .unwrap();
+
+      let rect = child.layout_rect();
+      let origin = Point::new(
+        (DISCLOSURE_WIDTH - layout.width()) / 2.0,
+        rect.y0 + rect.height().min(font_size * 2.0) / 2.0 + font_size * 0.334,
+      );
+      ctx.draw_text(&layout, origin, &color);
+    }
+  }
+
+  /// Index of the selected row, if any.
+  pub fn selected(&self) -> Option<usize> {
+    self.selected
+  }
+
+  /// Index of the row at `y`, in the coordinates of the table, as of its last layout. The section
+  /// of an expanded row counts as part of the row; the headers and footer are not rows.
+  pub fn row_at_y(&self, y: f64) -> Option<usize> {
+    row_at(&self.row_offsets, y)
+  }
+
+  /// The column the rows are sorted by and its direction, if any.
+  pub fn sort(&self) -> Option<(usize, SortState)> {
+    self.sort
+  }
+
+  fn toggle_sort(&mut self, ctx: &mut EventCtx, column: usize) {
+    let state = next_sort_state(self.sort, column);
+    self.sort = match state {
+      SortState::Unsorted => None,
+      _ => Some((column, state)),
+    };
+    ctx.request_paint();
+
+    if let Some(on_sort) = &self.on_sort {
+      on_sort(ctx, column, state);
+    }
+  }
+
+  fn check_need_rows(&mut self) {
+    let (on_need_rows, visible) = match (&self.on_need_rows, self.visible) {
+      (Some(on_need_rows), Some(visible)) => (on_need_rows, visible),
+      _ => return,
+    };
+
+    let range = visible_rows(
+      self.children.iter().map(|child| child.layout_rect()),
+      visible,
+    );
+    if need_rows(&mut self.need_rows_sent, &range, self.children.len()) {
+      on_need_rows(range);
+    }
+  }
+
+  /// Draws ▲ or ▼ at the right end of the header of the sorted column.
+  fn paint_sort_indicator(&self, ctx: &mut PaintCtx, env: &Env) {
+    let (column, state) = match self.sort {
+      Some(sort) => sort,
+      None => return,
+    };
+    let glyph = match state {
+      SortState::Ascending => "\u{25b2}",
+      SortState::Descending => "\u{25bc}",
+      SortState::Unsorted => return,
+    };
+
+    let headers = self.headers.widget();
+    let cell = match headers.cells.get(column) {
+      Some(cell) => cell.layout_rect(),
+      None => return,
+    };
+    let offset = self.headers.layout_rect().origin().to_vec2();
+    let clip = headers.visible_rect(column) + offset;
+
+    let font_size = env.get(theme::TEXT_SIZE_NORMAL) * 0.7;
+    let font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+    let layout = ctx
+      .text()
+      .new_text_layout(&font, glyph, f64::INFINITY)
+      .build()
+      .unwrap();
+
+    let cell = cell + offset;
+    let origin = Point::new(
+      cell.x1 - layout.width() - 4.0,
+      cell.center().y + font_size * 0.334,
+    );
+
+    ctx.with_save(|ctx| {
+      ctx.clip(clip);
+      ctx.draw_text(&layout, origin, &env.get(theme::LABEL_COLOR));
+    });
+  }
+
+  /// Paints the headers, rows and footer, before clipping to the width of the table.
+  fn paint_contents(&mut self, ctx: &mut PaintCtx, data: &L, env: &Env) {
+    self.headers.paint(ctx, &(), env);
+    self.paint_sort_indicator(ctx, env);
+
+    if let Some(child) = self.selected.and_then(|index| self.children.get(index)) {
+      let color = env.get(theme::SELECTION_COLOR);
+      let color = if ctx.is_focused() {
+        color
+      } else {
+        color.with_alpha(0.5)
+      };
+      ctx.fill(child.layout_rect(), &color);
+    }
+
+    let mut children = self.children.iter_mut();
+    let mut sections = self.sections.iter_mut();
+    let expanded = &self.expanded;
+    data.for_each(|child_data, i| {
+      if let Some(child) = children.next() {
+        child.paint(ctx, child_data, env);
+      }
+      if let Some(section) = sections.next().filter(|_| expanded.contains(&i)) {
+        section.paint(ctx, child_data, env);
+      }
+    });
+
+    if self.section.is_some() {
+      self.paint_disclosures(ctx, env);
+    }
+
+    self.footer.paint(ctx, data, env);
+
+    let gap = self.reordering.as_ref().and_then(|reorder| reorder.gap);
+    if let Some(y) = gap.and_then(|gap| self.row_offsets.get(gap)) {
+      let indicator = Line::new((self.gutter(), *y), (ctx.size().width, *y));
+      ctx.stroke(indicator, &env.get(theme::PRIMARY_LIGHT), 2.0);
+    }
+
+    if self.frozen_columns > 0 && self.frozen_columns < self.columns.len() {
+      let x = self.gutter() + self.headers.widget().frozen_width();
+      let separator = Line::new((x, 0.0), (x, ctx.size().height));
+      ctx.stroke(separator, &env.get(theme::BORDER_DARK), 1.0);
+    }
+  }
+
+  /// Scrolls the columns after the frozen ones by `delta` pixels, within the width they overflow
+  /// the table by. Returns whether they moved.
+  fn scroll_by(&mut self, ctx: &mut EventCtx, delta: f64) -> bool {
+    let scroll_x = clamp_scroll(self.scroll_x + delta, self.max_scroll_x);
+    if scroll_x == self.scroll_x {
+      return false;
+    }
+
+    self.scroll_x = scroll_x;
+    ctx.request_layout();
+    true
+  }
+
+  /// Whether `pos` lands on a cell of an editable column, in any row.
+  fn in_editable_cell(&self, pos: Point) -> bool {
+    let row = match self
+      .children
+      .iter()
+      .find(|child| child.layout_rect().contains(pos))
+    {
+      Some(row) => row,
+      None => return false,
+    };
+
+    let rect = row.layout_rect();
+    let row = row.widget();
+    let column = column_at(&row.widths, row.column_x(pos.x - rect.x0));
+    matches!(
+      column.and_then(|column| self.columns.get(column)),
+      Some(Column { editable: true, .. })
+    )
+  }
+
+  fn select(&mut self, ctx: &mut EventCtx, data: &mut L, index: usize, env: &Env) {
+    if self.selected == Some(index) {
+      return;
+    }
+
+    self.selected = Some(index);
+    ctx.request_paint();
+
+    if let Some(on_select) = &self.on_select {
+      call_with_row(on_select, ctx, data, index, env);
+    }
+  }
+
+  fn update_child_count(&mut self, data: &L, _env: &Env) -> bool {
     let len = self.children.len();
     match len.cmp(&data.data_len()) {
-      Ordering::Greater => self.children.truncate(data.data_len()),
+      Ordering::Greater => {
+        let new_len = data.data_len();
+        self.children.truncate(new_len);
+        self.sections.truncate(new_len);
+        self.expanded.retain(|index| *index < new_len);
+      }
       Ordering::Less => data.for_each(|_, i| {
         if i >= len {
-          let mut widget = Flex::row();
+          let mut widget = Row::new();
 
           for column in self.columns.iter() {
-            let child = (column.widget)();
-            widget.add_child(SizedBox::new(child).width(column.width));
+            widget.add_cell((column.widget)(i), column.initial_width());
           }
 
           self.children.push(WidgetPod::new(widget));
+
+          if let Some(section) = &self.section {
+            self.sections.push(WidgetPod::new(section()));
+          }
         }
       }),
       Ordering::Equal => (),
     }
     len != data.data_len()
   }
-}
 
-impl<C: Data, T: ListIter<C>> Widget<T> for Table<C> {
-  fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
-    let mut children = self.children.iter_mut();
-    data.for_each_mut(|child_data, _| {
-      if let Some(child) = children.next() {
-        child.event(ctx, event, child_data, env);
-      }
-    });
-  }
+  /// Resolves the width of every column, measuring the natural width of all the cells of the
+  /// auto-sized ones.
+  fn measure_widths(&mut self, ctx: &mut LayoutCtx, data: &L, env: &Env) -> Vec<f64> {
+    let mut widths: Vec<f64> = self.columns.iter().map(Column::initial_width).collect();
 
-  fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
-    if let LifeCycle::WidgetAdded = event {
-      if self.update_child_count(data, env) {
-        ctx.children_changed();
-      }
+    if !self
+      .columns
+      .iter()
+      .any(|column| matches!(column.width, ColumnWidth::Auto))
+    {
+      return widths;
     }
 
-    let mut children = self.children.iter_mut();
-    data.for_each(|child_data, _| {
-      if let Some(child) = children.next() {
-        child.lifecycle(ctx, event, child_data, env);
+    let mut grow = |natural: Vec<f64>| {
+      for (width, natural) in widths.iter_mut().zip(natural) {
+        *width = width.max(natural);
       }
-    });
-  }
+    };
+
+    grow(self.headers.widget_mut().natural_widths(ctx, &(), env));
+    grow(self.footer.widget_mut().natural_widths(ctx, data, env));
 
-  fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
-    // we send update to children first, before adding or removing children;
-    // this way we avoid sending update to newly added children, at the cost
-    // of potentially updating children that are going to be removed.
     let mut children = self.children.iter_mut();
     data.for_each(|child_data, _| {
       if let Some(child) = children.next() {
-        child.update(ctx, child_data, env);
+        grow(child.widget_mut().natural_widths(ctx, child_data, env));
       }
     });
 
-    if self.update_child_count(data, env) {
-      ctx.children_changed();
+    self
+      .columns
+      .iter()
+      .zip(widths)
+      .map(|(column, width)| match column.width {
+        ColumnWidth::Fixed(fixed) => column.bounded(fixed),
+        ColumnWidth::Auto => column.bounded(width),
+      })
+      .collect()
+  }
+}
+
+impl<T> Column<T> {
+  /// Width used before the column is measured.
+  fn initial_width(&self) -> f64 {
+    match self.width {
+      ColumnWidth::Fixed(width) => self.bounded(width),
+      ColumnWidth::Auto => self.bounded(0.0),
     }
   }
 
-  fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
-    let mut width = bc.min().width;
-    let mut y = 0.0;
-    let mut paint_rect = Rect::ZERO;
+  fn bounded(&self, width: f64) -> f64 {
+    clamp_width(width, self.min_width, self.max_width)
+  }
+}
 
-    let header_bc = BoxConstraints::new(
-      Size::new(bc.min().width, 0.0),
-      Size::new(bc.max().width, std::f64::INFINITY),
-    );
+impl RowStack {
+  fn new(x: f64) -> Self {
+    Self {
+      x,
+      y: 0.0,
+      offsets: Vec::new(),
+    }
+  }
 
-    let header_size = self.headers.layout(ctx, &header_bc, &(), env);
-    let rect = Rect::from_origin_size(Point::new(0.0, y), header_size);
-    self.headers.set_layout_rect(ctx, &(), env, rect);
-    paint_rect = paint_rect.union(self.headers.paint_rect());
-    width = width.max(header_size.width);
-    y += header_size.height;
+  /// The rect of a widget of `size` placed right below the previous one.
+  fn place(&mut self, size: Size) -> Rect {
+    let rect = Rect::from_origin_size(Point::new(self.x, self.y), size);
+    self.y += size.height;
+    rect
+  }
+
+  /// Starts a row at the bottom of the last widget placed, or ends the last row there.
+  fn mark_row(&mut self) {
+    self.offsets.push(self.y);
+  }
+}
+
+impl Resize {
+  /// Width of the column with the cursor at `x`, before the bounds of the column apply.
+  fn width_at(&self, x: f64) -> f64 {
+    (self.start_width + x - self.start_x).max(MIN_RESIZED_WIDTH)
+  }
+}
+
+impl<T: Data, L: ListIter<T>> Default for Table<T, L> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<C: Data, T: ListIter<C>> Widget<T> for Table<C, T> {
+  fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+    // Wheel events arrive before an enclosing `Scroll` moves, so the view is that of the last paint
+    self.check_need_rows();
+    self.headers.event(ctx, event, &mut (), env);
 
     let mut children = self.children.iter_mut();
-    data.for_each(|child_data, _| {
-      let child = match children.next() {
-        Some(child) => child,
+    let mut sections = self.sections.iter_mut();
+    let expanded = &self.expanded;
+    data.for_each_mut(|child_data, i| {
+      if let Some(child) = children.next() {
+        child.event(ctx, event, child_data, env);
+      }
+      // Collapsed sections aren't laid out, so they can't be hit
+      if let Some(section) = sections.next().filter(|_| expanded.contains(&i)) {
+        section.event(ctx, event, child_data, env);
+      }
+    });
+
+    self.footer.event(ctx, event, data, env);
+
+    match event {
+      Event::MouseDown(mouse) => {
+        // Editors request focus for themselves, and the last request wins
+        if !self.in_editable_cell(mouse.pos) {
+          ctx.request_focus();
+        }
+
+        let header = self.headers.layout_rect();
+        if header.contains(mouse.pos) {
+          let headers = self.headers.widget();
+          let x = headers.column_x(mouse.pos.x - header.x0);
+
+          if let Some(column) = divider_at(&headers.widths, x) {
+            self.resizing = Some(Resize {
+              column,
+              start_x: mouse.pos.x,
+              start_width: headers.widths[column],
+            });
+            ctx.set_active(true);
+            ctx.set_handled();
+            return;
+          }
+
+          let column = column_at(&headers.widths, x);
+
+          if let (Some(column), false) = (column, ctx.is_handled()) {
+            if let Some(on_header_click) = &self.on_header_click {
+              on_header_click(ctx, column);
+            }
+            if self.on_sort.is_some() {
+              self.toggle_sort(ctx, column);
+            }
+          }
+          return;
+        }
+
+        if self.section.is_some() {
+          let rows = self.children.iter().map(|child| child.layout_rect());
+          if let Some(index) = disclosure_at(rows, mouse.pos) {
+            self.toggle_expanded(ctx, index);
+            ctx.set_handled();
+            return;
+          }
+        }
+
+        let clicked = self
+          .children
+          .iter()
+          .position(|child| child.layout_rect().contains(mouse.pos));
+        if let Some(index) = clicked {
+          // Cells handling the click, such as checkboxes and editors, keep it to themselves
+          let draggable = self.on_reorder.is_some() && !self.in_editable_cell(mouse.pos);
+          if draggable && !ctx.is_handled() {
+            self.reordering = Some(Reorder {
+              from: index,
+              start_y: mouse.pos.y,
+              gap: None,
+            });
+            ctx.set_active(true);
+          }
+          self.select(ctx, data, index, env);
+        }
+      }
+      Event::MouseMove(mouse) if self.reordering.is_some() => {
+        let offsets = &self.row_offsets;
+        if let Some(reorder) = &mut self.reordering {
+          if reorder.gap.is_some() || (mouse.pos.y - reorder.start_y).abs() >= REORDER_THRESHOLD {
+            let gap = drop_gap(offsets, mouse.pos.y);
+            if reorder.gap != Some(gap) {
+              reorder.gap = Some(gap);
+              ctx.request_paint();
+            }
+          }
+        }
+      }
+      Event::MouseUp(_) if self.reordering.is_some() => {
+        let reorder = self.reordering.take();
+        ctx.set_active(false);
+        ctx.request_paint();
+
+        let len = self.children.len();
+        let moved = reorder
+          .filter(|reorder| reorder.from < len)
+          .and_then(|reorder| Some((reorder.from, dropped_index(reorder.from, reorder.gap?)?)));
+        if let (Some((from, to)), Some(on_reorder)) = (moved, &self.on_reorder) {
+          on_reorder(ctx, from, to.min(len - 1));
+        }
+        ctx.set_handled();
+      }
+      Event::KeyDown(key) if self.reordering.is_some() && key.key_code == KeyCode::Escape => {
+        self.reordering = None;
+        ctx.set_active(false);
+        ctx.request_paint();
+        ctx.set_handled();
+      }
+      Event::MouseMove(mouse) => {
+        if let Some(resize) = &self.resizing {
+          // Resized columns keep their width, even auto-sized ones
+          let column = &mut self.columns[resize.column];
+          column.width = ColumnWidth::Fixed(column.bounded(resize.width_at(mouse.pos.x)));
+          self.widths = None;
+          ctx.request_layout();
+        }
+
+        let header = self.headers.layout_rect();
+        let headers = self.headers.widget();
+        let over_divider = header.contains(mouse.pos)
+          && divider_at(&headers.widths, headers.column_x(mouse.pos.x - header.x0)).is_some();
+        if self.resizing.is_some() || over_divider {
+          ctx.set_cursor(&Cursor::ResizeLeftRight);
+        }
+      }
+      Event::MouseUp(_) if self.resizing.is_some() => {
+        self.resizing = None;
+        ctx.set_active(false);
+        ctx.set_handled();
+      }
+      Event::Wheel(wheel) if !ctx.is_handled() => {
+        // Shift turns the vertical wheel sideways, as in most applications
+        let delta = if wheel.mods.shift {
+          wheel.wheel_delta.y
+        } else {
+          wheel.wheel_delta.x
+        };
+
+        // Left unhandled when there's nothing to scroll, so an enclosing `Scroll` gets it
+        if self.scroll_by(ctx, delta) {
+          ctx.set_handled();
+        }
+      }
+      Event::KeyDown(key) if ctx.is_focused() => {
+        let len = self.children.len();
+        let target = match key.key_code {
+          KeyCode::ArrowUp => step_selection(self.selected, -1, len),
+          KeyCode::ArrowDown => step_selection(self.selected, 1, len),
+          KeyCode::PageUp => step_selection(self.selected, -(PAGE_ROWS as isize), len),
+          KeyCode::PageDown => step_selection(self.selected, PAGE_ROWS as isize, len),
+          KeyCode::ArrowLeft | KeyCode::ArrowRight => {
+            let step = if key.key_code == KeyCode::ArrowLeft {
+              -SCROLL_STEP
+            } else {
+              SCROLL_STEP
+            };
+            self.scroll_by(ctx, step);
+            ctx.set_handled();
+            return;
+          }
+          KeyCode::Return | KeyCode::NumpadEnter => {
+            if let (Some(index), Some(on_activate)) = (self.selected, &self.on_activate) {
+              call_with_row(on_activate, ctx, data, index, env);
+            }
+            ctx.set_handled();
+            return;
+          }
+          _ => return,
+        };
+
+        if let Some(index) = target {
+          self.select(ctx, data, index, env);
+        }
+        ctx.set_handled();
+      }
+      _ => (),
+    }
+  }
+
+  fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+    match event {
+      LifeCycle::WidgetAdded => {
+        ctx.register_for_focus();
+        if self.update_child_count(data, env) {
+          ctx.children_changed();
+        }
+      }
+      LifeCycle::FocusChanged(_) => ctx.request_paint(),
+      _ => (),
+    }
+
+    self.headers.lifecycle(ctx, event, &(), env);
+
+    let mut children = self.children.iter_mut();
+    let mut sections = self.sections.iter_mut();
+    data.for_each(|child_data, _| {
+      if let Some(child) = children.next() {
+        child.lifecycle(ctx, event, child_data, env);
+      }
+      if let Some(section) = sections.next() {
+        section.lifecycle(ctx, event, child_data, env);
+      }
+    });
+
+    self.footer.lifecycle(ctx, event, data, env);
+  }
+
+  fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, env: &Env) {
+    // we send update to children first, before adding or removing children;
+    // this way we avoid sending update to newly added children, at the cost
+    // of potentially updating children that are going to be removed.
+    self.headers.update(ctx, &(), env);
+
+    let mut children = self.children.iter_mut();
+    let mut sections = self.sections.iter_mut();
+    data.for_each(|child_data, _| {
+      if let Some(child) = children.next() {
+        child.update(ctx, child_data, env);
+      }
+      if let Some(section) = sections.next() {
+        section.update(ctx, child_data, env);
+      }
+    });
+
+    if self.update_child_count(data, env) {
+      ctx.children_changed();
+      self.selected = self.selected.filter(|index| *index < self.children.len());
+      self.check_need_rows();
+    }
+
+    self.footer.update(ctx, data, env);
+
+    if !old_data.same(data) {
+      self.widths = None;
+      ctx.request_layout();
+    }
+  }
+
+  fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+    if self.widths.is_none() {
+      self.widths = Some(self.measure_widths(ctx, data, env));
+    }
+
+    let widths = self.widths.clone().unwrap_or_default();
+
+    // Every row is exactly as wide as its columns, regardless of the content of its cells. Columns
+    // wider than the table scroll sideways within it.
+    let gutter = self.gutter();
+    let width: f64 = gutter + widths.iter().sum::<f64>();
+    let view_width = bc.constrain(Size::new(width, 0.0)).width;
+    self.max_scroll_x = (width - view_width).max(0.0);
+    self.scroll_x = clamp_scroll(self.scroll_x, self.max_scroll_x);
+
+    let (frozen, scroll_x) = (self.frozen_columns, self.scroll_x);
+    self.headers.widget_mut().arrange(&widths, frozen, scroll_x);
+    self.footer.widget_mut().arrange(&widths, frozen, scroll_x);
+    for child in self.children.iter_mut() {
+      child.widget_mut().arrange(&widths, frozen, scroll_x);
+    }
+
+    let mut stack = RowStack::new(gutter);
+    let mut paint_rect = Rect::ZERO;
+
+    let header_bc = BoxConstraints::new(
+      Size::new(bc.min().width, 0.0),
+      Size::new(bc.max().width, f64::INFINITY),
+    );
+
+    let header_size = self.headers.layout(ctx, &header_bc, &(), env);
+    self
+      .headers
+      .set_layout_rect(ctx, &(), env, stack.place(header_size));
+    paint_rect = paint_rect.union(self.headers.paint_rect());
+
+    // Sections span the part of the table in view, right of the disclosure triangles
+    let section_width = (view_width - gutter).max(0.0);
+    let section_bc = BoxConstraints::new(
+      Size::new(section_width, 0.0),
+      Size::new(section_width, f64::INFINITY),
+    );
+
+    let mut children = self.children.iter_mut();
+    let mut sections = self.sections.iter_mut();
+    let expanded = &self.expanded;
+    data.for_each(|child_data, i| {
+      let child = match children.next() {
+        Some(child) => child,
         None => {
           return;
         }
       };
+      stack.mark_row();
       let child_bc = BoxConstraints::new(
         Size::new(bc.min().width, 0.0),
-        Size::new(bc.max().width, std::f64::INFINITY),
+        Size::new(bc.max().width, f64::INFINITY),
       );
       let child_size = child.layout(ctx, &child_bc, child_data, env);
-      let rect = Rect::from_origin_size(Point::new(0.0, y), child_size);
-      child.set_layout_rect(ctx, child_data, env, rect);
+      child.set_layout_rect(ctx, child_data, env, stack.place(child_size));
       paint_rect = paint_rect.union(child.paint_rect());
-      width = width.max(child_size.width);
-      y += child_size.height;
+
+      if let Some(section) = sections.next().filter(|_| expanded.contains(&i)) {
+        let section_size = section.layout(ctx, &section_bc, child_data, env);
+        section.set_layout_rect(ctx, child_data, env, stack.place(section_size));
+        paint_rect = paint_rect.union(section.paint_rect());
+      }
     });
+    stack.mark_row();
+
+    let footer_size = self.footer.layout(ctx, &header_bc, data, env);
+    self
+      .footer
+      .set_layout_rect(ctx, data, env, stack.place(footer_size));
+    paint_rect = paint_rect.union(self.footer.paint_rect());
 
-    let my_size = bc.constrain(Size::new(width, y));
+    let my_size = bc.constrain(Size::new(width, stack.y));
+    self.row_offsets = stack.offsets;
+
+    // Whatever overflows sideways is clipped when painting
+    paint_rect.x0 = paint_rect.x0.max(0.0);
+    paint_rect.x1 = paint_rect.x1.min(my_size.width);
     let insets = paint_rect - Rect::ZERO.with_size(my_size);
     ctx.set_paint_insets(insets);
     my_size
   }
 
   fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
-    self.headers.paint(ctx, &(), env);
+    let region = ctx.region().to_rect();
+    let clip = Rect::new(
+      region.x0.max(0.0),
+      region.y0,
+      region.x1.min(ctx.size().width),
+      region.y1,
+    );
+    ctx.with_save(|ctx| {
+      ctx.clip(clip);
+      self.paint_contents(ctx, data, env);
+    });
 
-    let mut children = self.children.iter_mut();
-    data.for_each(|child_data, _| {
-      if let Some(child) = children.next() {
-        child.paint(ctx, child_data, env);
+    // Inside a `Scroll`, the region is the part of the table in view
+    self.visible = Some(region);
+  }
+}
+
+impl<T> Row<T> {
+  fn new() -> Self {
+    Self {
+      cells: Vec::new(),
+      widths: Vec::new(),
+      frozen: 0,
+      scroll_x: 0.0,
+    }
+  }
+
+  fn arrange(&mut self, widths: &[f64], frozen: usize, scroll_x: f64) {
+    self.widths = widths.to_vec();
+    self.frozen = frozen;
+    self.scroll_x = scroll_x;
+  }
+
+  /// Width of the columns that don't scroll.
+  fn frozen_width(&self) -> f64 {
+    self.widths.iter().take(self.frozen).sum()
+  }
+
+  /// Converts `x`, measured from the left edge of the row, to a position along the columns laid
+  /// end to end, undoing the scroll past the frozen columns.
+  fn column_x(&self, x: f64) -> f64 {
+    let frozen_width = self.frozen_width();
+    if x < frozen_width {
+      x
+    } else {
+      x + self.scroll_x
+    }
+  }
+
+  /// Part of the cell at `index` left visible: scrolled cells disappear under the frozen ones.
+  fn visible_rect(&self, index: usize) -> Rect {
+    let mut rect = self.cells[index].layout_rect();
+    if index >= self.frozen {
+      rect.x0 = rect.x0.max(self.frozen_width());
+    }
+    rect
+  }
+
+  fn add_cell(&mut self, cell: impl Widget<T> + 'static, width: f64) {
+    self.cells.push(WidgetPod::new(Box::new(cell)));
+    self.widths.push(width);
+  }
+
+  /// Lays out every cell without width constraints and reports how wide each one wants to be.
+  fn natural_widths(&mut self, ctx: &mut LayoutCtx, data: &T, env: &Env) -> Vec<f64>
+  where
+    T: Data,
+  {
+    let bc = BoxConstraints::new(Size::ZERO, Size::new(f64::INFINITY, f64::INFINITY));
+
+    self
+      .cells
+      .iter_mut()
+      .map(|cell| cell.layout(ctx, &bc, data, env).width)
+      .collect()
+  }
+}
+
+impl<T: Data> Widget<T> for Row<T> {
+  fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+    for cell in self.cells.iter_mut() {
+      cell.event(ctx, event, data, env);
+    }
+  }
+
+  fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+    for cell in self.cells.iter_mut() {
+      cell.lifecycle(ctx, event, data, env);
+    }
+  }
+
+  fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
+    for cell in self.cells.iter_mut() {
+      cell.update(ctx, data, env);
+    }
+  }
+
+  fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
+    let sizes: Vec<Size> = self
+      .cells
+      .iter_mut()
+      .zip(self.widths.iter())
+      .map(|(cell, width)| {
+        let cell_bc =
+          BoxConstraints::new(Size::new(*width, 0.0), Size::new(*width, bc.max().height));
+        cell.layout(ctx, &cell_bc, data, env)
+      })
+      .collect();
+
+    let mut rects = cell_rects(&self.widths, &sizes);
+    for rect in rects.iter_mut().skip(self.frozen) {
+      *rect = *rect - Vec2::new(self.scroll_x, 0.0);
+    }
+    for (cell, rect) in self.cells.iter_mut().zip(rects.iter()) {
+      cell.set_layout_rect(ctx, data, env, *rect);
+    }
+
+    let height = rects.iter().map(|rect| rect.height()).fold(0.0, f64::max);
+    Size::new(self.widths.iter().sum(), height)
+  }
+
+  fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
+    for index in 0..self.cells.len() {
+      let clip = self.visible_rect(index);
+      let cell = &mut self.cells[index];
+
+      ctx.with_save(|ctx| {
+        ctx.clip(clip);
+        cell.paint(ctx, data, env);
+      });
+    }
+  }
+}
+
+/// Rows shown in the order of a comparator, leaving the list that stores them untouched. A table
+/// over the view lists the rows sorted, and edits made through it reach the stored rows.
+///
+/// The order is kept up to date as the rows change, whether through `set_rows` or the table.
+#[derive(Clone)]
+pub struct SortedView<T> {
+  rows: Arc<Vec<T>>,
+  /// Index in `rows` of each row, in the order shown.
+  order: Arc<Vec<usize>>,
+  compare: Option<Comparator<T>>,
+}
+
+impl<T> SortedView<T> {
+  /// Shows `rows` in their stored order until a comparator is set.
+  pub fn new(rows: Arc<Vec<T>>) -> Self {
+    let order = Arc::new((0..rows.len()).collect());
+    Self {
+      rows,
+      order,
+      compare: None,
+    }
+  }
+
+  /// Shows the rows in the order of `compare`. Rows comparing equal keep their stored order.
+  pub fn sort_by(&mut self, compare: impl Fn(&T, &T) -> Ordering + 'static) {
+    self.compare = Some(Arc::new(compare));
+    self.sort();
+  }
+
+  /// Goes back to showing the rows in their stored order.
+  pub fn clear_sort(&mut self) {
+    self.compare = None;
+    self.sort();
+  }
+
+  /// The rows, in their stored order.
+  pub fn rows(&self) -> &Arc<Vec<T>> {
+    &self.rows
+  }
+
+  /// Replaces the rows, sorting the new ones.
+  pub fn set_rows(&mut self, rows: Arc<Vec<T>>) {
+    self.rows = rows;
+    self.sort();
+  }
+
+  /// Index in the stored rows of the row shown at `index`, as given to the table callbacks.
+  pub fn source_index(&self, index: usize) -> Option<usize> {
+    self.order.get(index).copied()
+  }
+
+  fn sort(&mut self) {
+    let mut order: Vec<usize> = (0..self.rows.len()).collect();
+    if let Some(compare) = &self.compare {
+      let rows = &self.rows;
+      order.sort_by(|a, b| compare(&rows[*a], &rows[*b]));
+    }
+    self.order = Arc::new(order);
+  }
+}
+
+impl<T: Data> Data for SortedView<T> {
+  fn same(&self, other: &Self) -> bool {
+    let same_compare = match (&self.compare, &other.compare) {
+      (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+      (None, None) => true,
+      _ => false,
+    };
+
+    same_compare && self.rows.same(&other.rows) && self.order == other.order
+  }
+}
+
+impl<T: Data> ListIter<T> for SortedView<T> {
+  fn for_each(&self, mut cb: impl FnMut(&T, usize)) {
+    for (i, index) in self.order.iter().enumerate() {
+      cb(&self.rows[*index], i);
+    }
+  }
+
+  fn for_each_mut(&mut self, mut cb: impl FnMut(&mut T, usize)) {
+    let mut rows: Option<Vec<T>> = None;
+
+    for (i, index) in self.order.iter().enumerate() {
+      let row = &self.rows[*index];
+      let mut edited = row.to_owned();
+      cb(&mut edited, i);
+
+      if !row.same(&edited) {
+        rows.get_or_insert_with(|| self.rows.to_vec())[*index] = edited;
+      }
+    }
+
+    // An edit may change where the row belongs
+    if let Some(rows) = rows {
+      self.set_rows(Arc::new(rows));
+    }
+  }
+
+  fn data_len(&self) -> usize {
+    self.order.len()
+  }
+}
+
+/// Text split into as many lines as it takes to fit the width it's laid out at.
+struct WrappedText<T> {
+  value: fn(&T) -> String,
+  text: String,
+  lines: Vec<String>,
+}
+
+impl<T> WrappedText<T> {
+  fn new(value: fn(&T) -> String) -> Self {
+    Self {
+      value,
+      text: String::new(),
+      lines: Vec::new(),
+    }
+  }
+}
+
+impl<T: Data> Widget<T> for WrappedText<T> {
+  fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut T, _env: &Env) {}
+
+  fn lifecycle(&mut self, _ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, _env: &Env) {
+    if let LifeCycle::WidgetAdded = event {
+      self.text = (self.value)(data);
+    }
+  }
+
+  fn update(&mut self, ctx: &mut UpdateCtx, old_data: &T, data: &T, _env: &Env) {
+    if !old_data.same(data) {
+      let text = (self.value)(data);
+      if text != self.text {
+        self.text = text;
+        ctx.request_layout();
+      }
+    }
+  }
+
+  fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &T, env: &Env) -> Size {
+    let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+    let mut text = ctx.text();
+    let font = text
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+    let mut measure = |line: &str| {
+      text
+        .new_text_layout(&font, line, f64::INFINITY)
+        .build()
+        .map_or(0.0, |layout| layout.width())
+    };
+
+    let max_width = bc.max().width - 2.0 * TEXT_X_PADDING;
+    self.lines = wrap_lines(&self.text, max_width, &mut measure)
+      .into_iter()
+      .map(String::from)
+      .collect();
+
+    let width = self
+      .lines
+      .iter()
+      .map(|line| measure(line))
+      .fold(0.0, f64::max);
+    let line_count = self.lines.len().max(1) as f64;
+
+    bc.constrain(Size::new(
+      width + 2.0 * TEXT_X_PADDING,
+      line_count * font_size * LINE_HEIGHT_FACTOR,
+    ))
+  }
+
+  fn paint(&mut self, ctx: &mut PaintCtx, _data: &T, env: &Env) {
+    let font_size = env.get(theme::TEXT_SIZE_NORMAL);
+    let line_height = font_size * LINE_HEIGHT_FACTOR;
+    let color = env.get(theme::LABEL_COLOR);
+    let font = ctx
+      .text()
+      .new_font_by_name(env.get(theme::FONT_NAME), font_size)
+      .build()
+      .unwrap();
+
+    for (index, line) in self.lines.iter().enumerate() {
+      let layout = ctx
+        .text()
+        .new_text_layout(&font, line, f64::INFINITY)
+        .build()
+        .unwrap();
+      let baseline = line_height * (index as f64 + 0.8);
+      ctx.draw_text(&layout, (TEXT_X_PADDING, baseline), &color);
+    }
+  }
+}
+
+/// Splits `text` into lines no wider than `max_width`, as reported by `measure`, breaking at
+/// whitespace and at line breaks. A word too wide for a line of its own is left whole, and empty
+/// text has no lines.
+fn wrap_lines<'a>(
+  text: &'a str,
+  max_width: f64,
+  measure: &mut impl FnMut(&str) -> f64,
+) -> Vec<&'a str> {
+  let mut lines = Vec::new();
+
+  for paragraph in text.lines() {
+    let mut start: Option<usize> = None;
+    let mut end = 0;
+
+    for (offset, word) in paragraph.split_whitespace().map(|word| {
+      let offset = word.as_ptr() as usize - paragraph.as_ptr() as usize;
+      (offset, word)
+    }) {
+      let word_end = offset + word.len();
+      match start {
+        None => start = Some(offset),
+        Some(line_start) if measure(&paragraph[line_start..word_end]) > max_width => {
+          lines.push(&paragraph[line_start..end]);
+          start = Some(offset);
+        }
+        Some(_) => (),
+      }
+      end = word_end;
+    }
+
+    match start {
+      Some(line_start) => lines.push(&paragraph[line_start..end]),
+      None => lines.push(""),
+    }
+  }
+
+  lines
+}
+
+fn call_with_row<T: Data, L: ListIter<T>>(
+  callback: &RowCallback<T>,
+  ctx: &mut EventCtx,
+  data: &mut L,
+  index: usize,
+  env: &Env,
+) {
+  data.for_each_mut(|row, i| {
+    if i == index {
+      callback(ctx, row, i, env);
+    }
+  });
+}
+
+/// State of `column` after a click on its header, cycling ascending, descending and unsorted. Any
+/// other column starts over at ascending.
+fn next_sort_state(current: Option<(usize, SortState)>, column: usize) -> SortState {
+  match current {
+    Some((sorted, SortState::Ascending)) if sorted == column => SortState::Descending,
+    Some((sorted, SortState::Descending)) if sorted == column => SortState::Unsorted,
+    _ => SortState::Ascending,
+  }
+}
+
+/// Range of the rows whose rects overlap `visible`, assuming they're stacked top to bottom.
+fn visible_rows(rects: impl Iterator<Item = Rect>, visible: Rect) -> Range<usize> {
+  let mut start = None;
+  let mut end = 0;
+
+  for (index, rect) in rects.enumerate() {
+    if rect.y1 > visible.y0 && rect.y0 < visible.y1 {
+      start = start.or(Some(index));
+      end = index + 1;
+    }
+  }
+
+  start.unwrap_or(end)..end
+}
+
+/// Whether `visible` rows newly come within `PAGE_ROWS` of the last of `len` rows: since `sent`
+/// last recorded the number of rows, rows were added or removed, or the end left the view.
+fn need_rows(sent: &mut Option<usize>, visible: &Range<usize>, len: usize) -> bool {
+  if visible.end + PAGE_ROWS < len {
+    *sent = None;
+    return false;
+  }
+  if *sent == Some(len) {
+    return false;
+  }
+  *sent = Some(len);
+  true
+}
+
+/// Finds the row whose disclosure triangle is under `pos`, given the rects of the rows stacked top
+/// to bottom.
+fn disclosure_at(rows: impl Iterator<Item = Rect>, pos: Point) -> Option<usize> {
+  if pos.x < 0.0 || pos.x >= DISCLOSURE_WIDTH {
+    return None;
+  }
+
+  rows
+    .enumerate()
+    .find(|(_, rect)| rect.y0 <= pos.y && pos.y < rect.y1)
+    .map(|(index, _)| index)
+}
+
+/// Moves the selection by `delta` rows, stopping at the first and last ones. Without a selection,
+/// moving down starts at the first row and moving up at the last.
+fn step_selection(selected: Option<usize>, delta: isize, len: usize) -> Option<usize> {
+  if len == 0 {
+    return None;
+  }
+
+  let target = match selected {
+    Some(index) => index as isize + delta,
+    None if delta > 0 => 0,
+    None => len as isize - 1,
+  };
+
+  Some(target.max(0).min(len as isize - 1) as usize)
+}
+
+/// Keeps `width` within the optional bounds, the minimum winning when they cross.
+fn clamp_width(width: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+  let width = max.map_or(width, |max| width.min(max));
+  min.map_or(width, |min| width.max(min))
+}
+
+/// Keeps a horizontal scroll offset between the first column and `max`.
+fn clamp_scroll(scroll_x: f64, max: f64) -> f64 {
+  scroll_x.min(max).max(0.0)
+}
+
+/// Finds the column under `x`, measured from the left edge of the first column.
+fn column_at(widths: &[f64], x: f64) -> Option<usize> {
+  if x < 0.0 {
+    return None;
+  }
+
+  let mut right = 0.0;
+  widths.iter().position(|width| {
+    right += width;
+    x < right
+  })
+}
+
+/// Finds the row spanning `y`, given the top of every row followed by the bottom of the last one.
+fn row_at(offsets: &[f64], y: f64) -> Option<usize> {
+  offsets.windows(2).position(|row| row[0] <= y && y < row[1])
+}
+
+/// Finds the gap between rows closest to `y`, from 0 above the first row to the number of rows
+/// below the last one, given the offsets of `row_at`. Past the first or last row, the drop goes to
+/// that end.
+fn drop_gap(offsets: &[f64], y: f64) -> usize {
+  match row_at(offsets, y) {
+    Some(row) if y >= (offsets[row] + offsets[row + 1]) / 2.0 => row + 1,
+    Some(row) => row,
+    None => match offsets.last() {
+      Some(bottom) if y >= *bottom => offsets.len() - 1,
+      _ => 0,
+    },
+  }
+}
+
+/// Index a row dragged from `from` ends up at once dropped in `gap`, `None` when it stays put.
+fn dropped_index(from: usize, gap: usize) -> Option<usize> {
+  let to = if gap > from { gap - 1 } else { gap };
+  if to == from {
+    None
+  } else {
+    Some(to)
+  }
+}
+
+/// Finds the column whose right edge is within `DIVIDER_TOLERANCE` of `x`, measured from the left
+/// edge of the first column.
+fn divider_at(widths: &[f64], x: f64) -> Option<usize> {
+  let mut right = 0.0;
+  widths.iter().position(|width| {
+    right += width;
+    (x - right).abs() <= DIVIDER_TOLERANCE
+  })
+}
+
+/// Places cells side by side at the widths of their columns, ignoring how wide their content
+/// measured. Every cell takes the height of the tallest one.
+fn cell_rects(widths: &[f64], sizes: &[Size]) -> Vec<Rect> {
+  let height = sizes.iter().map(|size| size.height).fold(0.0, f64::max);
+  let mut x = 0.0;
+
+  widths
+    .iter()
+    .map(|width| {
+      let rect = Rect::new(x, 0.0, x + width, height);
+      x += width;
+      rect
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[derive(Clone, Data)]
+  struct Person {
+    first_name: String,
+    last_name: String,
+    phone: String,
+    notes: String,
+  }
+
+  #[test]
+  fn test_to_csv() {
+    let person = |first: &str, last: &str, phone: &str, notes: &str| Person {
+      first_name: first.to_string(),
+      last_name: last.to_string(),
+      phone: phone.to_string(),
+      notes: notes.to_string(),
+    };
+    let people = Arc::new(vec![
+      person("John", "Doe", "555-1234", "Mornings, never on weekends"),
+      person("Jane", "Smith", "555-0000", ""),
+      person("Jane", "Smith", "555-0000", "Says \"hi\"\nfirst"),
+    ]);
+
+    let table: Table<Person, Arc<Vec<Person>>> = Table::new()
+      .with_indexed_column(
+        Label::new("#"),
+        |index| Label::new(format!("{}", index + 1)),
+        40.0,
+      )
+      .with_column_value("First Name", |p: &Person| p.first_name.clone(), 200.0)
+      .with_column_value("Last Name", |p: &Person| p.last_name.clone(), 200.0)
+      .with_column_value("Phone Number", |p: &Person| p.phone.clone(), 120.0)
+      .with_column_value("Notes", |p: &Person| p.notes.clone(), 160.0);
+
+    // The widget column has no text and is left out
+    assert_eq!(
+      table.to_csv(&people),
+      "First Name,Last Name,Phone Number,Notes\r\n\
+       John,Doe,555-1234,\"Mornings, never on weekends\"\r\n\
+       Jane,Smith,555-0000,\r\n\
+       Jane,Smith,555-0000,\"Says \"\"hi\"\"\nfirst\"\r\n"
+    );
+  }
+
+  #[test]
+  fn test_sorted_view() {
+    let mut view = SortedView::new(Arc::new(vec![30, 10, 20]));
+    view.sort_by(|a: &i32, b: &i32| a.cmp(b));
+
+    let mut shown = Vec::new();
+    view.for_each(|row, _| shown.push(*row));
+    assert_eq!(shown, vec![10, 20, 30]);
+    assert_eq!(view.source_index(0), Some(1));
+
+    // Edits land on the stored row, then the order follows the new value
+    view.for_each_mut(|row, i| {
+      if i == 0 {
+        *row = 40;
       }
     });
+    assert_eq!(**view.rows(), vec![30, 40, 20]);
+    let mut shown = Vec::new();
+    view.for_each(|row, _| shown.push(*row));
+    assert_eq!(shown, vec![20, 30, 40]);
+
+    view.set_rows(Arc::new(vec![5, 1]));
+    assert_eq!(view.data_len(), 2);
+    assert_eq!(view.source_index(0), Some(1));
+
+    view.clear_sort();
+    assert_eq!(view.source_index(0), Some(0));
+  }
+
+  #[test]
+  fn test_overlong_cell_keeps_columns_aligned() {
+    let widths = [200.0, 200.0, 100.0];
+
+    let header = cell_rects(&widths, &[Size::new(80.0, 20.0); 3]);
+    let body = cell_rects(
+      &widths,
+      &[
+        Size::new(80.0, 20.0),
+        // A long unbroken label, measured far wider than its column
+        Size::new(950.0, 20.0),
+        Size::new(60.0, 40.0),
+      ],
+    );
+
+    for (header, body) in header.iter().zip(body.iter()) {
+      assert_eq!(header.x0, body.x0);
+      assert_eq!(header.x1, body.x1);
+    }
+    assert_eq!(body.last().unwrap().x1, 500.0);
+    assert!(body.iter().all(|rect| rect.height() == 40.0));
+  }
+
+  #[test]
+  fn test_wrapped_cell_grows_row() {
+    // Ten pixels per character
+    let mut measure = |text: &str| text.len() as f64 * 10.0;
+
+    let lines = wrap_lines("one two three  four\nfive", 90.0, &mut measure);
+    assert_eq!(lines, vec!["one two", "three", "four", "five"]);
+    assert!(wrap_lines("", 90.0, &mut measure).is_empty());
+
+    // The wrapped cell sets the height of its row, so the next row starts below its last line
+    let line_height = 20.0;
+    let wrapped = Size::new(90.0, lines.len() as f64 * line_height);
+    let rects = cell_rects(&[100.0, 100.0], &[Size::new(40.0, line_height), wrapped]);
+    assert_eq!(rects[0].height(), 4.0 * line_height);
+
+    // Stacked as the table lays out its rows, under a header
+    let row_height = rects.iter().map(|rect| rect.height()).fold(0.0, f64::max);
+    let mut stack = RowStack::new(DISCLOSURE_WIDTH);
+    stack.place(Size::new(200.0, 30.0));
+    stack.mark_row();
+    let wrapped_row = stack.place(Size::new(200.0, row_height));
+    stack.mark_row();
+    let next_row = stack.place(Size::new(200.0, line_height));
+    stack.mark_row();
+
+    // The selection is painted over the rect of the row, as tall as its wrapped cell
+    assert_eq!(wrapped_row.height(), 4.0 * line_height);
+    assert!(stack.offsets[1] >= wrapped_row.y1);
+    assert_eq!(next_row.y0, stack.offsets[1]);
+    assert_eq!(row_at(&stack.offsets, wrapped_row.y1 - 1.0), Some(0));
+    assert_eq!(row_at(&stack.offsets, wrapped_row.y1), Some(1));
+  }
+
+  #[test]
+  fn test_column_at() {
+    let widths = [100.0, 50.0, 80.0];
+
+    assert_eq!(column_at(&widths, 0.0), Some(0));
+    assert_eq!(column_at(&widths, 99.0), Some(0));
+    assert_eq!(column_at(&widths, 100.0), Some(1));
+    assert_eq!(column_at(&widths, 229.0), Some(2));
+    assert_eq!(column_at(&widths, 230.0), None);
+    assert_eq!(column_at(&widths, -1.0), None);
+
+    assert_eq!(divider_at(&widths, 50.0), None);
+    assert_eq!(divider_at(&widths, 97.0), Some(0));
+    assert_eq!(divider_at(&widths, 153.0), Some(1));
+    assert_eq!(divider_at(&widths, 230.0), Some(2));
+  }
+
+  #[test]
+  fn test_row_at() {
+    // Rows of 20, 60 and 20 pixels below a 30 pixel header
+    let offsets = [30.0, 50.0, 110.0, 130.0];
+
+    assert_eq!(row_at(&offsets, 10.0), None);
+    assert_eq!(row_at(&offsets, 30.0), Some(0));
+    assert_eq!(row_at(&offsets, 49.9), Some(0));
+    assert_eq!(row_at(&offsets, 50.0), Some(1));
+    assert_eq!(row_at(&offsets, 109.0), Some(1));
+    assert_eq!(row_at(&offsets, 129.0), Some(2));
+    assert_eq!(row_at(&offsets, 130.0), None);
+
+    // No rows, or none laid out yet
+    assert_eq!(row_at(&[30.0], 30.0), None);
+    assert_eq!(row_at(&[], 0.0), None);
+  }
+
+  #[test]
+  fn test_drop_gap() {
+    let offsets = [30.0, 50.0, 110.0, 130.0];
+
+    assert_eq!(drop_gap(&offsets, 35.0), 0);
+    assert_eq!(drop_gap(&offsets, 45.0), 1);
+    assert_eq!(drop_gap(&offsets, 100.0), 2);
+    assert_eq!(drop_gap(&offsets, 125.0), 3);
+    // Dragged above the first row or below the last one
+    assert_eq!(drop_gap(&offsets, 10.0), 0);
+    assert_eq!(drop_gap(&offsets, -50.0), 0);
+    assert_eq!(drop_gap(&offsets, 500.0), 3);
+
+    // Row 1 dropped above row 0, below row 2, or right back where it was
+    assert_eq!(dropped_index(1, 0), Some(0));
+    assert_eq!(dropped_index(1, 3), Some(2));
+    assert_eq!(dropped_index(1, 1), None);
+    assert_eq!(dropped_index(1, 2), None);
+  }
+
+  #[test]
+  fn test_resize_within_bounds() {
+    let resize = Resize {
+      column: 0,
+      start_x: 300.0,
+      start_width: 120.0,
+    };
+    let (min, max) = (Some(80.0), Some(200.0));
+
+    assert_eq!(clamp_width(resize.width_at(330.0), min, max), 150.0);
+    // Dragged far left, below the minimum of the column
+    assert_eq!(clamp_width(resize.width_at(100.0), min, max), 80.0);
+    assert_eq!(clamp_width(resize.width_at(600.0), min, max), 200.0);
+    // Without bounds, columns still don't collapse to nothing
+    assert_eq!(
+      clamp_width(resize.width_at(100.0), None, None),
+      MIN_RESIZED_WIDTH
+    );
+    assert_eq!(clamp_width(50.0, Some(80.0), Some(60.0)), 80.0);
+  }
+
+  #[test]
+  fn test_horizontal_scroll() {
+    assert_eq!(clamp_scroll(-10.0, 80.0), 0.0);
+    assert_eq!(clamp_scroll(50.0, 80.0), 50.0);
+    assert_eq!(clamp_scroll(120.0, 80.0), 80.0);
+    // Columns that fit don't scroll at all
+    assert_eq!(clamp_scroll(10.0, 0.0), 0.0);
+  }
+
+  #[test]
+  fn test_next_sort_state() {
+    use SortState::*;
+
+    assert_eq!(next_sort_state(None, 1), Ascending);
+    assert_eq!(next_sort_state(Some((1, Ascending)), 1), Descending);
+    assert_eq!(next_sort_state(Some((1, Descending)), 1), Unsorted);
+    // Clicking another column moves the sort to it
+    assert_eq!(next_sort_state(Some((1, Descending)), 2), Ascending);
+  }
+
+  #[test]
+  fn test_disclosure_at() {
+    // The second row is expanded, so the third one starts below its section
+    let rows = [
+      Rect::new(DISCLOSURE_WIDTH, 0.0, 300.0, 20.0),
+      Rect::new(DISCLOSURE_WIDTH, 20.0, 300.0, 40.0),
+      Rect::new(DISCLOSURE_WIDTH, 100.0, 300.0, 120.0),
+    ];
+
+    assert_eq!(
+      disclosure_at(rows.iter().copied(), Point::new(8.0, 30.0)),
+      Some(1)
+    );
+    assert_eq!(
+      disclosure_at(rows.iter().copied(), Point::new(8.0, 110.0)),
+      Some(2)
+    );
+    // Over the section of the second row, or over the cells
+    assert_eq!(
+      disclosure_at(rows.iter().copied(), Point::new(8.0, 70.0)),
+      None
+    );
+    assert_eq!(
+      disclosure_at(rows.iter().copied(), Point::new(50.0, 30.0)),
+      None
+    );
+  }
+
+  #[test]
+  fn test_step_selection() {
+    assert_eq!(step_selection(None, 1, 0), None);
+    assert_eq!(step_selection(None, 1, 5), Some(0));
+    assert_eq!(step_selection(None, -1, 5), Some(4));
+    assert_eq!(step_selection(Some(2), -1, 5), Some(1));
+    assert_eq!(step_selection(Some(2), 10, 5), Some(4));
+    assert_eq!(step_selection(Some(2), -10, 5), Some(0));
+  }
+
+  #[test]
+  fn test_need_rows() {
+    // Rows 20 pixels tall, the view showing five of them
+    let rows =
+      |len: usize| (0..len).map(|i| Rect::new(0.0, i as f64 * 20.0, 100.0, (i + 1) as f64 * 20.0));
+    let view = |top: f64| Rect::new(0.0, top, 100.0, top + 100.0);
+    let mut sent = None;
+
+    // Far from the end
+    assert_eq!(visible_rows(rows(30), view(0.0)), 0..5);
+    assert!(!need_rows(&mut sent, &(0..5), 30));
+
+    // Reaching the end calls once, however much it scrolls near it
+    assert!(need_rows(
+      &mut sent,
+      &visible_rows(rows(30), view(300.0)),
+      30
+    ));
+    assert!(!need_rows(
+      &mut sent,
+      &visible_rows(rows(30), view(310.0)),
+      30
+    ));
+    assert!(!need_rows(&mut sent, &(20..25), 30));
+
+    // Appended rows still within reach call again
+    assert!(need_rows(&mut sent, &(20..25), 33));
+
+    // Leaving the end and coming back calls again
+    assert!(!need_rows(&mut sent, &(0..5), 33));
+    assert!(need_rows(&mut sent, &(25..30), 33));
   }
 }